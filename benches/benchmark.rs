@@ -1,5 +1,3 @@
-use aes_wasm::*;
-
 use aegis::aegis128l::Aegis128L;
 use aegis::aegis256::Aegis256;
 use aes::cipher::{KeyIvInit, StreamCipher};
@@ -43,7 +41,7 @@ fn test_aes128ctr_rust(m: &mut [u8]) {
 }
 
 fn test_aes128cbc(m: &mut [u8]) {
-    use aes128cbc::*;
+    use aes_wasm::aes128cbc::*;
     let key = Key::default();
     let iv = IV::default();
     black_box(encrypt(m, &key, iv));
@@ -84,56 +82,56 @@ fn test_aes256cbc_rust(m: &mut [u8]) {
 }
 
 fn test_aes256cbc(m: &mut [u8]) {
-    use aes256cbc::*;
+    use aes_wasm::aes256cbc::*;
     let key = Key::default();
     let iv = IV::default();
     black_box(encrypt(m, &key, iv));
 }
 
 fn test_aes128gcm(m: &mut [u8]) {
-    use aes128gcm::*;
+    use aes_wasm::aes128gcm::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aes128ocb(m: &mut [u8]) {
-    use aes128ocb::*;
+    use aes_wasm::aes128ocb::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aegis128l(m: &mut [u8]) {
-    use aegis128l::*;
+    use aes_wasm::aegis128l::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aegis128x2(m: &mut [u8]) {
-    use aegis128x2::*;
+    use aes_wasm::aegis128x2::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aegis128x4(m: &mut [u8]) {
-    use aegis128x4::*;
+    use aes_wasm::aegis128x4::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aes256gcm(m: &mut [u8]) {
-    use aes256gcm::*;
+    use aes_wasm::aes256gcm::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aes256ocb(m: &mut [u8]) {
-    use aes256ocb::*;
+    use aes_wasm::aes256ocb::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
@@ -147,35 +145,35 @@ fn test_aegis256_rust(m: &mut [u8]) {
 }
 
 fn test_aegis256(m: &mut [u8]) {
-    use aegis256::*;
+    use aes_wasm::aegis256::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aegis256x2(m: &mut [u8]) {
-    use aegis256x2::*;
+    use aes_wasm::aegis256x2::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aegis256x4(m: &mut [u8]) {
-    use aegis256x4::*;
+    use aes_wasm::aegis256x4::*;
     let key = Key::default();
     let nonce = Nonce::default();
     black_box(encrypt_detached(m, [], &key, nonce));
 }
 
 fn test_aes128ctr(m: &mut [u8]) {
-    use aes128ctr::*;
+    use aes_wasm::aes128ctr::*;
     let key = Key::default();
     let iv = IV::default();
     black_box(encrypt(m, &key, iv));
 }
 
 fn test_cmac_aes128(m: &mut [u8]) {
-    use cmac_aes128::*;
+    use aes_wasm::cmac_aes128::*;
     let key = Key::default();
     black_box(mac(m, &key));
 }
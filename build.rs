@@ -1,7 +1,93 @@
 use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The Zig release `wasm-libs/build.zig` is written for.
+const ZIG_VERSION: &str = "0.14";
+
+const OPTIMIZE_MODES: [&str; 4] = ["Debug", "ReleaseSafe", "ReleaseFast", "ReleaseSmall"];
 
 pub fn main() {
     let src_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    println!("cargo:rustc-link-lib=static=aes");
-    println!("cargo:rustc-link-search=native={}/wasm-libs", src_dir);
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap();
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+    let pure_rust = env::var_os("CARGO_FEATURE_PURE_RUST").is_some();
+
+    println!("cargo:rustc-check-cfg=cfg(zig_backend)");
+
+    // The Zig library is built for wasm32-wasi, but doesn't import any WASI functions, so that it
+    // can also be linked on wasm32-unknown-unknown.
+    // Other targets, and the `pure-rust` feature, use the Rust implementation of the same functions.
+    if target_arch == "wasm32" && (target_os == "wasi" || target_os == "unknown") && !pure_rust {
+        println!("cargo:rustc-cfg=zig_backend");
+        println!("cargo:rustc-link-lib=static=aes");
+        if env::var_os("CARGO_FEATURE_BUILD_FROM_SOURCE").is_some() {
+            let lib_dir = build_from_source(&Path::new(&src_dir).join("wasm-libs"));
+            println!("cargo:rustc-link-search=native={}", lib_dir.display());
+            println!(
+                "cargo:rustc-env=AES_WASM_ZIG_LIB={}",
+                lib_dir.join("libaes.a").display()
+            );
+        } else {
+            println!("cargo:rustc-link-search=native={}/wasm-libs", src_dir);
+        }
+    }
+}
+
+/// Builds `libaes.a` with `zig build`, and returns the directory it was installed to.
+///
+/// The Zig code only uses the standard library, so nothing is downloaded.
+fn build_from_source(zig_dir: &Path) -> PathBuf {
+    for file in ["aes.zig", "build.zig", "build.zig.zon"] {
+        println!("cargo:rerun-if-changed={}", zig_dir.join(file).display());
+    }
+    println!("cargo:rerun-if-env-changed=ZIG");
+    println!("cargo:rerun-if-env-changed=ZIG_OPTIMIZE");
+
+    let zig = env::var("ZIG").unwrap_or_else(|_| "zig".to_string());
+    let version = match Command::new(&zig).arg("version").output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        _ => panic!(
+            "The `build-from-source` feature requires Zig {ZIG_VERSION}.x, but `{zig}` could not be run. \
+             Install Zig from https://ziglang.org/download/ or set the ZIG environment variable \
+             to the path of the `zig` executable."
+        ),
+    };
+    if version.split('.').take(2).collect::<Vec<_>>().join(".") != ZIG_VERSION {
+        panic!(
+            "The `build-from-source` feature requires Zig {ZIG_VERSION}.x, \
+             but `{zig}` is version {version}."
+        );
+    }
+
+    let optimize = env::var("ZIG_OPTIMIZE").unwrap_or_else(|_| {
+        match env::var("OPT_LEVEL").as_deref() {
+            Ok("s") | Ok("z") => "ReleaseSmall",
+            _ => "ReleaseFast",
+        }
+        .to_string()
+    });
+    if !OPTIMIZE_MODES.contains(&optimize.as_str()) {
+        panic!("Invalid ZIG_OPTIMIZE value `{optimize}`, expected one of {OPTIMIZE_MODES:?}.");
+    }
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("zig");
+    let status = Command::new(&zig)
+        .current_dir(zig_dir)
+        .arg("build")
+        .arg(format!("-Doptimize={optimize}"))
+        .arg("--prefix")
+        .arg(&out_dir)
+        .arg("--cache-dir")
+        .arg(out_dir.join("cache"))
+        .arg("--global-cache-dir")
+        .arg(out_dir.join("global-cache"))
+        .status()
+        .unwrap_or_else(|e| panic!("Failed to run `{zig} build`: {e}"));
+    if !status.success() {
+        panic!("`{zig} build` failed with {status}");
+    }
+    out_dir.join("lib")
 }
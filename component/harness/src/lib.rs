@@ -0,0 +1 @@
+//! Tests for the `aes-wasm:crypto/aead` component live in `tests/`.
@@ -0,0 +1,151 @@
+//! Runs the `aes-wasm:crypto/aead` component with wasmtime.
+//!
+//! The component must be built first, with `cargo component build --release` in the `component`
+//! directory. `AES_WASM_COMPONENT` can be set to the path of another build.
+
+use std::path::PathBuf;
+
+use wasmtime::component::{Component, Linker, ResourceTable};
+use wasmtime::{Config, Engine, Store};
+use wasmtime_wasi::{WasiCtx, WasiCtxView, WasiView};
+
+wasmtime::component::bindgen!({
+    path: "../wit",
+    world: "crypto",
+});
+
+use exports::aes_wasm::crypto::aead::{Algorithm, Error};
+
+const ALGORITHMS: [Algorithm; 12] = [
+    Algorithm::Aes128Gcm,
+    Algorithm::Aes256Gcm,
+    Algorithm::Aes128Ocb,
+    Algorithm::Aes256Ocb,
+    Algorithm::Aegis128l,
+    Algorithm::Aegis128x2,
+    Algorithm::Aegis128x4,
+    Algorithm::Aegis256,
+    Algorithm::Aegis256x2,
+    Algorithm::Aegis256x4,
+    Algorithm::Aes128CbcHmacSha256,
+    Algorithm::Aes256CbcHmacSha512,
+];
+
+struct State {
+    ctx: WasiCtx,
+    table: ResourceTable,
+}
+
+impl WasiView for State {
+    fn ctx(&mut self) -> WasiCtxView<'_> {
+        WasiCtxView {
+            ctx: &mut self.ctx,
+            table: &mut self.table,
+        }
+    }
+}
+
+fn component_path() -> PathBuf {
+    match std::env::var_os("AES_WASM_COMPONENT") {
+        Some(path) => path.into(),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../target/wasm32-wasip1/release/aes_wasm_component.wasm"),
+    }
+}
+
+fn instantiate() -> (Store<State>, Crypto) {
+    let engine = Engine::new(&Config::new()).unwrap();
+    let path = component_path();
+    let component = Component::from_file(&engine, &path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to load {}: {e}. Run `cargo component build --release` first.",
+            path.display()
+        )
+    });
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::p2::add_to_linker_sync(&mut linker).unwrap();
+    let state = State {
+        ctx: WasiCtx::builder().build(),
+        table: ResourceTable::new(),
+    };
+    let mut store = Store::new(&engine, state);
+    let crypto = Crypto::instantiate(&mut store, &component, &linker).unwrap();
+    (store, crypto)
+}
+
+#[test]
+fn aead() {
+    let (mut store, crypto) = instantiate();
+    let aead = crypto.aes_wasm_crypto_aead();
+    let (msg, ad) = (b"hello world", b"ad");
+    for alg in ALGORITHMS {
+        let name = aead.call_name(&mut store, alg).unwrap();
+        assert_eq!(aead.call_from_name(&mut store, &name).unwrap(), Some(alg));
+        let key = aead.call_generate_key(&mut store, alg).unwrap();
+        let nonce = aead.call_generate_nonce(&mut store, alg).unwrap();
+        assert_eq!(
+            key.len() as u32,
+            aead.call_key_length(&mut store, alg).unwrap()
+        );
+        assert_eq!(
+            nonce.len() as u32,
+            aead.call_nonce_length(&mut store, alg).unwrap()
+        );
+        let tag_len = aead.call_tag_length(&mut store, alg).unwrap();
+
+        let ciphertext = aead
+            .call_encrypt(&mut store, alg, msg, ad, &key, &nonce)
+            .unwrap()
+            .unwrap();
+        assert!(ciphertext.len() >= msg.len() + tag_len as usize);
+        let plaintext = aead
+            .call_decrypt(&mut store, alg, &ciphertext, ad, &key, &nonce)
+            .unwrap();
+        assert_eq!(plaintext.as_deref(), Ok(&msg[..]));
+
+        let tampered = aead
+            .call_decrypt(&mut store, alg, &ciphertext, b"", &key, &nonce)
+            .unwrap();
+        assert_eq!(tampered, Err(Error::VerificationFailed));
+        let truncated = aead
+            .call_decrypt(
+                &mut store,
+                alg,
+                &ciphertext[..tag_len as usize - 1],
+                ad,
+                &key,
+                &nonce,
+            )
+            .unwrap();
+        assert_eq!(truncated, Err(Error::InvalidLength));
+        let short_key = aead
+            .call_encrypt(&mut store, alg, msg, ad, &key[1..].to_vec(), &nonce)
+            .unwrap();
+        assert_eq!(short_key, Err(Error::InvalidKeyLength));
+        let short_nonce = aead
+            .call_decrypt(&mut store, alg, &ciphertext, ad, &key, &nonce[1..].to_vec())
+            .unwrap();
+        assert_eq!(short_nonce, Err(Error::InvalidNonceLength));
+    }
+    assert_eq!(aead.call_from_name(&mut store, "rot13").unwrap(), None);
+}
+
+/// Checks the output of the component against a known-answer test.
+#[test]
+fn known_answer() {
+    let (mut store, crypto) = instantiate();
+    let aead = crypto.aes_wasm_crypto_aead();
+    let (key, nonce) = (vec![0u8; 16], vec![0u8; 12]);
+    let ciphertext = aead
+        .call_encrypt(&mut store, Algorithm::Aes128Gcm, b"", b"", &key, &nonce)
+        .unwrap()
+        .unwrap();
+    // Test case 1 of the original GCM specification.
+    assert_eq!(
+        ciphertext,
+        [
+            0x58, 0xe2, 0xfc, 0xce, 0xfa, 0x7e, 0x30, 0x61, 0x36, 0x7f, 0x1d, 0x57, 0xa4, 0xe7,
+            0x45, 0x5a
+        ]
+    );
+}
@@ -0,0 +1,721 @@
+// Generated by `wit-bindgen` 0.41.0. DO NOT EDIT!
+// Options used:
+//   * runtime_path: "wit_bindgen_rt"
+#[rustfmt::skip]
+#[allow(dead_code, clippy::all)]
+pub mod exports {
+    pub mod aes_wasm {
+        pub mod crypto {
+            /// AEAD ciphers of the `aes-wasm` crate.
+            ///
+            /// Ciphertexts are returned with the authentication tag appended, and decryption expects them
+            /// in the same format.
+            #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+            pub mod aead {
+                #[used]
+                #[doc(hidden)]
+                static __FORCE_SECTION_REF: fn() = super::super::super::super::__link_custom_section_describing_imports;
+                use super::super::super::super::_rt;
+                /// AEAD cipher.
+                #[repr(u8)]
+                #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+                pub enum Algorithm {
+                    /// AES-128-GCM.
+                    Aes128Gcm,
+                    /// AES-256-GCM.
+                    Aes256Gcm,
+                    /// AES-128-OCB.
+                    Aes128Ocb,
+                    /// AES-256-OCB.
+                    Aes256Ocb,
+                    /// AEGIS-128L.
+                    Aegis128l,
+                    /// AEGIS-128X2.
+                    Aegis128x2,
+                    /// AEGIS-128X4.
+                    Aegis128x4,
+                    /// AEGIS-256.
+                    Aegis256,
+                    /// AEGIS-256X2.
+                    Aegis256x2,
+                    /// AEGIS-256X4.
+                    Aegis256x4,
+                    /// AES-128-CBC-HMAC-SHA-256 (`A128CBC-HS256`).
+                    Aes128CbcHmacSha256,
+                    /// AES-256-CBC-HMAC-SHA-512 (`A256CBC-HS512`).
+                    Aes256CbcHmacSha512,
+                }
+                impl ::core::fmt::Debug for Algorithm {
+                    fn fmt(
+                        &self,
+                        f: &mut ::core::fmt::Formatter<'_>,
+                    ) -> ::core::fmt::Result {
+                        match self {
+                            Algorithm::Aes128Gcm => {
+                                f.debug_tuple("Algorithm::Aes128Gcm").finish()
+                            }
+                            Algorithm::Aes256Gcm => {
+                                f.debug_tuple("Algorithm::Aes256Gcm").finish()
+                            }
+                            Algorithm::Aes128Ocb => {
+                                f.debug_tuple("Algorithm::Aes128Ocb").finish()
+                            }
+                            Algorithm::Aes256Ocb => {
+                                f.debug_tuple("Algorithm::Aes256Ocb").finish()
+                            }
+                            Algorithm::Aegis128l => {
+                                f.debug_tuple("Algorithm::Aegis128l").finish()
+                            }
+                            Algorithm::Aegis128x2 => {
+                                f.debug_tuple("Algorithm::Aegis128x2").finish()
+                            }
+                            Algorithm::Aegis128x4 => {
+                                f.debug_tuple("Algorithm::Aegis128x4").finish()
+                            }
+                            Algorithm::Aegis256 => {
+                                f.debug_tuple("Algorithm::Aegis256").finish()
+                            }
+                            Algorithm::Aegis256x2 => {
+                                f.debug_tuple("Algorithm::Aegis256x2").finish()
+                            }
+                            Algorithm::Aegis256x4 => {
+                                f.debug_tuple("Algorithm::Aegis256x4").finish()
+                            }
+                            Algorithm::Aes128CbcHmacSha256 => {
+                                f.debug_tuple("Algorithm::Aes128CbcHmacSha256").finish()
+                            }
+                            Algorithm::Aes256CbcHmacSha512 => {
+                                f.debug_tuple("Algorithm::Aes256CbcHmacSha512").finish()
+                            }
+                        }
+                    }
+                }
+                impl Algorithm {
+                    #[doc(hidden)]
+                    pub unsafe fn _lift(val: u8) -> Algorithm {
+                        if !cfg!(debug_assertions) {
+                            return ::core::mem::transmute(val);
+                        }
+                        match val {
+                            0 => Algorithm::Aes128Gcm,
+                            1 => Algorithm::Aes256Gcm,
+                            2 => Algorithm::Aes128Ocb,
+                            3 => Algorithm::Aes256Ocb,
+                            4 => Algorithm::Aegis128l,
+                            5 => Algorithm::Aegis128x2,
+                            6 => Algorithm::Aegis128x4,
+                            7 => Algorithm::Aegis256,
+                            8 => Algorithm::Aegis256x2,
+                            9 => Algorithm::Aegis256x4,
+                            10 => Algorithm::Aes128CbcHmacSha256,
+                            11 => Algorithm::Aes256CbcHmacSha512,
+                            _ => panic!("invalid enum discriminant"),
+                        }
+                    }
+                }
+                /// Error returned by `encrypt` and `decrypt`.
+                #[repr(u8)]
+                #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+                pub enum Error {
+                    /// Ciphertext verification failed.
+                    VerificationFailed,
+                    /// The key length doesn't match the algorithm.
+                    InvalidKeyLength,
+                    /// The nonce length doesn't match the algorithm.
+                    InvalidNonceLength,
+                    /// Invalid or unsupported parameters.
+                    InvalidParameters,
+                    /// The message or the associated data exceeds the algorithm's length limit.
+                    MessageTooLong,
+                    /// The ciphertext is shorter than a tag, or its length is invalid.
+                    InvalidLength,
+                    /// The cipher implementation failed to encrypt the message.
+                    EncryptionFailed,
+                }
+                impl Error {
+                    pub fn name(&self) -> &'static str {
+                        match self {
+                            Error::VerificationFailed => "verification-failed",
+                            Error::InvalidKeyLength => "invalid-key-length",
+                            Error::InvalidNonceLength => "invalid-nonce-length",
+                            Error::InvalidParameters => "invalid-parameters",
+                            Error::MessageTooLong => "message-too-long",
+                            Error::InvalidLength => "invalid-length",
+                            Error::EncryptionFailed => "encryption-failed",
+                        }
+                    }
+                    pub fn message(&self) -> &'static str {
+                        match self {
+                            Error::VerificationFailed => {
+                                "Ciphertext verification failed."
+                            }
+                            Error::InvalidKeyLength => {
+                                "The key length doesn't match the algorithm."
+                            }
+                            Error::InvalidNonceLength => {
+                                "The nonce length doesn't match the algorithm."
+                            }
+                            Error::InvalidParameters => {
+                                "Invalid or unsupported parameters."
+                            }
+                            Error::MessageTooLong => {
+                                "The message or the associated data exceeds the algorithm's length limit."
+                            }
+                            Error::InvalidLength => {
+                                "The ciphertext is shorter than a tag, or its length is invalid."
+                            }
+                            Error::EncryptionFailed => {
+                                "The cipher implementation failed to encrypt the message."
+                            }
+                        }
+                    }
+                }
+                impl ::core::fmt::Debug for Error {
+                    fn fmt(
+                        &self,
+                        f: &mut ::core::fmt::Formatter<'_>,
+                    ) -> ::core::fmt::Result {
+                        f.debug_struct("Error")
+                            .field("code", &(*self as i32))
+                            .field("name", &self.name())
+                            .field("message", &self.message())
+                            .finish()
+                    }
+                }
+                impl ::core::fmt::Display for Error {
+                    fn fmt(
+                        &self,
+                        f: &mut ::core::fmt::Formatter<'_>,
+                    ) -> ::core::fmt::Result {
+                        write!(f, "{} (error {})", self.name(), * self as i32)
+                    }
+                }
+                impl std::error::Error for Error {}
+                impl Error {
+                    #[doc(hidden)]
+                    pub unsafe fn _lift(val: u8) -> Error {
+                        if !cfg!(debug_assertions) {
+                            return ::core::mem::transmute(val);
+                        }
+                        match val {
+                            0 => Error::VerificationFailed,
+                            1 => Error::InvalidKeyLength,
+                            2 => Error::InvalidNonceLength,
+                            3 => Error::InvalidParameters,
+                            4 => Error::MessageTooLong,
+                            5 => Error::InvalidLength,
+                            6 => Error::EncryptionFailed,
+                            _ => panic!("invalid enum discriminant"),
+                        }
+                    }
+                }
+                /// Secret key, `key-length` bytes long.
+                pub type Key = _rt::Vec<u8>;
+                /// Nonce, `nonce-length` bytes long.
+                pub type Nonce = _rt::Vec<u8>;
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_key_length_cabi<T: Guest>(arg0: i32) -> i32 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let result0 = T::key_length(Algorithm::_lift(arg0 as u8));
+                    _rt::as_i32(result0)
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_nonce_length_cabi<T: Guest>(arg0: i32) -> i32 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let result0 = T::nonce_length(Algorithm::_lift(arg0 as u8));
+                    _rt::as_i32(result0)
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_tag_length_cabi<T: Guest>(arg0: i32) -> i32 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let result0 = T::tag_length(Algorithm::_lift(arg0 as u8));
+                    _rt::as_i32(result0)
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_name_cabi<T: Guest>(arg0: i32) -> *mut u8 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let result0 = T::name(Algorithm::_lift(arg0 as u8));
+                    let ptr1 = (&raw mut _RET_AREA.0).cast::<u8>();
+                    let vec2 = (result0.into_bytes()).into_boxed_slice();
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    ::core::mem::forget(vec2);
+                    *ptr1.add(::core::mem::size_of::<*const u8>()).cast::<usize>() = len2;
+                    *ptr1.add(0).cast::<*mut u8>() = ptr2.cast_mut();
+                    ptr1
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn __post_return_name<T: Guest>(arg0: *mut u8) {
+                    let l0 = *arg0.add(0).cast::<*mut u8>();
+                    let l1 = *arg0
+                        .add(::core::mem::size_of::<*const u8>())
+                        .cast::<usize>();
+                    _rt::cabi_dealloc(l0, l1, 1);
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_from_name_cabi<T: Guest>(
+                    arg0: *mut u8,
+                    arg1: usize,
+                ) -> *mut u8 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let len0 = arg1;
+                    let bytes0 = _rt::Vec::from_raw_parts(arg0.cast(), len0, len0);
+                    let result1 = T::from_name(_rt::string_lift(bytes0));
+                    let ptr2 = (&raw mut _RET_AREA.0).cast::<u8>();
+                    match result1 {
+                        Some(e) => {
+                            *ptr2.add(0).cast::<u8>() = (1i32) as u8;
+                            *ptr2.add(1).cast::<u8>() = (e.clone() as i32) as u8;
+                        }
+                        None => {
+                            *ptr2.add(0).cast::<u8>() = (0i32) as u8;
+                        }
+                    };
+                    ptr2
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_generate_key_cabi<T: Guest>(arg0: i32) -> *mut u8 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let result0 = T::generate_key(Algorithm::_lift(arg0 as u8));
+                    let ptr1 = (&raw mut _RET_AREA.0).cast::<u8>();
+                    let vec2 = (result0).into_boxed_slice();
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    ::core::mem::forget(vec2);
+                    *ptr1.add(::core::mem::size_of::<*const u8>()).cast::<usize>() = len2;
+                    *ptr1.add(0).cast::<*mut u8>() = ptr2.cast_mut();
+                    ptr1
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn __post_return_generate_key<T: Guest>(arg0: *mut u8) {
+                    let l0 = *arg0.add(0).cast::<*mut u8>();
+                    let l1 = *arg0
+                        .add(::core::mem::size_of::<*const u8>())
+                        .cast::<usize>();
+                    let base2 = l0;
+                    let len2 = l1;
+                    _rt::cabi_dealloc(base2, len2 * 1, 1);
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_generate_nonce_cabi<T: Guest>(
+                    arg0: i32,
+                ) -> *mut u8 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let result0 = T::generate_nonce(Algorithm::_lift(arg0 as u8));
+                    let ptr1 = (&raw mut _RET_AREA.0).cast::<u8>();
+                    let vec2 = (result0).into_boxed_slice();
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    ::core::mem::forget(vec2);
+                    *ptr1.add(::core::mem::size_of::<*const u8>()).cast::<usize>() = len2;
+                    *ptr1.add(0).cast::<*mut u8>() = ptr2.cast_mut();
+                    ptr1
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn __post_return_generate_nonce<T: Guest>(arg0: *mut u8) {
+                    let l0 = *arg0.add(0).cast::<*mut u8>();
+                    let l1 = *arg0
+                        .add(::core::mem::size_of::<*const u8>())
+                        .cast::<usize>();
+                    let base2 = l0;
+                    let len2 = l1;
+                    _rt::cabi_dealloc(base2, len2 * 1, 1);
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_encrypt_cabi<T: Guest>(
+                    arg0: i32,
+                    arg1: *mut u8,
+                    arg2: usize,
+                    arg3: *mut u8,
+                    arg4: usize,
+                    arg5: *mut u8,
+                    arg6: usize,
+                    arg7: *mut u8,
+                    arg8: usize,
+                ) -> *mut u8 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let len0 = arg2;
+                    let len1 = arg4;
+                    let len2 = arg6;
+                    let len3 = arg8;
+                    let result4 = T::encrypt(
+                        Algorithm::_lift(arg0 as u8),
+                        _rt::Vec::from_raw_parts(arg1.cast(), len0, len0),
+                        _rt::Vec::from_raw_parts(arg3.cast(), len1, len1),
+                        _rt::Vec::from_raw_parts(arg5.cast(), len2, len2),
+                        _rt::Vec::from_raw_parts(arg7.cast(), len3, len3),
+                    );
+                    let ptr5 = (&raw mut _RET_AREA.0).cast::<u8>();
+                    match result4 {
+                        Ok(e) => {
+                            *ptr5.add(0).cast::<u8>() = (0i32) as u8;
+                            let vec6 = (e).into_boxed_slice();
+                            let ptr6 = vec6.as_ptr().cast::<u8>();
+                            let len6 = vec6.len();
+                            ::core::mem::forget(vec6);
+                            *ptr5
+                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len6;
+                            *ptr5
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = ptr6.cast_mut();
+                        }
+                        Err(e) => {
+                            *ptr5.add(0).cast::<u8>() = (1i32) as u8;
+                            *ptr5
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<u8>() = (e.clone() as i32) as u8;
+                        }
+                    };
+                    ptr5
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn __post_return_encrypt<T: Guest>(arg0: *mut u8) {
+                    let l0 = i32::from(*arg0.add(0).cast::<u8>());
+                    match l0 {
+                        0 => {
+                            let l1 = *arg0
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>();
+                            let l2 = *arg0
+                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>();
+                            let base3 = l1;
+                            let len3 = l2;
+                            _rt::cabi_dealloc(base3, len3 * 1, 1);
+                        }
+                        _ => {}
+                    }
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn _export_decrypt_cabi<T: Guest>(
+                    arg0: i32,
+                    arg1: *mut u8,
+                    arg2: usize,
+                    arg3: *mut u8,
+                    arg4: usize,
+                    arg5: *mut u8,
+                    arg6: usize,
+                    arg7: *mut u8,
+                    arg8: usize,
+                ) -> *mut u8 {
+                    #[cfg(target_arch = "wasm32")] _rt::run_ctors_once();
+                    let len0 = arg2;
+                    let len1 = arg4;
+                    let len2 = arg6;
+                    let len3 = arg8;
+                    let result4 = T::decrypt(
+                        Algorithm::_lift(arg0 as u8),
+                        _rt::Vec::from_raw_parts(arg1.cast(), len0, len0),
+                        _rt::Vec::from_raw_parts(arg3.cast(), len1, len1),
+                        _rt::Vec::from_raw_parts(arg5.cast(), len2, len2),
+                        _rt::Vec::from_raw_parts(arg7.cast(), len3, len3),
+                    );
+                    let ptr5 = (&raw mut _RET_AREA.0).cast::<u8>();
+                    match result4 {
+                        Ok(e) => {
+                            *ptr5.add(0).cast::<u8>() = (0i32) as u8;
+                            let vec6 = (e).into_boxed_slice();
+                            let ptr6 = vec6.as_ptr().cast::<u8>();
+                            let len6 = vec6.len();
+                            ::core::mem::forget(vec6);
+                            *ptr5
+                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len6;
+                            *ptr5
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>() = ptr6.cast_mut();
+                        }
+                        Err(e) => {
+                            *ptr5.add(0).cast::<u8>() = (1i32) as u8;
+                            *ptr5
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<u8>() = (e.clone() as i32) as u8;
+                        }
+                    };
+                    ptr5
+                }
+                #[doc(hidden)]
+                #[allow(non_snake_case)]
+                pub unsafe fn __post_return_decrypt<T: Guest>(arg0: *mut u8) {
+                    let l0 = i32::from(*arg0.add(0).cast::<u8>());
+                    match l0 {
+                        0 => {
+                            let l1 = *arg0
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<*mut u8>();
+                            let l2 = *arg0
+                                .add(2 * ::core::mem::size_of::<*const u8>())
+                                .cast::<usize>();
+                            let base3 = l1;
+                            let len3 = l2;
+                            _rt::cabi_dealloc(base3, len3 * 1, 1);
+                        }
+                        _ => {}
+                    }
+                }
+                pub trait Guest {
+                    /// Returns the key length of the algorithm, in bytes.
+                    fn key_length(algorithm: Algorithm) -> u32;
+                    /// Returns the nonce length of the algorithm, in bytes.
+                    fn nonce_length(algorithm: Algorithm) -> u32;
+                    /// Returns the authentication tag length of the algorithm, in bytes.
+                    fn tag_length(algorithm: Algorithm) -> u32;
+                    /// Returns the IANA AEAD registry name of the algorithm, e.g. `AEAD_AES_128_GCM`.
+                    fn name(algorithm: Algorithm) -> _rt::String;
+                    /// Parses an IANA or libsodium-style algorithm name, such as `aegis128l`.
+                    fn from_name(name: _rt::String) -> Option<Algorithm>;
+                    /// Generates a random key for the algorithm.
+                    fn generate_key(algorithm: Algorithm) -> Key;
+                    /// Generates a random nonce for the algorithm.
+                    fn generate_nonce(algorithm: Algorithm) -> Nonce;
+                    /// Encrypts a message, and returns the ciphertext with the tag appended.
+                    fn encrypt(
+                        algorithm: Algorithm,
+                        msg: _rt::Vec<u8>,
+                        ad: _rt::Vec<u8>,
+                        key: Key,
+                        nonce: Nonce,
+                    ) -> Result<_rt::Vec<u8>, Error>;
+                    /// Verifies and decrypts a ciphertext with the tag appended.
+                    fn decrypt(
+                        algorithm: Algorithm,
+                        ciphertext: _rt::Vec<u8>,
+                        ad: _rt::Vec<u8>,
+                        key: Key,
+                        nonce: Nonce,
+                    ) -> Result<_rt::Vec<u8>, Error>;
+                }
+                #[doc(hidden)]
+                macro_rules! __export_aes_wasm_crypto_aead_cabi {
+                    ($ty:ident with_types_in $($path_to_types:tt)*) => {
+                        const _ : () = { #[unsafe (export_name =
+                        "aes-wasm:crypto/aead#key-length")] unsafe extern "C" fn
+                        export_key_length(arg0 : i32,) -> i32 { unsafe {
+                        $($path_to_types)*:: _export_key_length_cabi::<$ty > (arg0) } }
+                        #[unsafe (export_name = "aes-wasm:crypto/aead#nonce-length")]
+                        unsafe extern "C" fn export_nonce_length(arg0 : i32,) -> i32 {
+                        unsafe { $($path_to_types)*:: _export_nonce_length_cabi::<$ty >
+                        (arg0) } } #[unsafe (export_name =
+                        "aes-wasm:crypto/aead#tag-length")] unsafe extern "C" fn
+                        export_tag_length(arg0 : i32,) -> i32 { unsafe {
+                        $($path_to_types)*:: _export_tag_length_cabi::<$ty > (arg0) } }
+                        #[unsafe (export_name = "aes-wasm:crypto/aead#name")] unsafe
+                        extern "C" fn export_name(arg0 : i32,) -> * mut u8 { unsafe {
+                        $($path_to_types)*:: _export_name_cabi::<$ty > (arg0) } }
+                        #[unsafe (export_name = "cabi_post_aes-wasm:crypto/aead#name")]
+                        unsafe extern "C" fn _post_return_name(arg0 : * mut u8,) { unsafe
+                        { $($path_to_types)*:: __post_return_name::<$ty > (arg0) } }
+                        #[unsafe (export_name = "aes-wasm:crypto/aead#from-name")] unsafe
+                        extern "C" fn export_from_name(arg0 : * mut u8, arg1 : usize,) ->
+                        * mut u8 { unsafe { $($path_to_types)*::
+                        _export_from_name_cabi::<$ty > (arg0, arg1) } } #[unsafe
+                        (export_name = "aes-wasm:crypto/aead#generate-key")] unsafe
+                        extern "C" fn export_generate_key(arg0 : i32,) -> * mut u8 {
+                        unsafe { $($path_to_types)*:: _export_generate_key_cabi::<$ty >
+                        (arg0) } } #[unsafe (export_name =
+                        "cabi_post_aes-wasm:crypto/aead#generate-key")] unsafe extern "C"
+                        fn _post_return_generate_key(arg0 : * mut u8,) { unsafe {
+                        $($path_to_types)*:: __post_return_generate_key::<$ty > (arg0) }
+                        } #[unsafe (export_name = "aes-wasm:crypto/aead#generate-nonce")]
+                        unsafe extern "C" fn export_generate_nonce(arg0 : i32,) -> * mut
+                        u8 { unsafe { $($path_to_types)*::
+                        _export_generate_nonce_cabi::<$ty > (arg0) } } #[unsafe
+                        (export_name = "cabi_post_aes-wasm:crypto/aead#generate-nonce")]
+                        unsafe extern "C" fn _post_return_generate_nonce(arg0 : * mut
+                        u8,) { unsafe { $($path_to_types)*::
+                        __post_return_generate_nonce::<$ty > (arg0) } } #[unsafe
+                        (export_name = "aes-wasm:crypto/aead#encrypt")] unsafe extern "C"
+                        fn export_encrypt(arg0 : i32, arg1 : * mut u8, arg2 : usize, arg3
+                        : * mut u8, arg4 : usize, arg5 : * mut u8, arg6 : usize, arg7 : *
+                        mut u8, arg8 : usize,) -> * mut u8 { unsafe {
+                        $($path_to_types)*:: _export_encrypt_cabi::<$ty > (arg0, arg1,
+                        arg2, arg3, arg4, arg5, arg6, arg7, arg8) } } #[unsafe
+                        (export_name = "cabi_post_aes-wasm:crypto/aead#encrypt")] unsafe
+                        extern "C" fn _post_return_encrypt(arg0 : * mut u8,) { unsafe {
+                        $($path_to_types)*:: __post_return_encrypt::<$ty > (arg0) } }
+                        #[unsafe (export_name = "aes-wasm:crypto/aead#decrypt")] unsafe
+                        extern "C" fn export_decrypt(arg0 : i32, arg1 : * mut u8, arg2 :
+                        usize, arg3 : * mut u8, arg4 : usize, arg5 : * mut u8, arg6 :
+                        usize, arg7 : * mut u8, arg8 : usize,) -> * mut u8 { unsafe {
+                        $($path_to_types)*:: _export_decrypt_cabi::<$ty > (arg0, arg1,
+                        arg2, arg3, arg4, arg5, arg6, arg7, arg8) } } #[unsafe
+                        (export_name = "cabi_post_aes-wasm:crypto/aead#decrypt")] unsafe
+                        extern "C" fn _post_return_decrypt(arg0 : * mut u8,) { unsafe {
+                        $($path_to_types)*:: __post_return_decrypt::<$ty > (arg0) } } };
+                    };
+                }
+                #[doc(hidden)]
+                pub(crate) use __export_aes_wasm_crypto_aead_cabi;
+                #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                struct _RetArea(
+                    [::core::mem::MaybeUninit<
+                        u8,
+                    >; 3 * ::core::mem::size_of::<*const u8>()],
+                );
+                static mut _RET_AREA: _RetArea = _RetArea(
+                    [::core::mem::MaybeUninit::uninit(); 3
+                        * ::core::mem::size_of::<*const u8>()],
+                );
+            }
+        }
+    }
+}
+#[rustfmt::skip]
+mod _rt {
+    #![allow(dead_code, clippy::all)]
+    pub use alloc_crate::vec::Vec;
+    #[cfg(target_arch = "wasm32")]
+    pub fn run_ctors_once() {
+        wit_bindgen_rt::run_ctors_once();
+    }
+    pub fn as_i32<T: AsI32>(t: T) -> i32 {
+        t.as_i32()
+    }
+    pub trait AsI32 {
+        fn as_i32(self) -> i32;
+    }
+    impl<'a, T: Copy + AsI32> AsI32 for &'a T {
+        fn as_i32(self) -> i32 {
+            (*self).as_i32()
+        }
+    }
+    impl AsI32 for i32 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u32 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for i16 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u16 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for i8 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for u8 {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for char {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    impl AsI32 for usize {
+        #[inline]
+        fn as_i32(self) -> i32 {
+            self as i32
+        }
+    }
+    pub unsafe fn cabi_dealloc(ptr: *mut u8, size: usize, align: usize) {
+        if size == 0 {
+            return;
+        }
+        let layout = alloc::Layout::from_size_align_unchecked(size, align);
+        alloc::dealloc(ptr, layout);
+    }
+    pub use alloc_crate::string::String;
+    pub unsafe fn string_lift(bytes: Vec<u8>) -> String {
+        if cfg!(debug_assertions) {
+            String::from_utf8(bytes).unwrap()
+        } else {
+            String::from_utf8_unchecked(bytes)
+        }
+    }
+    extern crate alloc as alloc_crate;
+    pub use alloc_crate::alloc;
+}
+/// Generates `#[unsafe(no_mangle)]` functions to export the specified type as
+/// the root implementation of all generated traits.
+///
+/// For more information see the documentation of `wit_bindgen::generate!`.
+///
+/// ```rust
+/// # macro_rules! export{ ($($t:tt)*) => (); }
+/// # trait Guest {}
+/// struct MyType;
+///
+/// impl Guest for MyType {
+///     // ...
+/// }
+///
+/// export!(MyType);
+/// ```
+#[allow(unused_macros)]
+#[doc(hidden)]
+macro_rules! __export_crypto_impl {
+    ($ty:ident) => {
+        self::export!($ty with_types_in self);
+    };
+    ($ty:ident with_types_in $($path_to_types_root:tt)*) => {
+        $($path_to_types_root)*::
+        exports::aes_wasm::crypto::aead::__export_aes_wasm_crypto_aead_cabi!($ty
+        with_types_in $($path_to_types_root)*:: exports::aes_wasm::crypto::aead);
+    };
+}
+#[doc(inline)]
+pub(crate) use __export_crypto_impl as export;
+#[cfg(target_arch = "wasm32")]
+#[unsafe(
+    link_section = "component-type:wit-bindgen:0.41.0:aes-wasm:crypto:crypto:encoded world"
+)]
+#[doc(hidden)]
+#[allow(clippy::octal_escapes)]
+pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 824] = *b"\
+\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xbb\x05\x01A\x02\x01\
+A\x02\x01B\x1b\x01m\x0c\x0aaes128-gcm\x0aaes256-gcm\x0aaes128-ocb\x0aaes256-ocb\x09\
+aegis128l\x0aaegis128x2\x0aaegis128x4\x08aegis256\x0aaegis256x2\x0aaegis256x4\x16\
+aes128-cbc-hmac-sha256\x16aes256-cbc-hmac-sha512\x04\0\x09algorithm\x03\0\0\x01m\
+\x07\x13verification-failed\x12invalid-key-length\x14invalid-nonce-length\x12inv\
+alid-parameters\x10message-too-long\x0einvalid-length\x11encryption-failed\x04\0\
+\x05error\x03\0\x02\x01p}\x04\0\x03key\x03\0\x04\x01p}\x04\0\x05nonce\x03\0\x06\x01\
+@\x01\x09algorithm\x01\0y\x04\0\x0akey-length\x01\x08\x04\0\x0cnonce-length\x01\x08\
+\x04\0\x0atag-length\x01\x08\x01@\x01\x09algorithm\x01\0s\x04\0\x04name\x01\x09\x01\
+k\x01\x01@\x01\x04names\0\x0a\x04\0\x09from-name\x01\x0b\x01@\x01\x09algorithm\x01\
+\0\x05\x04\0\x0cgenerate-key\x01\x0c\x01@\x01\x09algorithm\x01\0\x07\x04\0\x0ege\
+nerate-nonce\x01\x0d\x01p}\x01j\x01\x0e\x01\x03\x01@\x05\x09algorithm\x01\x03msg\
+\x0e\x02ad\x0e\x03key\x05\x05nonce\x07\0\x0f\x04\0\x07encrypt\x01\x10\x01@\x05\x09\
+algorithm\x01\x0aciphertext\x0e\x02ad\x0e\x03key\x05\x05nonce\x07\0\x0f\x04\0\x07\
+decrypt\x01\x11\x04\0\x14aes-wasm:crypto/aead\x05\0\x04\0\x16aes-wasm:crypto/cry\
+pto\x04\0\x0b\x0c\x01\0\x06crypto\x03\0\0\0G\x09producers\x01\x0cprocessed-by\x02\
+\x0dwit-component\x070.227.1\x10wit-bindgen-rust\x060.41.0";
+#[inline(never)]
+#[doc(hidden)]
+pub fn __link_custom_section_describing_imports() {
+    wit_bindgen_rt::maybe_link_cabi_realloc();
+}
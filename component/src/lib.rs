@@ -0,0 +1,121 @@
+//! WebAssembly component exporting the `aes-wasm:crypto/aead` interface.
+//!
+//! Build with `cargo component build --release`. The interface is defined in `wit/world.wit`.
+
+#[allow(warnings)]
+mod bindings;
+
+use aes_wasm::{Algorithm, Error};
+use bindings::exports::aes_wasm::crypto::aead::{self, Guest, Key, Nonce};
+
+struct Component;
+
+impl From<aead::Algorithm> for Algorithm {
+    fn from(alg: aead::Algorithm) -> Self {
+        match alg {
+            aead::Algorithm::Aes128Gcm => Algorithm::Aes128Gcm,
+            aead::Algorithm::Aes256Gcm => Algorithm::Aes256Gcm,
+            aead::Algorithm::Aes128Ocb => Algorithm::Aes128Ocb,
+            aead::Algorithm::Aes256Ocb => Algorithm::Aes256Ocb,
+            aead::Algorithm::Aegis128l => Algorithm::Aegis128L,
+            aead::Algorithm::Aegis128x2 => Algorithm::Aegis128X2,
+            aead::Algorithm::Aegis128x4 => Algorithm::Aegis128X4,
+            aead::Algorithm::Aegis256 => Algorithm::Aegis256,
+            aead::Algorithm::Aegis256x2 => Algorithm::Aegis256X2,
+            aead::Algorithm::Aegis256x4 => Algorithm::Aegis256X4,
+            aead::Algorithm::Aes128CbcHmacSha256 => Algorithm::Aes128CbcHs256,
+            aead::Algorithm::Aes256CbcHmacSha512 => Algorithm::Aes256CbcHs512,
+        }
+    }
+}
+
+/// Fails for algorithms added to the crate but not yet to the interface.
+impl TryFrom<Algorithm> for aead::Algorithm {
+    type Error = ();
+
+    fn try_from(alg: Algorithm) -> Result<Self, ()> {
+        Ok(match alg {
+            Algorithm::Aes128Gcm => aead::Algorithm::Aes128Gcm,
+            Algorithm::Aes256Gcm => aead::Algorithm::Aes256Gcm,
+            Algorithm::Aes128Ocb => aead::Algorithm::Aes128Ocb,
+            Algorithm::Aes256Ocb => aead::Algorithm::Aes256Ocb,
+            Algorithm::Aegis128L => aead::Algorithm::Aegis128l,
+            Algorithm::Aegis128X2 => aead::Algorithm::Aegis128x2,
+            Algorithm::Aegis128X4 => aead::Algorithm::Aegis128x4,
+            Algorithm::Aegis256 => aead::Algorithm::Aegis256,
+            Algorithm::Aegis256X2 => aead::Algorithm::Aegis256x2,
+            Algorithm::Aegis256X4 => aead::Algorithm::Aegis256x4,
+            Algorithm::Aes128CbcHs256 => aead::Algorithm::Aes128CbcHmacSha256,
+            Algorithm::Aes256CbcHs512 => aead::Algorithm::Aes256CbcHmacSha512,
+            _ => return Err(()),
+        })
+    }
+}
+
+impl From<Error> for aead::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            // AEADs report padding errors as verification failures.
+            Error::VerificationFailed | Error::InvalidPadding => aead::Error::VerificationFailed,
+            Error::InvalidKeyLength => aead::Error::InvalidKeyLength,
+            Error::InvalidNonceLength => aead::Error::InvalidNonceLength,
+            Error::MessageTooLong => aead::Error::MessageTooLong,
+            Error::InvalidLength => aead::Error::InvalidLength,
+            Error::EncryptionFailed => aead::Error::EncryptionFailed,
+            Error::InvalidParameters => aead::Error::InvalidParameters,
+            _ => aead::Error::InvalidParameters,
+        }
+    }
+}
+
+impl Guest for Component {
+    fn key_length(algorithm: aead::Algorithm) -> u32 {
+        Algorithm::from(algorithm).key_len() as u32
+    }
+
+    fn nonce_length(algorithm: aead::Algorithm) -> u32 {
+        Algorithm::from(algorithm).nonce_len() as u32
+    }
+
+    fn tag_length(algorithm: aead::Algorithm) -> u32 {
+        Algorithm::from(algorithm).tag_len() as u32
+    }
+
+    fn name(algorithm: aead::Algorithm) -> String {
+        Algorithm::from(algorithm).name().to_string()
+    }
+
+    fn from_name(name: String) -> Option<aead::Algorithm> {
+        name.parse::<Algorithm>().ok()?.try_into().ok()
+    }
+
+    fn generate_key(algorithm: aead::Algorithm) -> Key {
+        Algorithm::from(algorithm).generate_key()
+    }
+
+    fn generate_nonce(algorithm: aead::Algorithm) -> Nonce {
+        Algorithm::from(algorithm).generate_nonce()
+    }
+
+    fn encrypt(
+        algorithm: aead::Algorithm,
+        msg: Vec<u8>,
+        ad: Vec<u8>,
+        key: Key,
+        nonce: Nonce,
+    ) -> Result<Vec<u8>, aead::Error> {
+        Ok(Algorithm::from(algorithm).encrypt(msg, ad, &key, &nonce)?)
+    }
+
+    fn decrypt(
+        algorithm: aead::Algorithm,
+        ciphertext: Vec<u8>,
+        ad: Vec<u8>,
+        key: Key,
+        nonce: Nonce,
+    ) -> Result<Vec<u8>, aead::Error> {
+        Ok(Algorithm::from(algorithm).decrypt(ciphertext, ad, &key, &nonce)?)
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
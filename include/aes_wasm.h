@@ -0,0 +1,419 @@
+/*
+ * C interface of the aes-wasm crate, enabled by its `capi` feature.
+ *
+ * Generated by the `capi::test::header` test, run with AES_WASM_UPDATE_HEADER=1 to update it.
+ *
+ * Functions return AES_WASM_OK on success, or a negative AES_WASM_ERROR_* code.
+ *
+ * Inputs are passed as a pointer and a length, and the pointer may be NULL if the length is
+ * zero. Outputs are written to a buffer passed with its capacity, and their length is stored
+ * in the `*_len` pointer, unless it is NULL. If the buffer is too small,
+ * AES_WASM_ERROR_BUFFER_TOO_SMALL is returned and the required length is stored instead.
+ * Output buffers may overlap with the inputs.
+ *
+ * AEAD ciphertexts have the tag appended.
+ */
+
+#ifndef AES_WASM_H
+#define AES_WASM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define AES_WASM_OK (0)
+#define AES_WASM_ERROR_VERIFICATION_FAILED (-1)
+#define AES_WASM_ERROR_INVALID_PARAMETERS (-2)
+#define AES_WASM_ERROR_INVALID_KEY_LENGTH (-3)
+#define AES_WASM_ERROR_INVALID_NONCE_LENGTH (-4)
+#define AES_WASM_ERROR_BUFFER_TOO_SMALL (-5)
+#define AES_WASM_ERROR_MESSAGE_TOO_LONG (-6)
+#define AES_WASM_ERROR_INVALID_LENGTH (-7)
+#define AES_WASM_ERROR_INVALID_PADDING (-8)
+#define AES_WASM_ERROR_ENCRYPTION_FAILED (-9)
+
+/* Memory allocation, for hosts */
+
+uint8_t *aes_wasm_alloc(size_t len);
+
+void aes_wasm_free(uint8_t *ptr, size_t len);
+
+/* aegis128l */
+
+#define AES_WASM_AEGIS128L_KEY_LEN 16
+#define AES_WASM_AEGIS128L_NONCE_LEN 16
+#define AES_WASM_AEGIS128L_TAG_LEN 32
+
+int aes_wasm_aegis128l_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aegis128l_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aegis128x2 */
+
+#define AES_WASM_AEGIS128X2_KEY_LEN 16
+#define AES_WASM_AEGIS128X2_NONCE_LEN 16
+#define AES_WASM_AEGIS128X2_TAG_LEN 32
+
+int aes_wasm_aegis128x2_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aegis128x2_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aegis128x4 */
+
+#define AES_WASM_AEGIS128X4_KEY_LEN 16
+#define AES_WASM_AEGIS128X4_NONCE_LEN 16
+#define AES_WASM_AEGIS128X4_TAG_LEN 32
+
+int aes_wasm_aegis128x4_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aegis128x4_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aegis256 */
+
+#define AES_WASM_AEGIS256_KEY_LEN 32
+#define AES_WASM_AEGIS256_NONCE_LEN 32
+#define AES_WASM_AEGIS256_TAG_LEN 32
+
+int aes_wasm_aegis256_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aegis256_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aegis256x2 */
+
+#define AES_WASM_AEGIS256X2_KEY_LEN 32
+#define AES_WASM_AEGIS256X2_NONCE_LEN 32
+#define AES_WASM_AEGIS256X2_TAG_LEN 32
+
+int aes_wasm_aegis256x2_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aegis256x2_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aegis256x4 */
+
+#define AES_WASM_AEGIS256X4_KEY_LEN 32
+#define AES_WASM_AEGIS256X4_NONCE_LEN 32
+#define AES_WASM_AEGIS256X4_TAG_LEN 32
+
+int aes_wasm_aegis256x4_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aegis256x4_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes128gcm */
+
+#define AES_WASM_AES128GCM_KEY_LEN 16
+#define AES_WASM_AES128GCM_NONCE_LEN 12
+#define AES_WASM_AES128GCM_TAG_LEN 16
+
+int aes_wasm_aes128gcm_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes128gcm_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes256gcm */
+
+#define AES_WASM_AES256GCM_KEY_LEN 32
+#define AES_WASM_AES256GCM_NONCE_LEN 12
+#define AES_WASM_AES256GCM_TAG_LEN 16
+
+int aes_wasm_aes256gcm_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes256gcm_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes128ocb */
+
+#define AES_WASM_AES128OCB_KEY_LEN 16
+#define AES_WASM_AES128OCB_NONCE_LEN 12
+#define AES_WASM_AES128OCB_TAG_LEN 16
+
+int aes_wasm_aes128ocb_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes128ocb_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes256ocb */
+
+#define AES_WASM_AES256OCB_KEY_LEN 32
+#define AES_WASM_AES256OCB_NONCE_LEN 12
+#define AES_WASM_AES256OCB_TAG_LEN 16
+
+int aes_wasm_aes256ocb_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes256ocb_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes128cbc_hs256 */
+
+#define AES_WASM_AES128CBC_HS256_KEY_LEN 32
+#define AES_WASM_AES128CBC_HS256_NONCE_LEN 16
+#define AES_WASM_AES128CBC_HS256_TAG_LEN 16
+
+int aes_wasm_aes128cbc_hs256_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes128cbc_hs256_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes256cbc_hs512 */
+
+#define AES_WASM_AES256CBC_HS512_KEY_LEN 64
+#define AES_WASM_AES256CBC_HS512_NONCE_LEN 16
+#define AES_WASM_AES256CBC_HS512_TAG_LEN 32
+
+int aes_wasm_aes256cbc_hs512_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes256cbc_hs512_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *ad, size_t ad_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes128cbc */
+
+#define AES_WASM_AES128CBC_KEY_LEN 16
+#define AES_WASM_AES128CBC_IV_LEN 16
+
+int aes_wasm_aes128cbc_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes128cbc_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes256cbc */
+
+#define AES_WASM_AES256CBC_KEY_LEN 32
+#define AES_WASM_AES256CBC_IV_LEN 16
+
+int aes_wasm_aes256cbc_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes256cbc_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes128ctr */
+
+#define AES_WASM_AES128CTR_KEY_LEN 16
+#define AES_WASM_AES128CTR_IV_LEN 16
+
+int aes_wasm_aes128ctr_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes128ctr_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+/* aes256ctr */
+
+#define AES_WASM_AES256CTR_KEY_LEN 32
+#define AES_WASM_AES256CTR_IV_LEN 16
+
+int aes_wasm_aes256ctr_encrypt(
+    uint8_t *c, size_t c_capacity, size_t *c_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_aes256ctr_decrypt(
+    uint8_t *m, size_t m_capacity, size_t *m_len,
+    const uint8_t *c, size_t c_len,
+    const uint8_t *iv, size_t iv_len,
+    const uint8_t *key, size_t key_len);
+
+/* cmac_aes128 */
+
+#define AES_WASM_CMAC_AES128_KEY_LEN 16
+#define AES_WASM_CMAC_AES128_TAG_LEN 16
+
+int aes_wasm_cmac_aes128_mac(
+    uint8_t *tag, size_t tag_capacity, size_t *tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_cmac_aes128_verify(
+    const uint8_t *tag, size_t tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *key, size_t key_len);
+
+/* pmac_aes128 */
+
+#define AES_WASM_PMAC_AES128_KEY_LEN 16
+#define AES_WASM_PMAC_AES128_TAG_LEN 16
+
+int aes_wasm_pmac_aes128_mac(
+    uint8_t *tag, size_t tag_capacity, size_t *tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_pmac_aes128_verify(
+    const uint8_t *tag, size_t tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *key, size_t key_len);
+
+/* xcbc_aes128 */
+
+#define AES_WASM_XCBC_AES128_KEY_LEN 16
+#define AES_WASM_XCBC_AES128_TAG_LEN 12
+
+int aes_wasm_xcbc_aes128_mac(
+    uint8_t *tag, size_t tag_capacity, size_t *tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_xcbc_aes128_verify(
+    const uint8_t *tag, size_t tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *key, size_t key_len);
+
+/* poly1305_aes */
+
+#define AES_WASM_POLY1305_AES_KEY_LEN 32
+#define AES_WASM_POLY1305_AES_TAG_LEN 16
+#define AES_WASM_POLY1305_AES_NONCE_LEN 16
+
+int aes_wasm_poly1305_aes_mac(
+    uint8_t *tag, size_t tag_capacity, size_t *tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+int aes_wasm_poly1305_aes_verify(
+    const uint8_t *tag, size_t tag_len,
+    const uint8_t *m, size_t m_len,
+    const uint8_t *nonce, size_t nonce_len,
+    const uint8_t *key, size_t key_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
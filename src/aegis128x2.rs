@@ -4,9 +4,9 @@
 //!
 //! ## Example
 //! ```rust
-//! use aes_wasm::aegis128x2::{encrypt, decrypt, Key, Nonce};
-//! let key = Key::default();
-//! let nonce = Nonce::default();
+//! use aes_wasm::aegis128x2::{encrypt, decrypt, generate_key, generate_nonce};
+//! let key = generate_key();
+//! let nonce = generate_nonce();
 //! let msg = b"hello";
 //! let ad = b"ad";
 //! let ciphertext = encrypt(msg, ad, &key, nonce);
@@ -14,7 +14,8 @@
 //! assert_eq!(plaintext, msg);
 //! ```
 
-mod zig {
+#[cfg(zig_backend)]
+mod backend {
     extern "C" {
         pub fn _aegis128x2_encrypt(
             c: *mut u8,
@@ -42,6 +43,9 @@ mod zig {
     }
 }
 
+#[cfg(not(zig_backend))]
+use crate::soft as backend;
+
 pub use crate::*;
 
 /// The length of the key in bytes.
@@ -56,6 +60,7 @@ pub const TAG_LEN: usize = 32;
 ///
 /// This constant is used for nonce array sizing.
 pub const NONCE_LEN: usize = 16;
+length_limits!(aegis);
 
 /// Key type for AEGIS-128X2 (16 bytes).
 pub type Key = [u8; KEY_LEN];
@@ -64,6 +69,187 @@ pub type Tag = [u8; TAG_LEN];
 /// Nonce type for AEGIS-128X2 (16 bytes).
 pub type Nonce = [u8; NONCE_LEN];
 
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+/// Generates a random nonce using the system's secure random number generator.
+///
+/// Random 128-bit nonces should not be used to encrypt more than 2^48 messages
+/// with the same key.
+///
+/// # Returns
+/// A new random nonce.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::generate_nonce;
+/// let nonce = generate_nonce();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_nonce() -> Nonce {
+    let mut nonce = Nonce::default();
+    random_bytes(&mut nonce);
+    nonce
+}
+
+/// Encrypts a message in place and returns the authentication tag.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+///
+/// # Arguments
+/// * `buf` - The plaintext message, overwritten with the ciphertext.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// The authentication tag.
+///
+/// # Panics
+/// Panics if `buf` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. [`try_encrypt_in_place_detached`] returns an error instead.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{encrypt_in_place_detached, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let mut buf = *b"hello";
+/// let tag = encrypt_in_place_detached(&mut buf, b"ad", &key, nonce);
+/// ```
+pub fn encrypt_in_place_detached(
+    buf: &mut [u8],
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Tag {
+    try_encrypt_in_place_detached(buf, ad, key, nonce).expect("AEGIS-128X2 encryption failed")
+}
+
+/// Encrypts a message in place and returns the authentication tag, checking its length.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+///
+/// # Arguments
+/// * `buf` - The plaintext message, overwritten with the ciphertext.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// `Ok(tag)`, or
+/// `Err(Error::MessageTooLong)` if `buf` is longer than `MAX_MSG_LEN` bytes, or `ad` longer
+/// than `MAX_AD_LEN` bytes.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{try_encrypt_in_place_detached, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let mut buf = *b"hello";
+/// let tag = try_encrypt_in_place_detached(&mut buf, b"ad", &key, nonce).unwrap();
+/// ```
+pub fn try_encrypt_in_place_detached(
+    buf: &mut [u8],
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<Tag, Error> {
+    let ad = ad.as_ref();
+    check_lengths(buf.len(), ad.len())?;
+    let buf_len = buf.len();
+    let buf_ptr = buf.as_mut_ptr();
+    let mut tag = Tag::default();
+    let res = unsafe {
+        backend::_aegis128x2_encrypt(
+            buf_ptr,
+            buf_len,
+            tag.as_mut_ptr(),
+            buf_ptr,
+            buf_len,
+            ad.as_ptr(),
+            ad.len(),
+            nonce.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+    if res != 0 {
+        buf.fill(0);
+        return Err(Error::EncryptionFailed);
+    }
+    Ok(tag)
+}
+
+/// Decrypts a ciphertext in place, verifying the authentication tag.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+/// If verification fails, `buf` is cleared.
+///
+/// # Arguments
+/// * `buf` - The ciphertext, overwritten with the plaintext.
+/// * `tag` - The authentication tag.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// `Ok(())` if verification succeeds, or `Err(Error)` if it fails.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{decrypt_in_place_detached, encrypt_in_place_detached, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let mut buf = *b"hello";
+/// let tag = encrypt_in_place_detached(&mut buf, b"ad", &key, nonce);
+/// decrypt_in_place_detached(&mut buf, &tag, b"ad", &key, nonce).unwrap();
+/// assert_eq!(&buf, b"hello");
+/// ```
+pub fn decrypt_in_place_detached(
+    buf: &mut [u8],
+    tag: &Tag,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<(), Error> {
+    let ad = ad.as_ref();
+    check_lengths(buf.len(), ad.len())?;
+    let buf_len = buf.len();
+    let buf_ptr = buf.as_mut_ptr();
+    let res = unsafe {
+        backend::_aegis128x2_decrypt(
+            buf_ptr,
+            buf_len,
+            buf_ptr,
+            buf_len,
+            tag.as_ptr(),
+            ad.as_ptr(),
+            ad.len(),
+            nonce.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+    if res != 0 {
+        buf.fill(0);
+        return Err(Error::VerificationFailed);
+    }
+    Ok(())
+}
+
 /// Encrypts a message and returns the ciphertext and authentication tag separately (detached).
 ///
 /// # Arguments
@@ -75,6 +261,10 @@ pub type Nonce = [u8; NONCE_LEN];
 /// # Returns
 /// Tuple of ciphertext and tag.
 ///
+/// # Panics
+/// Panics if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. [`try_encrypt_detached`] returns an error instead.
+///
 /// # Example
 /// ```
 /// use aes_wasm::aegis128x2::{encrypt_detached, Key, Nonce};
@@ -84,19 +274,52 @@ pub type Nonce = [u8; NONCE_LEN];
 /// let ad = b"ad";
 /// let (ciphertext, tag) = encrypt_detached(msg, ad, &key, nonce);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn encrypt_detached(
     msg: impl AsRef<[u8]>,
     ad: impl AsRef<[u8]>,
     key: &Key,
     nonce: Nonce,
 ) -> (Vec<u8>, Tag) {
+    try_encrypt_detached(msg, ad, key, nonce).expect("AEGIS-128X2 encryption failed")
+}
+
+/// Encrypts a message and returns the ciphertext and authentication tag separately (detached),
+/// checking its length.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// Tuple of ciphertext and tag, or
+/// `Err(Error::MessageTooLong)` if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer
+/// than `MAX_AD_LEN` bytes.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{try_encrypt_detached, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let (ciphertext, tag) = try_encrypt_detached(b"hello", b"ad", &key, nonce).unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_encrypt_detached(
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<(Vec<u8>, Tag), Error> {
     let msg = msg.as_ref();
     let ad = ad.as_ref();
+    check_lengths(msg.len(), ad.len())?;
     let ciphertext_len = msg.len();
     let mut ciphertext = Vec::with_capacity(ciphertext_len);
     let mut tag = Tag::default();
     unsafe {
-        zig::_aegis128x2_encrypt(
+        let res = backend::_aegis128x2_encrypt(
             ciphertext.as_mut_ptr(),
             ciphertext_len,
             tag.as_mut_ptr(),
@@ -107,9 +330,12 @@ pub fn encrypt_detached(
             nonce.as_ptr(),
             key.as_ptr(),
         );
+        if res != 0 {
+            return Err(Error::EncryptionFailed);
+        }
         ciphertext.set_len(ciphertext_len);
     };
-    (ciphertext, tag)
+    Ok((ciphertext, tag))
 }
 
 /// Encrypts a message and returns the ciphertext with the authentication tag appended.
@@ -123,6 +349,10 @@ pub fn encrypt_detached(
 /// # Returns
 /// Ciphertext with tag appended.
 ///
+/// # Panics
+/// Panics if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. [`try_encrypt`] returns an error instead.
+///
 /// # Example
 /// ```
 /// use aes_wasm::aegis128x2::{encrypt, Key, Nonce};
@@ -132,10 +362,42 @@ pub fn encrypt_detached(
 /// let ad = b"ad";
 /// let ciphertext = encrypt(msg, ad, &key, nonce);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn encrypt(msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key, nonce: Nonce) -> Vec<u8> {
-    let mut res = encrypt_detached(msg, ad, key, nonce);
-    res.0.extend_from_slice(res.1.as_ref());
-    res.0
+    try_encrypt(msg, ad, key, nonce).expect("AEGIS-128X2 encryption failed")
+}
+
+/// Encrypts a message and returns the ciphertext with the authentication tag appended,
+/// checking its length.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// Ciphertext with tag appended, or
+/// `Err(Error::MessageTooLong)` if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer
+/// than `MAX_AD_LEN` bytes.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{try_encrypt, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let ciphertext = try_encrypt(b"hello", b"ad", &key, nonce).unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_encrypt(
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<Vec<u8>, Error> {
+    let (mut ciphertext, tag) = try_encrypt_detached(msg, ad, key, nonce)?;
+    ciphertext.extend_from_slice(&tag);
+    Ok(ciphertext)
 }
 
 /// Decrypts a ciphertext and tag, returning the plaintext if verification succeeds.
@@ -160,6 +422,7 @@ pub fn encrypt(msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key, nonce: No
 /// let (ciphertext, tag) = encrypt_detached(msg, ad, &key, nonce);
 /// let plaintext = decrypt_detached(ciphertext, &tag, ad, &key, nonce).unwrap();
 /// ```
+#[cfg(feature = "alloc")]
 pub fn decrypt_detached(
     ciphertext: impl AsRef<[u8]>,
     tag: &Tag,
@@ -169,10 +432,11 @@ pub fn decrypt_detached(
 ) -> Result<Vec<u8>, Error> {
     let ciphertext = ciphertext.as_ref();
     let ad = ad.as_ref();
+    check_lengths(ciphertext.len(), ad.len())?;
     let msg_len = ciphertext.len();
     let mut msg = Vec::with_capacity(msg_len);
     unsafe {
-        let res = zig::_aegis128x2_decrypt(
+        let res = backend::_aegis128x2_decrypt(
             msg.as_mut_ptr(),
             msg_len,
             ciphertext.as_ptr(),
@@ -200,7 +464,8 @@ pub fn decrypt_detached(
 /// * `nonce` - Nonce value.
 ///
 /// # Returns
-/// `Ok(plaintext)` if verification succeeds, or `Err(Error)` if it fails.
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidLength)` if the input is
+/// shorter than a tag, or `Err(Error::VerificationFailed)` if verification fails.
 ///
 /// # Example
 /// ```
@@ -212,6 +477,7 @@ pub fn decrypt_detached(
 /// let ciphertext = encrypt(msg, ad, &key, nonce);
 /// let plaintext = decrypt(ciphertext, ad, &key, nonce).unwrap();
 /// ```
+#[cfg(feature = "alloc")]
 pub fn decrypt(
     ciphertext_and_tag: impl AsRef<[u8]>,
     ad: impl AsRef<[u8]>,
@@ -220,13 +486,77 @@ pub fn decrypt(
 ) -> Result<Vec<u8>, Error> {
     let ciphertext_and_tag = ciphertext_and_tag.as_ref();
     if ciphertext_and_tag.len() < TAG_LEN {
-        return Err(Error::VerificationFailed);
+        return Err(Error::InvalidLength);
     }
     let ciphertext = &ciphertext_and_tag[..ciphertext_and_tag.len() - TAG_LEN];
     let tag = &ciphertext_and_tag[ciphertext_and_tag.len() - TAG_LEN..];
     decrypt_detached(ciphertext, tag.try_into().unwrap(), ad, key, nonce)
 }
 
+/// Encrypts a message with a random nonce, and returns the nonce followed by the ciphertext
+/// and authentication tag.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// Nonce, ciphertext and tag, concatenated.
+///
+/// # Panics
+/// Panics if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. See [`try_encrypt`].
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{generate_key, seal};
+/// let key = generate_key();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let sealed = seal(msg, ad, &key);
+/// ```
+#[cfg(all(feature = "alloc", feature = "getrandom"))]
+pub fn seal(msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key) -> Vec<u8> {
+    let nonce = generate_nonce();
+    let (ciphertext, tag) = encrypt_detached(msg, ad, key, nonce);
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len() + TAG_LEN);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    sealed.extend_from_slice(&tag);
+    sealed
+}
+
+/// Decrypts the output of `seal`, returning the plaintext if verification succeeds.
+///
+/// # Arguments
+/// * `sealed` - Nonce, ciphertext and tag, concatenated.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidLength)` if the input is
+/// shorter than a nonce and a tag, or `Err(Error::VerificationFailed)` if verification fails.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aegis128x2::{generate_key, open, seal};
+/// let key = generate_key();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let sealed = seal(msg, ad, &key);
+/// let plaintext = open(sealed, ad, &key).unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn open(sealed: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key) -> Result<Vec<u8>, Error> {
+    let sealed = sealed.as_ref();
+    if sealed.len() < NONCE_LEN {
+        return Err(Error::InvalidLength);
+    }
+    let (nonce, ciphertext_and_tag) = sealed.split_at(NONCE_LEN);
+    decrypt(ciphertext_and_tag, ad, key, nonce.try_into().unwrap())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -243,5 +573,40 @@ mod test {
         let ciphertext_and_tag = encrypt(msg, ad, &key, nonce);
         let plaintext = decrypt(ciphertext_and_tag, ad, &key, nonce).unwrap();
         assert_eq!(plaintext, msg);
+        assert_eq!(
+            try_encrypt(msg, ad, &key, nonce),
+            Ok(encrypt(msg, ad, &key, nonce))
+        );
+        assert_eq!(
+            decrypt([0u8; TAG_LEN - 1], ad, &key, nonce),
+            Err(Error::InvalidLength)
+        );
+        let key = generate_key();
+        let sealed = seal(msg, ad, &key);
+        let plaintext = open(&sealed, ad, &key).unwrap();
+        assert_eq!(plaintext, msg);
+        assert_ne!(seal(msg, ad, &key)[..NONCE_LEN], sealed[..NONCE_LEN]);
+        assert_eq!(open(&sealed, b"", &key), Err(Error::VerificationFailed));
+    }
+
+    #[test]
+    fn in_place() {
+        let key = generate_key();
+        let nonce = generate_nonce();
+        let msg = b"hello world, in place";
+        let ad = b"additional data";
+        let mut buf = *msg;
+        let tag = encrypt_in_place_detached(&mut buf, ad, &key, nonce);
+        let (ciphertext, expected_tag) = encrypt_detached(msg, ad, &key, nonce);
+        assert_eq!(buf[..], ciphertext[..]);
+        assert_eq!(tag, expected_tag);
+        decrypt_in_place_detached(&mut buf, &tag, ad, &key, nonce).unwrap();
+        assert_eq!(&buf, msg);
+        let tag = encrypt_in_place_detached(&mut buf, ad, &key, nonce);
+        assert_eq!(
+            decrypt_in_place_detached(&mut buf, &tag, b"", &key, nonce),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(buf, [0u8; 21]);
     }
 }
@@ -4,16 +4,17 @@
 //!
 //! ## Example
 //! ```rust
-//! use aes_wasm::aes128ctr::{encrypt, decrypt, Key, IV};
-//! let key = Key::default();
-//! let iv = IV::default();
+//! use aes_wasm::aes128ctr::{encrypt, decrypt, generate_key, generate_iv};
+//! let key = generate_key();
+//! let iv = generate_iv();
 //! let msg = b"hello";
 //! let ciphertext = encrypt(msg, &key, iv);
 //! let plaintext = decrypt(ciphertext, &key, iv);
 //! assert_eq!(plaintext, msg);
 //! ```
 
-mod zig {
+#[cfg(zig_backend)]
+mod backend {
     extern "C" {
         pub fn aes128ctr(
             c: *mut u8,
@@ -26,6 +27,9 @@ mod zig {
     }
 }
 
+#[cfg(not(zig_backend))]
+use crate::soft as backend;
+
 pub use crate::*;
 
 /// The length of the key in bytes.
@@ -42,6 +46,40 @@ pub type Key = [u8; KEY_LEN];
 /// IV type for AES-128-CTR (16 bytes).
 pub type IV = [u8; IV_LEN];
 
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes128ctr::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+/// Generates a random IV using the system's secure random number generator.
+///
+/// # Returns
+/// A new random IV.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes128ctr::generate_iv;
+/// let iv = generate_iv();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_iv() -> IV {
+    let mut iv = IV::default();
+    random_bytes(&mut iv);
+    iv
+}
+
 /// Encrypts a message using AES-128 in CTR mode.
 ///
 /// # Arguments
@@ -60,12 +98,36 @@ pub type IV = [u8; IV_LEN];
 /// let msg = b"hello";
 /// let ciphertext = encrypt(msg, &key, iv);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
+    try_encrypt(msg, key, iv).expect("AES-128-CTR encryption failed")
+}
+
+/// Encrypts a message using AES-128 in CTR mode, reporting backend failures.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Returns
+/// Ciphertext as a `Vec<u8>`, or
+/// `Err(Error::EncryptionFailed)` if the cipher implementation failed.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes128ctr::{try_encrypt, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let ciphertext = try_encrypt(b"hello", &key, iv).unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Result<Vec<u8>, Error> {
     let msg = msg.as_ref();
     let ciphertext_len = msg.len();
     let mut ciphertext = Vec::with_capacity(ciphertext_len);
     unsafe {
-        zig::aes128ctr(
+        let res = backend::aes128ctr(
             ciphertext.as_mut_ptr(),
             ciphertext_len,
             msg.as_ptr(),
@@ -73,9 +135,12 @@ pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
             iv.as_ptr(),
             key.as_ptr(),
         );
+        if res != 0 {
+            return Err(Error::EncryptionFailed);
+        }
         ciphertext.set_len(ciphertext_len);
     };
-    ciphertext
+    Ok(ciphertext)
 }
 
 /// Decrypts a ciphertext using AES-128 in CTR mode.
@@ -97,10 +162,77 @@ pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
 /// let ciphertext = encrypt(msg, &key, iv);
 /// let plaintext = decrypt(ciphertext, &key, iv);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn decrypt(ciphertext: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
     encrypt(ciphertext, key, iv)
 }
 
+/// Encrypts or decrypts a buffer in place using AES-128 in CTR mode.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+///
+/// # Arguments
+/// * `buf` - The data to encrypt or decrypt, overwritten with the result.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes128ctr::{apply_keystream, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let mut buf = *b"hello";
+/// apply_keystream(&mut buf, &key, iv);
+/// apply_keystream(&mut buf, &key, iv);
+/// assert_eq!(&buf, b"hello");
+/// ```
+pub fn apply_keystream(buf: &mut [u8], key: &Key, iv: IV) {
+    try_apply_keystream(buf, key, iv).expect("AES-128-CTR encryption failed")
+}
+
+/// Encrypts or decrypts a buffer in place using AES-128 in CTR mode, reporting backend
+/// failures.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+/// If the cipher implementation fails, `buf` is cleared.
+///
+/// # Arguments
+/// * `buf` - The data to encrypt or decrypt, overwritten with the result.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Returns
+/// `Ok(())`, or
+/// `Err(Error::EncryptionFailed)` if the cipher implementation failed.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes128ctr::{try_apply_keystream, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let mut buf = *b"hello";
+/// try_apply_keystream(&mut buf, &key, iv).unwrap();
+/// ```
+pub fn try_apply_keystream(buf: &mut [u8], key: &Key, iv: IV) -> Result<(), Error> {
+    let buf_len = buf.len();
+    let buf_ptr = buf.as_mut_ptr();
+    let res = unsafe {
+        backend::aes128ctr(
+            buf_ptr,
+            buf_len,
+            buf_ptr,
+            buf_len,
+            iv.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+    if res != 0 {
+        buf.fill(0);
+        return Err(Error::EncryptionFailed);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -114,4 +246,16 @@ mod test {
         let plaintext = decrypt(ciphertext, &key, iv);
         assert_eq!(plaintext, msg);
     }
+
+    #[test]
+    fn in_place() {
+        let key = generate_key();
+        let iv = generate_iv();
+        let msg = b"hello world, in place";
+        let mut buf = *msg;
+        apply_keystream(&mut buf, &key, iv);
+        assert_eq!(buf[..], encrypt(msg, &key, iv)[..]);
+        apply_keystream(&mut buf, &key, iv);
+        assert_eq!(&buf, msg);
+    }
 }
@@ -4,16 +4,17 @@
 //!
 //! ## Example
 //! ```rust
-//! use aes_wasm::aes256cbc::{encrypt, decrypt, Key, IV};
-//! let key = Key::default();
-//! let iv = IV::default();
+//! use aes_wasm::aes256cbc::{encrypt, decrypt, generate_key, generate_iv};
+//! let key = generate_key();
+//! let iv = generate_iv();
 //! let msg = b"hello";
 //! let ciphertext = encrypt(msg, &key, iv);
 //! let plaintext = decrypt(ciphertext, &key, iv).unwrap();
 //! assert_eq!(plaintext, msg);
 //! ```
 
-mod zig {
+#[cfg(zig_backend)]
+mod backend {
     extern "C" {
         pub fn aes256cbc_encrypt(
             c: *mut u8,
@@ -35,6 +36,9 @@ mod zig {
     }
 }
 
+#[cfg(not(zig_backend))]
+use crate::soft as backend;
+
 pub use crate::*;
 
 /// The length of the key in bytes.
@@ -57,6 +61,40 @@ pub type Tag = [u8; TAG_LEN];
 /// IV type for AES-256-CBC (16 bytes).
 pub type IV = [u8; IV_LEN];
 
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+/// Generates a random IV using the system's secure random number generator.
+///
+/// # Returns
+/// A new random IV.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc::generate_iv;
+/// let iv = generate_iv();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_iv() -> IV {
+    let mut iv = IV::default();
+    random_bytes(&mut iv);
+    iv
+}
+
 /// Encrypts a message using AES-256 in CBC mode.
 ///
 /// # Arguments
@@ -75,22 +113,76 @@ pub type IV = [u8; IV_LEN];
 /// let msg = b"hello";
 /// let ciphertext = encrypt(msg, &key, iv);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
     let msg = msg.as_ref();
-    let ciphertext_len = (msg.len() + 16) & !15;
-    let mut ciphertext = Vec::with_capacity(ciphertext_len);
-    unsafe {
-        zig::aes256cbc_encrypt(
-            ciphertext.as_mut_ptr(),
+    let mut ciphertext = vec![0u8; ciphertext_len(msg.len())];
+    encrypt_into(&mut ciphertext, msg, key, iv).expect("AES-256-CBC encryption failed");
+    ciphertext
+}
+
+/// Encrypts a message using AES-256 in CBC mode, and writes the ciphertext to `out`.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+///
+/// # Arguments
+/// * `out` - Output buffer, at least `ciphertext_len(msg.len())` bytes long.
+/// * `msg` - The plaintext message to encrypt.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Returns
+/// `Ok(len)`, the length of the ciphertext written to the beginning of `out`, or
+/// `Err(Error::BufferTooSmall)` if `out` is too short for it.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc::{encrypt_into, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let mut out = [0u8; 32];
+/// let len = encrypt_into(&mut out, b"hello", &key, iv).unwrap();
+/// assert_eq!(len, 16);
+/// ```
+pub fn encrypt_into(
+    out: &mut [u8],
+    msg: impl AsRef<[u8]>,
+    key: &Key,
+    iv: IV,
+) -> Result<usize, Error> {
+    let msg = msg.as_ref();
+    let ciphertext_len = ciphertext_len(msg.len());
+    let out = out.get_mut(..ciphertext_len).ok_or(Error::BufferTooSmall)?;
+    let res = unsafe {
+        backend::aes256cbc_encrypt(
+            out.as_mut_ptr(),
             ciphertext_len,
             msg.as_ptr(),
             msg.len(),
             iv.as_ptr(),
             key.as_ptr(),
-        );
-        ciphertext.set_len(ciphertext_len);
+        )
     };
-    ciphertext
+    if res != 0 {
+        out.fill(0);
+        return Err(Error::EncryptionFailed);
+    }
+    Ok(ciphertext_len)
+}
+
+/// Returns the length of the ciphertext of a `msg_len`-byte message.
+///
+/// Messages are padded to the next multiple of the block size, adding a full block if their
+/// length already is.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc::ciphertext_len;
+/// assert_eq!(ciphertext_len(5), 16);
+/// assert_eq!(ciphertext_len(16), 32);
+/// ```
+pub const fn ciphertext_len(msg_len: usize) -> usize {
+    (msg_len + 16) & !15
 }
 
 /// Decrypts a ciphertext using AES-256 in CBC mode.
@@ -101,7 +193,12 @@ pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
 /// * `iv` - Initialization vector.
 ///
 /// # Returns
-/// `Ok(plaintext)` if decryption succeeds, or `Err(Error)` if it fails.
+/// `Ok(plaintext)` if decryption succeeds, `Err(Error::InvalidLength)` if the ciphertext is
+/// empty or not a multiple of the block size, or `Err(Error::InvalidPadding)` if the padding
+/// is invalid.
+///
+/// CBC ciphertexts are not authenticated: padding errors must not be revealed to an attacker
+/// able to submit ciphertexts. Use `aes256cbc_hs512` or another AEAD instead.
 ///
 /// # Example
 /// ```
@@ -112,29 +209,99 @@ pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
 /// let ciphertext = encrypt(msg, &key, iv);
 /// let plaintext = decrypt(ciphertext, &key, iv).unwrap();
 /// ```
+#[cfg(feature = "alloc")]
 pub fn decrypt(ciphertext: impl AsRef<[u8]>, key: &Key, iv: IV) -> Result<Vec<u8>, Error> {
     let ciphertext = ciphertext.as_ref();
-    let msg_max_len = ciphertext
-        .len()
-        .checked_sub(1)
-        .ok_or(Error::VerificationFailed)?;
-    let mut msg: Vec<u8> = Vec::with_capacity(msg_max_len);
-    unsafe {
-        let res = zig::aes256cbc_decrypt(
+    let mut msg = vec![0u8; ciphertext.len().saturating_sub(1)];
+    let msg_len = decrypt_into(&mut msg, ciphertext, key, iv)?;
+    msg.truncate(msg_len);
+    Ok(msg)
+}
+
+/// Decrypts a ciphertext using AES-256 in CBC mode, and writes the plaintext to `out`.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+/// If decryption fails, `out` is cleared.
+///
+/// # Arguments
+/// * `out` - Output buffer, at least `ciphertext.len() - 1` bytes long, the maximum length of
+///   the plaintext.
+/// * `ciphertext` - The ciphertext to decrypt.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Returns
+/// `Ok(len)`, the length of the plaintext written to the beginning of `out`,
+/// `Err(Error::InvalidLength)` if the ciphertext is empty or not a multiple of the block size,
+/// `Err(Error::BufferTooSmall)` if `out` is too short, or `Err(Error::InvalidPadding)` if the
+/// padding is invalid.
+///
+/// CBC ciphertexts are not authenticated: padding errors must not be revealed to an attacker
+/// able to submit ciphertexts. Use `aes256cbc_hs512` or another AEAD instead.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc::{decrypt_into, encrypt_into, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let mut ciphertext = [0u8; 16];
+/// encrypt_into(&mut ciphertext, b"hello", &key, iv).unwrap();
+/// let mut msg = [0u8; 15];
+/// let len = decrypt_into(&mut msg, &ciphertext, &key, iv).unwrap();
+/// assert_eq!(&msg[..len], b"hello");
+/// ```
+pub fn decrypt_into(
+    out: &mut [u8],
+    ciphertext: impl AsRef<[u8]>,
+    key: &Key,
+    iv: IV,
+) -> Result<usize, Error> {
+    let ciphertext = ciphertext.as_ref();
+    if ciphertext.is_empty() || ciphertext.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+    let msg_max_len = ciphertext.len() - 1;
+    let msg = out.get_mut(..msg_max_len).ok_or(Error::BufferTooSmall)?;
+    let res = unsafe {
+        backend::aes256cbc_decrypt(
             msg.as_mut_ptr(),
             msg_max_len,
             ciphertext.as_ptr(),
             ciphertext.len(),
             iv.as_ptr(),
             key.as_ptr(),
-        );
-        if res < 0 {
-            return Err(Error::VerificationFailed);
-        }
-        let msg_len = res as usize;
-        msg.set_len(msg_len);
+        )
     };
-    Ok(msg)
+    if res < 0 || res as usize > msg_max_len {
+        msg.fill(0);
+        return Err(Error::InvalidPadding);
+    }
+    let msg_len = res as usize;
+    // The committed libaes.a predates the CBC code of aes.zig, and doesn't fully validate the
+    // padding. Until it is regenerated, check that the last block decrypts to the message tail
+    // followed by valid padding, by encrypting it again with the previous ciphertext block as
+    // the IV. A library built from source doesn't need this.
+    #[cfg(all(zig_backend, not(feature = "build-from-source")))]
+    {
+        let pad_len = ciphertext.len() - msg_len;
+        let (prev, last) = ciphertext.split_at(ciphertext.len() - 16);
+        let prev = prev.last_chunk::<16>().copied().unwrap_or(iv);
+        let mut block = [0u8; 16];
+        if pad_len > 16
+            || encrypt_into(
+                &mut block,
+                &msg[msg_len - (16 - pad_len)..msg_len],
+                key,
+                prev,
+            )
+            .is_err()
+            || !ct_eq(&block, last)
+        {
+            msg.fill(0);
+            return Err(Error::InvalidPadding);
+        }
+    }
+    Ok(msg_len)
 }
 
 #[cfg(test)]
@@ -147,7 +314,91 @@ mod test {
         let iv = IV::default();
         let msg = b"Hello world";
         let ciphertext = encrypt(msg, &key, iv);
-        let plaintext = decrypt(ciphertext, &key, iv).unwrap();
+        let plaintext = decrypt(&ciphertext, &key, iv).unwrap();
         assert_eq!(plaintext, msg);
+        assert_eq!(
+            decrypt(&ciphertext[1..], &key, iv),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(decrypt([], &key, iv), Err(Error::InvalidLength));
+        // Sets the last padding byte to 4, 0 and 32.
+        for x in [1, 5, 5 ^ 32] {
+            let mut iv = iv;
+            iv[15] ^= x;
+            assert_eq!(decrypt(&ciphertext, &key, iv), Err(Error::InvalidPadding));
+        }
+    }
+
+    #[test]
+    fn padding() {
+        let key = Key::default();
+        let iv = IV::default();
+        let msg = [0x42u8; 48];
+        for len in 0..msg.len() {
+            let msg = &msg[..len];
+            let ciphertext = encrypt(msg, &key, iv);
+            assert_eq!(decrypt(&ciphertext, &key, iv).unwrap(), msg);
+            // Corrupts the first padding byte, and sets the last one above 16.
+            for (pos, x) in [(len, 1), (ciphertext.len() - 1, 32)] {
+                let (mut ciphertext, mut iv) = (ciphertext.clone(), iv);
+                match pos.checked_sub(16) {
+                    Some(pos) => ciphertext[pos] ^= x,
+                    None => iv[pos] ^= x,
+                }
+                assert_eq!(decrypt(&ciphertext, &key, iv), Err(Error::InvalidPadding));
+            }
+        }
+    }
+
+    #[test]
+    fn into() {
+        let key = Key::default();
+        let iv = IV::default();
+        let msg = b"Hello world";
+        let mut ciphertext = [0u8; 16];
+        assert_eq!(
+            encrypt_into(&mut ciphertext[..15], msg, &key, iv),
+            Err(Error::BufferTooSmall)
+        );
+        assert_eq!(encrypt_into(&mut ciphertext, msg, &key, iv), Ok(16));
+        assert_eq!(ciphertext[..], encrypt(msg, &key, iv)[..]);
+        let mut buf = [0u8; 15];
+        assert_eq!(
+            decrypt_into(&mut buf[..14], ciphertext, &key, iv),
+            Err(Error::BufferTooSmall)
+        );
+        assert_eq!(decrypt_into(&mut buf, ciphertext, &key, iv), Ok(msg.len()));
+        assert_eq!(&buf[..msg.len()], msg);
+    }
+
+    // The first two blocks of F.2.5 CBC-AES256.Encrypt from NIST SP 800-38A, with a 32-byte key.
+    // The vector has no padding: the ciphertext of the padded message starts with it.
+    #[test]
+    fn sp800_38a() {
+        let key: Key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
+            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
+            0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let iv: IV = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let msg = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51,
+        ];
+        let ciphertext = encrypt(msg, &key, iv);
+        assert_eq!(ciphertext.len(), 48);
+        assert_eq!(
+            ciphertext[..32],
+            [
+                0xf5, 0x8c, 0x4c, 0x04, 0xd6, 0xe5, 0xf1, 0xba, 0x77, 0x9e, 0xab, 0xfb, 0x5f, 0x7b,
+                0xfb, 0xd6, 0x9c, 0xfc, 0x4e, 0x96, 0x7e, 0xdb, 0x80, 0x8d, 0x67, 0x9f, 0x77, 0x7b,
+                0xc6, 0x70, 0x2c, 0x7d
+            ]
+        );
+        assert_eq!(decrypt(ciphertext, &key, iv).unwrap(), msg);
     }
 }
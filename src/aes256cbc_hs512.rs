@@ -0,0 +1,458 @@
+//! AES-256-CBC-HMAC-SHA-512 AEAD cipher for WASI (WebAssembly System Interface).
+//!
+//! Provides authenticated encryption and decryption using the Encrypt-then-MAC
+//! composition of AES-256-CBC and HMAC-SHA-512, as specified by RFC 7518 (`A256CBC-HS512`).
+//!
+//! The tag is verified in constant time before the ciphertext is decrypted and unpadded.
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::aes256cbc_hs512::{encrypt, decrypt, generate_key, generate_nonce};
+//! let key = generate_key();
+//! let nonce = generate_nonce();
+//! let msg = b"hello";
+//! let ad = b"ad";
+//! let ciphertext = encrypt(msg, ad, &key, nonce);
+//! let plaintext = decrypt(ciphertext, ad, &key, nonce).unwrap();
+//! assert_eq!(plaintext, msg);
+//! ```
+
+use hmac_sha512::HMAC;
+
+pub use crate::*;
+
+/// The length of the key in bytes.
+///
+/// The first half of the key is the HMAC key, the second half is the AES key.
+pub const KEY_LEN: usize = 64;
+/// The length of the authentication tag in bytes.
+///
+/// This constant is used for tag array sizing.
+pub const TAG_LEN: usize = 32;
+/// The length of the nonce (CBC IV) in bytes.
+///
+/// This constant is used for nonce array sizing.
+pub const NONCE_LEN: usize = 16;
+length_limits!(cbc_hmac);
+
+/// Key type for AES-256-CBC-HMAC-SHA-512 (64 bytes).
+pub type Key = [u8; KEY_LEN];
+/// Tag type for AES-256-CBC-HMAC-SHA-512 (32 bytes).
+pub type Tag = [u8; TAG_LEN];
+/// Nonce type for AES-256-CBC-HMAC-SHA-512 (16 bytes).
+pub type Nonce = [u8; NONCE_LEN];
+
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+/// Generates a random nonce using the system's secure random number generator.
+///
+/// Random 128-bit nonces should not be used to encrypt more than 2^48 messages
+/// with the same key.
+///
+/// # Returns
+/// A new random nonce.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::generate_nonce;
+/// let nonce = generate_nonce();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_nonce() -> Nonce {
+    let mut nonce = Nonce::default();
+    random_bytes(&mut nonce);
+    nonce
+}
+
+fn split_key(key: &Key) -> (&[u8], &aes256cbc::Key) {
+    let (mac_key, enc_key) = key.split_at(KEY_LEN / 2);
+    (mac_key, enc_key.try_into().unwrap())
+}
+
+fn compute_tag(ciphertext: &[u8], ad: &[u8], mac_key: &[u8], nonce: &Nonce) -> Tag {
+    let al = (ad.len() as u64 * 8).to_be_bytes();
+    let mut hmac = HMAC::new(mac_key);
+    hmac.update(ad);
+    hmac.update(nonce);
+    hmac.update(ciphertext);
+    hmac.update(al);
+    hmac.finalize()[..TAG_LEN].try_into().unwrap()
+}
+
+/// Encrypts a message and returns the ciphertext and authentication tag separately (detached).
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value, used as the CBC IV. It must be unpredictable.
+///
+/// # Returns
+/// Tuple of ciphertext and tag.
+///
+/// # Panics
+/// Panics if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. [`try_encrypt_detached`] returns an error instead.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{encrypt_detached, Key, Nonce, KEY_LEN};
+/// let key = [0u8; KEY_LEN];
+/// let nonce = Nonce::default();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let (ciphertext, tag) = encrypt_detached(msg, ad, &key, nonce);
+/// ```
+pub fn encrypt_detached(
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> (Vec<u8>, Tag) {
+    try_encrypt_detached(msg, ad, key, nonce).expect("AES-256-CBC-HMAC-SHA-512 encryption failed")
+}
+
+/// Encrypts a message and returns the ciphertext and authentication tag separately (detached),
+/// checking its length.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value, used as the CBC IV. It must be unpredictable.
+///
+/// # Returns
+/// Tuple of ciphertext and tag, or
+/// `Err(Error::MessageTooLong)` if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer
+/// than `MAX_AD_LEN` bytes.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{try_encrypt_detached, Nonce, KEY_LEN};
+/// let key = [0u8; KEY_LEN];
+/// let nonce = Nonce::default();
+/// let (ciphertext, tag) = try_encrypt_detached(b"hello", b"ad", &key, nonce).unwrap();
+/// ```
+pub fn try_encrypt_detached(
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<(Vec<u8>, Tag), Error> {
+    let msg = msg.as_ref();
+    let ad = ad.as_ref();
+    check_lengths(msg.len(), ad.len())?;
+    let (mac_key, enc_key) = split_key(key);
+    let ciphertext = aes256cbc::encrypt(msg, enc_key, nonce);
+    let tag = compute_tag(&ciphertext, ad, mac_key, &nonce);
+    Ok((ciphertext, tag))
+}
+
+/// Encrypts a message and returns the ciphertext with the authentication tag appended.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value, used as the CBC IV. It must be unpredictable.
+///
+/// # Returns
+/// Ciphertext with tag appended.
+///
+/// # Panics
+/// Panics if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. [`try_encrypt`] returns an error instead.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{encrypt, Key, Nonce, KEY_LEN};
+/// let key = [0u8; KEY_LEN];
+/// let nonce = Nonce::default();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let ciphertext = encrypt(msg, ad, &key, nonce);
+/// ```
+pub fn encrypt(msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key, nonce: Nonce) -> Vec<u8> {
+    try_encrypt(msg, ad, key, nonce).expect("AES-256-CBC-HMAC-SHA-512 encryption failed")
+}
+
+/// Encrypts a message and returns the ciphertext with the authentication tag appended,
+/// checking its length.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value, used as the CBC IV. It must be unpredictable.
+///
+/// # Returns
+/// Ciphertext with tag appended, or
+/// `Err(Error::MessageTooLong)` if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer
+/// than `MAX_AD_LEN` bytes.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{try_encrypt, Nonce, KEY_LEN};
+/// let key = [0u8; KEY_LEN];
+/// let nonce = Nonce::default();
+/// let ciphertext = try_encrypt(b"hello", b"ad", &key, nonce).unwrap();
+/// ```
+pub fn try_encrypt(
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<Vec<u8>, Error> {
+    let (mut ciphertext, tag) = try_encrypt_detached(msg, ad, key, nonce)?;
+    ciphertext.extend_from_slice(&tag);
+    Ok(ciphertext)
+}
+
+/// Decrypts a ciphertext and tag, returning the plaintext if verification succeeds.
+///
+/// The tag is checked before the ciphertext is decrypted, so padding errors are never
+/// observable for forged ciphertexts, and are reported as `Error::VerificationFailed`.
+///
+/// # Arguments
+/// * `ciphertext` - The ciphertext to decrypt.
+/// * `tag` - The authentication tag.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, or `Err(Error)` if it fails.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{encrypt_detached, decrypt_detached, Key, Nonce, KEY_LEN};
+/// let key = [0u8; KEY_LEN];
+/// let nonce = Nonce::default();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let (ciphertext, tag) = encrypt_detached(msg, ad, &key, nonce);
+/// let plaintext = decrypt_detached(ciphertext, &tag, ad, &key, nonce).unwrap();
+/// ```
+pub fn decrypt_detached(
+    ciphertext: impl AsRef<[u8]>,
+    tag: &Tag,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<Vec<u8>, Error> {
+    let ciphertext = ciphertext.as_ref();
+    let ad = ad.as_ref();
+    check_lengths(ciphertext.len(), ad.len())?;
+    if ciphertext.is_empty() || ciphertext.len() % 16 != 0 {
+        return Err(Error::InvalidLength);
+    }
+    let (mac_key, enc_key) = split_key(key);
+    let expected_tag = compute_tag(ciphertext, ad, mac_key, &nonce);
+    if !ct_eq(&expected_tag, tag) {
+        return Err(Error::VerificationFailed);
+    }
+    aes256cbc::decrypt(ciphertext, enc_key, nonce).map_err(|_| Error::VerificationFailed)
+}
+
+/// Decrypts a ciphertext with tag appended, returning the plaintext if verification succeeds.
+///
+/// # Arguments
+/// * `ciphertext_and_tag` - Ciphertext with tag appended.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidLength)` if the input is
+/// shorter than a tag, or `Err(Error::VerificationFailed)` if verification fails.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{encrypt, decrypt, Key, Nonce, KEY_LEN};
+/// let key = [0u8; KEY_LEN];
+/// let nonce = Nonce::default();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let ciphertext = encrypt(msg, ad, &key, nonce);
+/// let plaintext = decrypt(ciphertext, ad, &key, nonce).unwrap();
+/// ```
+pub fn decrypt(
+    ciphertext_and_tag: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &Key,
+    nonce: Nonce,
+) -> Result<Vec<u8>, Error> {
+    let ciphertext_and_tag = ciphertext_and_tag.as_ref();
+    if ciphertext_and_tag.len() < TAG_LEN {
+        return Err(Error::InvalidLength);
+    }
+    let ciphertext = &ciphertext_and_tag[..ciphertext_and_tag.len() - TAG_LEN];
+    let tag = &ciphertext_and_tag[ciphertext_and_tag.len() - TAG_LEN..];
+    decrypt_detached(ciphertext, tag.try_into().unwrap(), ad, key, nonce)
+}
+
+/// Encrypts a message with a random nonce, and returns the nonce followed by the ciphertext
+/// and authentication tag.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// Nonce, ciphertext and tag, concatenated.
+///
+/// # Panics
+/// Panics if `msg` is longer than `MAX_MSG_LEN` bytes, or `ad` longer than `MAX_AD_LEN`
+/// bytes. See [`try_encrypt`].
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{generate_key, seal};
+/// let key = generate_key();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let sealed = seal(msg, ad, &key);
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn seal(msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key) -> Vec<u8> {
+    let nonce = generate_nonce();
+    let (ciphertext, tag) = encrypt_detached(msg, ad, key, nonce);
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len() + TAG_LEN);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    sealed.extend_from_slice(&tag);
+    sealed
+}
+
+/// Decrypts the output of `seal`, returning the plaintext if verification succeeds.
+///
+/// # Arguments
+/// * `sealed` - Nonce, ciphertext and tag, concatenated.
+/// * `ad` - Additional authenticated data (AAD).
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidLength)` if the input is
+/// shorter than a nonce and a tag, or `Err(Error::VerificationFailed)` if verification fails.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256cbc_hs512::{generate_key, open, seal};
+/// let key = generate_key();
+/// let msg = b"hello";
+/// let ad = b"ad";
+/// let sealed = seal(msg, ad, &key);
+/// let plaintext = open(sealed, ad, &key).unwrap();
+/// ```
+pub fn open(sealed: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &Key) -> Result<Vec<u8>, Error> {
+    let sealed = sealed.as_ref();
+    if sealed.len() < NONCE_LEN {
+        return Err(Error::InvalidLength);
+    }
+    let (nonce, ciphertext_and_tag) = sealed.split_at(NONCE_LEN);
+    decrypt(ciphertext_and_tag, ad, key, nonce.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn aes256cbc_hs512() {
+        let key = [0u8; KEY_LEN];
+        let nonce = Nonce::default();
+        let msg = b"hello world";
+        let ad = b"additional data";
+        let (ciphertext, tag) = encrypt_detached(msg, ad, &key, nonce);
+        let plaintext = decrypt_detached(ciphertext, &tag, ad, &key, nonce).unwrap();
+        assert_eq!(plaintext, msg);
+        let mut ciphertext_and_tag = encrypt(msg, ad, &key, nonce);
+        let plaintext = decrypt(&ciphertext_and_tag, ad, &key, nonce).unwrap();
+        assert_eq!(plaintext, msg);
+        ciphertext_and_tag[0] ^= 1;
+        assert_eq!(
+            decrypt(&ciphertext_and_tag, ad, &key, nonce),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            try_encrypt(msg, ad, &key, nonce),
+            Ok(encrypt(msg, ad, &key, nonce))
+        );
+        assert_eq!(
+            decrypt([0u8; TAG_LEN - 1], ad, &key, nonce),
+            Err(Error::InvalidLength)
+        );
+        assert_eq!(
+            decrypt_detached(
+                &ciphertext_and_tag[1..TAG_LEN],
+                &[0u8; TAG_LEN],
+                ad,
+                &key,
+                nonce
+            ),
+            Err(Error::InvalidLength)
+        );
+        // Authentic ciphertext with invalid padding.
+        let (mac_key, enc_key) = split_key(&key);
+        let ciphertext = aes256cbc::encrypt([], enc_key, nonce);
+        let mut nonce = nonce;
+        nonce[15] ^= 1;
+        let tag = compute_tag(&ciphertext, ad, mac_key, &nonce);
+        assert_eq!(
+            decrypt_detached(&ciphertext, &tag, ad, &key, nonce),
+            Err(Error::VerificationFailed)
+        );
+        let key = generate_key();
+        let sealed = seal(msg, ad, &key);
+        let plaintext = open(&sealed, ad, &key).unwrap();
+        assert_eq!(plaintext, msg);
+        assert_ne!(seal(msg, ad, &key)[..NONCE_LEN], sealed[..NONCE_LEN]);
+        assert_eq!(open(&sealed, b"", &key), Err(Error::VerificationFailed));
+    }
+
+    #[test]
+    fn aes256cbc_hs512_rfc7518() {
+        let key: Key = core::array::from_fn(|i| i as u8);
+        let nonce: Nonce = [
+            0x1a, 0xf3, 0x8c, 0x2d, 0xc2, 0xb9, 0x6f, 0xfd, 0xd8, 0x66, 0x94, 0x09, 0x23, 0x41,
+            0xbc, 0x04,
+        ];
+        let msg = b"A cipher system must not be required to be secret, and it must be able to fall into the hands of the enemy without inconvenience";
+        let ad = b"The second principle of Auguste Kerckhoffs";
+        let (ciphertext, tag) = encrypt_detached(msg, ad, &key, nonce);
+        assert_eq!(ciphertext.len(), 144);
+        assert_eq!(
+            ciphertext[..16],
+            [
+                0x4a, 0xff, 0xaa, 0xad, 0xb7, 0x8c, 0x31, 0xc5, 0xda, 0x4b, 0x1b, 0x59, 0x0d, 0x10,
+                0xff, 0xbd
+            ]
+        );
+        assert_eq!(
+            tag,
+            [
+                0x4d, 0xd3, 0xb4, 0xc0, 0x88, 0xa7, 0xf4, 0x5c, 0x21, 0x68, 0x39, 0x64, 0x5b, 0x20,
+                0x12, 0xbf, 0x2e, 0x62, 0x69, 0xa8, 0xc5, 0x6a, 0x81, 0x6d, 0xbc, 0x1b, 0x26, 0x77,
+                0x61, 0x95, 0x5b, 0xc5
+            ]
+        );
+        let plaintext = decrypt_detached(ciphertext, &tag, ad, &key, nonce).unwrap();
+        assert_eq!(plaintext, msg);
+    }
+}
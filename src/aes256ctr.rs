@@ -4,16 +4,17 @@
 //!
 //! ## Example
 //! ```rust
-//! use aes_wasm::aes256ctr::{encrypt, decrypt, Key, IV};
-//! let key = Key::default();
-//! let iv = IV::default();
+//! use aes_wasm::aes256ctr::{encrypt, decrypt, generate_key, generate_iv};
+//! let key = generate_key();
+//! let iv = generate_iv();
 //! let msg = b"hello";
 //! let ciphertext = encrypt(msg, &key, iv);
 //! let plaintext = decrypt(ciphertext, &key, iv);
 //! assert_eq!(plaintext, msg);
 //! ```
 
-mod zig {
+#[cfg(zig_backend)]
+mod backend {
     extern "C" {
         pub fn aes256ctr(
             c: *mut u8,
@@ -26,22 +27,59 @@ mod zig {
     }
 }
 
+#[cfg(not(zig_backend))]
+use crate::soft as backend;
+
 pub use crate::*;
 
 /// The length of the key in bytes.
 ///
 /// This constant is used for key array sizing.
-pub const KEY_LEN: usize = 16;
+pub const KEY_LEN: usize = 32;
 /// The length of the IV in bytes.
 ///
 /// This constant is used for IV array sizing.
 pub const IV_LEN: usize = 16;
 
-/// Key type for AES-256-CTR (16 bytes).
+/// Key type for AES-256-CTR (32 bytes).
 pub type Key = [u8; KEY_LEN];
 /// IV type for AES-256-CTR (16 bytes).
 pub type IV = [u8; IV_LEN];
 
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256ctr::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+/// Generates a random IV using the system's secure random number generator.
+///
+/// # Returns
+/// A new random IV.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256ctr::generate_iv;
+/// let iv = generate_iv();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_iv() -> IV {
+    let mut iv = IV::default();
+    random_bytes(&mut iv);
+    iv
+}
+
 /// Encrypts a message using AES-256 in CTR mode.
 ///
 /// # Arguments
@@ -60,12 +98,36 @@ pub type IV = [u8; IV_LEN];
 /// let msg = b"hello";
 /// let ciphertext = encrypt(msg, &key, iv);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
+    try_encrypt(msg, key, iv).expect("AES-256-CTR encryption failed")
+}
+
+/// Encrypts a message using AES-256 in CTR mode, reporting backend failures.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Returns
+/// Ciphertext as a `Vec<u8>`, or
+/// `Err(Error::EncryptionFailed)` if the cipher implementation failed.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256ctr::{try_encrypt, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let ciphertext = try_encrypt(b"hello", &key, iv).unwrap();
+/// ```
+#[cfg(feature = "alloc")]
+pub fn try_encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Result<Vec<u8>, Error> {
     let msg = msg.as_ref();
     let ciphertext_len = msg.len();
     let mut ciphertext = Vec::with_capacity(ciphertext_len);
     unsafe {
-        zig::aes256ctr(
+        let res = backend::aes256ctr(
             ciphertext.as_mut_ptr(),
             ciphertext_len,
             msg.as_ptr(),
@@ -73,9 +135,12 @@ pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
             iv.as_ptr(),
             key.as_ptr(),
         );
+        if res != 0 {
+            return Err(Error::EncryptionFailed);
+        }
         ciphertext.set_len(ciphertext_len);
     };
-    ciphertext
+    Ok(ciphertext)
 }
 
 /// Decrypts a ciphertext using AES-256 in CTR mode.
@@ -97,10 +162,77 @@ pub fn encrypt(msg: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
 /// let ciphertext = encrypt(msg, &key, iv);
 /// let plaintext = decrypt(ciphertext, &key, iv);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn decrypt(ciphertext: impl AsRef<[u8]>, key: &Key, iv: IV) -> Vec<u8> {
     encrypt(ciphertext, key, iv)
 }
 
+/// Encrypts or decrypts a buffer in place using AES-256 in CTR mode.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+///
+/// # Arguments
+/// * `buf` - The data to encrypt or decrypt, overwritten with the result.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256ctr::{apply_keystream, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let mut buf = *b"hello";
+/// apply_keystream(&mut buf, &key, iv);
+/// apply_keystream(&mut buf, &key, iv);
+/// assert_eq!(&buf, b"hello");
+/// ```
+pub fn apply_keystream(buf: &mut [u8], key: &Key, iv: IV) {
+    try_apply_keystream(buf, key, iv).expect("AES-256-CTR encryption failed")
+}
+
+/// Encrypts or decrypts a buffer in place using AES-256 in CTR mode, reporting backend
+/// failures.
+///
+/// This function doesn't allocate, and is available without the `alloc` feature.
+/// If the cipher implementation fails, `buf` is cleared.
+///
+/// # Arguments
+/// * `buf` - The data to encrypt or decrypt, overwritten with the result.
+/// * `key` - Reference to the secret key.
+/// * `iv` - Initialization vector.
+///
+/// # Returns
+/// `Ok(())`, or
+/// `Err(Error::EncryptionFailed)` if the cipher implementation failed.
+///
+/// # Example
+/// ```
+/// use aes_wasm::aes256ctr::{try_apply_keystream, Key, IV};
+/// let key = Key::default();
+/// let iv = IV::default();
+/// let mut buf = *b"hello";
+/// try_apply_keystream(&mut buf, &key, iv).unwrap();
+/// ```
+pub fn try_apply_keystream(buf: &mut [u8], key: &Key, iv: IV) -> Result<(), Error> {
+    let buf_len = buf.len();
+    let buf_ptr = buf.as_mut_ptr();
+    let res = unsafe {
+        backend::aes256ctr(
+            buf_ptr,
+            buf_len,
+            buf_ptr,
+            buf_len,
+            iv.as_ptr(),
+            key.as_ptr(),
+        )
+    };
+    if res != 0 {
+        buf.fill(0);
+        return Err(Error::EncryptionFailed);
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -114,4 +246,43 @@ mod test {
         let plaintext = decrypt(ciphertext, &key, iv);
         assert_eq!(plaintext, msg);
     }
+
+    #[test]
+    fn in_place() {
+        let key = generate_key();
+        let iv = generate_iv();
+        let msg = b"hello world, in place";
+        let mut buf = *msg;
+        apply_keystream(&mut buf, &key, iv);
+        assert_eq!(buf[..], encrypt(msg, &key, iv)[..]);
+        apply_keystream(&mut buf, &key, iv);
+        assert_eq!(&buf, msg);
+    }
+
+    // F.5.5 CTR-AES256.Encrypt from NIST SP 800-38A, with a 32-byte key.
+    #[test]
+    fn sp800_38a() {
+        let key: Key = [
+            0x60, 0x3d, 0xeb, 0x10, 0x15, 0xca, 0x71, 0xbe, 0x2b, 0x73, 0xae, 0xf0, 0x85, 0x7d,
+            0x77, 0x81, 0x1f, 0x35, 0x2c, 0x07, 0x3b, 0x61, 0x08, 0xd7, 0x2d, 0x98, 0x10, 0xa3,
+            0x09, 0x14, 0xdf, 0xf4,
+        ];
+        let iv: IV = [
+            0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd,
+            0xfe, 0xff,
+        ];
+        let msg = [
+            0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96, 0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93,
+            0x17, 0x2a, 0xae, 0x2d, 0x8a, 0x57, 0x1e, 0x03, 0xac, 0x9c, 0x9e, 0xb7, 0x6f, 0xac,
+            0x45, 0xaf, 0x8e, 0x51,
+        ];
+        assert_eq!(
+            encrypt(msg, &key, iv),
+            [
+                0x60, 0x1e, 0xc3, 0x13, 0x77, 0x57, 0x89, 0xa5, 0xb7, 0xa7, 0xf5, 0x04, 0xbb, 0xf3,
+                0xd2, 0x28, 0xf4, 0x43, 0xe3, 0xca, 0x4d, 0x62, 0xb5, 0x9a, 0xca, 0x84, 0xe9, 0x90,
+                0xca, 0xca, 0xf5, 0xc5
+            ]
+        );
+    }
 }
@@ -0,0 +1,455 @@
+//! Runtime selection of the AEAD ciphers of this crate.
+//!
+//! `Algorithm` identifies an AEAD cipher, and exposes its key, nonce and tag lengths as well as
+//! `encrypt` and `decrypt` functions taking byte slices, so that the cipher can be chosen from
+//! configuration.
+//!
+//! Algorithms are displayed using their IANA AEAD registry names, and can be parsed from
+//! IANA names as well as from libsodium-style names such as `aes256gcm` or `aegis128l`.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(feature = "aegis")] {
+//! use aes_wasm::Algorithm;
+//! let alg: Algorithm = "aegis128l".parse().unwrap();
+//! let key = alg.generate_key();
+//! let nonce = alg.generate_nonce();
+//! let ciphertext = alg.encrypt(b"hello", b"ad", &key, &nonce).unwrap();
+//! let plaintext = alg.decrypt(ciphertext, b"ad", &key, &nonce).unwrap();
+//! assert_eq!(plaintext, b"hello");
+//! assert_eq!(alg.to_string(), "AEAD_AEGIS128L");
+//! # }
+//! ```
+
+use core::fmt::{self, Display};
+use core::str::FromStr;
+
+pub use crate::*;
+
+/// AEAD cipher.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// AES-128-GCM.
+    #[cfg(feature = "gcm")]
+    Aes128Gcm,
+    /// AES-256-GCM.
+    #[cfg(feature = "gcm")]
+    Aes256Gcm,
+    /// AES-128-OCB.
+    #[cfg(feature = "ocb")]
+    Aes128Ocb,
+    /// AES-256-OCB.
+    #[cfg(feature = "ocb")]
+    Aes256Ocb,
+    /// AEGIS-128L.
+    #[cfg(feature = "aegis")]
+    Aegis128L,
+    /// AEGIS-128X2.
+    #[cfg(feature = "aegis")]
+    Aegis128X2,
+    /// AEGIS-128X4.
+    #[cfg(feature = "aegis")]
+    Aegis128X4,
+    /// AEGIS-256.
+    #[cfg(feature = "aegis")]
+    Aegis256,
+    /// AEGIS-256X2.
+    #[cfg(feature = "aegis")]
+    Aegis256X2,
+    /// AEGIS-256X4.
+    #[cfg(feature = "aegis")]
+    Aegis256X4,
+    /// AES-128-CBC-HMAC-SHA-256 (`A128CBC-HS256`).
+    #[cfg(feature = "cbc")]
+    Aes128CbcHs256,
+    /// AES-256-CBC-HMAC-SHA-512 (`A256CBC-HS512`).
+    #[cfg(feature = "cbc")]
+    Aes256CbcHs512,
+}
+
+/// Evaluates `$body` with `$m` bound to the module implementing `$alg`.
+macro_rules! with_module {
+    ($alg:expr, $m:ident => $body:expr) => {
+        match $alg {
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes128Gcm => {
+                use crate::aes128gcm as $m;
+                $body
+            }
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes256Gcm => {
+                use crate::aes256gcm as $m;
+                $body
+            }
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes128Ocb => {
+                use crate::aes128ocb as $m;
+                $body
+            }
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes256Ocb => {
+                use crate::aes256ocb as $m;
+                $body
+            }
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128L => {
+                use crate::aegis128l as $m;
+                $body
+            }
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X2 => {
+                use crate::aegis128x2 as $m;
+                $body
+            }
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X4 => {
+                use crate::aegis128x4 as $m;
+                $body
+            }
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256 => {
+                use crate::aegis256 as $m;
+                $body
+            }
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X2 => {
+                use crate::aegis256x2 as $m;
+                $body
+            }
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X4 => {
+                use crate::aegis256x4 as $m;
+                $body
+            }
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes128CbcHs256 => {
+                use crate::aes128cbc_hs256 as $m;
+                $body
+            }
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes256CbcHs512 => {
+                use crate::aes256cbc_hs512 as $m;
+                $body
+            }
+        }
+    };
+}
+
+/// The number of supported algorithms.
+const ALGORITHMS: usize = 2 * cfg!(feature = "gcm") as usize
+    + 2 * cfg!(feature = "ocb") as usize
+    + 6 * cfg!(feature = "aegis") as usize
+    + 2 * cfg!(feature = "cbc") as usize;
+
+impl Algorithm {
+    /// All the supported algorithms, depending on the enabled cargo features.
+    pub const ALL: [Algorithm; ALGORITHMS] = [
+        #[cfg(feature = "gcm")]
+        Algorithm::Aes128Gcm,
+        #[cfg(feature = "gcm")]
+        Algorithm::Aes256Gcm,
+        #[cfg(feature = "ocb")]
+        Algorithm::Aes128Ocb,
+        #[cfg(feature = "ocb")]
+        Algorithm::Aes256Ocb,
+        #[cfg(feature = "aegis")]
+        Algorithm::Aegis128L,
+        #[cfg(feature = "aegis")]
+        Algorithm::Aegis128X2,
+        #[cfg(feature = "aegis")]
+        Algorithm::Aegis128X4,
+        #[cfg(feature = "aegis")]
+        Algorithm::Aegis256,
+        #[cfg(feature = "aegis")]
+        Algorithm::Aegis256X2,
+        #[cfg(feature = "aegis")]
+        Algorithm::Aegis256X4,
+        #[cfg(feature = "cbc")]
+        Algorithm::Aes128CbcHs256,
+        #[cfg(feature = "cbc")]
+        Algorithm::Aes256CbcHs512,
+    ];
+
+    /// Returns the length of the key in bytes.
+    pub fn key_len(&self) -> usize {
+        with_module!(self, m => m::KEY_LEN)
+    }
+
+    /// Returns the length of the nonce in bytes.
+    pub fn nonce_len(&self) -> usize {
+        with_module!(self, m => m::NONCE_LEN)
+    }
+
+    /// Returns the length of the authentication tag in bytes.
+    pub fn tag_len(&self) -> usize {
+        with_module!(self, m => m::TAG_LEN)
+    }
+
+    /// Returns the length of the output of `encrypt` for a message of `msg_len` bytes.
+    ///
+    /// This is `msg_len + tag_len()`, except for AES-CBC-HMAC-SHA2, whose ciphertext is padded
+    /// to a multiple of the block size.
+    pub fn ciphertext_len(&self, msg_len: usize) -> usize {
+        match self {
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes128CbcHs256 | Algorithm::Aes256CbcHs512 => {
+                (msg_len / 16 + 1) * 16 + self.tag_len()
+            }
+            #[allow(unreachable_patterns)]
+            _ => msg_len + self.tag_len(),
+        }
+    }
+
+    /// Returns the IANA AEAD registry name of the algorithm.
+    pub fn name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes128Gcm => "AEAD_AES_128_GCM",
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes256Gcm => "AEAD_AES_256_GCM",
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes128Ocb => "AEAD_AES_128_OCB_TAGLEN128",
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes256Ocb => "AEAD_AES_256_OCB_TAGLEN128",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128L => "AEAD_AEGIS128L",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X2 => "AEAD_AEGIS128X2",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X4 => "AEAD_AEGIS128X4",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256 => "AEAD_AEGIS256",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X2 => "AEAD_AEGIS256X2",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X4 => "AEAD_AEGIS256X4",
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes128CbcHs256 => "AEAD_AES_128_CBC_HMAC_SHA_256",
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes256CbcHs512 => "AEAD_AES_256_CBC_HMAC_SHA_512",
+        }
+    }
+
+    fn short_name(&self) -> &'static str {
+        match self {
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes128Gcm => "aes128gcm",
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes256Gcm => "aes256gcm",
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes128Ocb => "aes128ocb",
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes256Ocb => "aes256ocb",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128L => "aegis128l",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X2 => "aegis128x2",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X4 => "aegis128x4",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256 => "aegis256",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X2 => "aegis256x2",
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X4 => "aegis256x4",
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes128CbcHs256 => "a128cbc-hs256",
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes256CbcHs512 => "a256cbc-hs512",
+        }
+    }
+
+    /// Returns the identifier of the algorithm in the `sealed` format.
+    pub fn id(&self) -> u8 {
+        match self {
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes128Gcm => 1,
+            #[cfg(feature = "gcm")]
+            Algorithm::Aes256Gcm => 2,
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes128Ocb => 3,
+            #[cfg(feature = "ocb")]
+            Algorithm::Aes256Ocb => 4,
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128L => 5,
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X2 => 6,
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis128X4 => 7,
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256 => 8,
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X2 => 9,
+            #[cfg(feature = "aegis")]
+            Algorithm::Aegis256X4 => 10,
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes128CbcHs256 => 11,
+            #[cfg(feature = "cbc")]
+            Algorithm::Aes256CbcHs512 => 12,
+        }
+    }
+
+    /// Returns the algorithm for an identifier of the `sealed` format.
+    ///
+    /// # Returns
+    /// The algorithm, or `Err(Error::InvalidParameters)` if the identifier is unknown.
+    pub fn from_id(id: u8) -> Result<Self, Error> {
+        Self::ALL
+            .into_iter()
+            .find(|alg| alg.id() == id)
+            .ok_or(Error::InvalidParameters)
+    }
+
+    /// Generates a random key for the algorithm using the system's secure random number generator.
+    #[cfg(feature = "getrandom")]
+    pub fn generate_key(&self) -> Vec<u8> {
+        with_module!(self, m => m::generate_key().to_vec())
+    }
+
+    /// Generates a random nonce for the algorithm using the system's secure random number generator.
+    #[cfg(feature = "getrandom")]
+    pub fn generate_nonce(&self) -> Vec<u8> {
+        with_module!(self, m => m::generate_nonce().to_vec())
+    }
+
+    /// Encrypts a message and returns the ciphertext with the authentication tag appended.
+    ///
+    /// # Arguments
+    /// * `msg` - The plaintext message to encrypt.
+    /// * `ad` - Additional authenticated data (AAD).
+    /// * `key` - The secret key, `key_len()` bytes long.
+    /// * `nonce` - The nonce, `nonce_len()` bytes long.
+    ///
+    /// # Returns
+    /// Ciphertext with tag appended, `Err(Error::InvalidKeyLength)` if the key length is
+    /// invalid, `Err(Error::InvalidNonceLength)` if the nonce length is invalid, or
+    /// `Err(Error::MessageTooLong)` if the message or the associated data exceeds the
+    /// algorithm's length limit.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "gcm")] {
+    /// use aes_wasm::Algorithm;
+    /// let alg = Algorithm::Aes256Gcm;
+    /// let key = alg.generate_key();
+    /// let nonce = alg.generate_nonce();
+    /// let ciphertext = alg.encrypt(b"hello", b"ad", &key, &nonce).unwrap();
+    /// # }
+    /// ```
+    pub fn encrypt(
+        &self,
+        msg: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+        key: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        with_module!(self, m => {
+            let key: &m::Key = key.try_into().map_err(|_| Error::InvalidKeyLength)?;
+            let nonce: m::Nonce = nonce.try_into().map_err(|_| Error::InvalidNonceLength)?;
+            m::try_encrypt(msg, ad, key, nonce)
+        })
+    }
+
+    /// Decrypts a ciphertext with tag appended, returning the plaintext if verification succeeds.
+    ///
+    /// # Arguments
+    /// * `ciphertext_and_tag` - Ciphertext with tag appended.
+    /// * `ad` - Additional authenticated data (AAD).
+    /// * `key` - The secret key, `key_len()` bytes long.
+    /// * `nonce` - The nonce, `nonce_len()` bytes long.
+    ///
+    /// # Returns
+    /// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidKeyLength)` or
+    /// `Err(Error::InvalidNonceLength)` if an input has an invalid length, or
+    /// `Err(Error::VerificationFailed)` if verification fails.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "gcm")] {
+    /// use aes_wasm::Algorithm;
+    /// let alg = Algorithm::Aes256Gcm;
+    /// let key = alg.generate_key();
+    /// let nonce = alg.generate_nonce();
+    /// let ciphertext = alg.encrypt(b"hello", b"ad", &key, &nonce).unwrap();
+    /// let plaintext = alg.decrypt(ciphertext, b"ad", &key, &nonce).unwrap();
+    /// # }
+    /// ```
+    pub fn decrypt(
+        &self,
+        ciphertext_and_tag: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+        key: &[u8],
+        nonce: &[u8],
+    ) -> Result<Vec<u8>, Error> {
+        with_module!(self, m => {
+            let key: &m::Key = key.try_into().map_err(|_| Error::InvalidKeyLength)?;
+            let nonce: m::Nonce = nonce.try_into().map_err(|_| Error::InvalidNonceLength)?;
+            m::decrypt(ciphertext_and_tag, ad, key, nonce)
+        })
+    }
+}
+
+impl Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    /// Parses an IANA name (`AEAD_AES_256_GCM`), or a libsodium-style name (`aes256gcm`).
+    ///
+    /// AES-CBC-HMAC-SHA2 algorithms are also recognized by their JOSE names (`A128CBC-HS256`).
+    /// Names are case-insensitive.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        Self::ALL
+            .into_iter()
+            .find(|alg| {
+                s.eq_ignore_ascii_case(alg.name()) || s.eq_ignore_ascii_case(alg.short_name())
+            })
+            .ok_or(Error::InvalidParameters)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+
+    #[test]
+    fn algorithm() {
+        let msg = b"hello world";
+        let ad = b"ad";
+        for alg in Algorithm::ALL {
+            assert_eq!(alg.to_string().parse(), Ok(alg));
+            assert_eq!(alg.short_name().parse(), Ok(alg));
+            assert_eq!(Algorithm::from_id(alg.id()), Ok(alg));
+            let (key, nonce) = (alg.generate_key(), alg.generate_nonce());
+            assert_eq!((key.len(), nonce.len()), (alg.key_len(), alg.nonce_len()));
+            let ciphertext = alg.encrypt(msg, ad, &key, &nonce).unwrap();
+            assert_eq!(ciphertext.len(), alg.ciphertext_len(msg.len()));
+            assert_eq!(alg.decrypt(&ciphertext, ad, &key, &nonce).unwrap(), msg);
+            assert_eq!(
+                alg.decrypt(&ciphertext, b"", &key, &nonce),
+                Err(Error::VerificationFailed)
+            );
+            assert_eq!(
+                alg.encrypt(msg, ad, &key[1..], &nonce),
+                Err(Error::InvalidKeyLength)
+            );
+            assert_eq!(
+                alg.decrypt(&ciphertext, ad, &key, &nonce[1..]),
+                Err(Error::InvalidNonceLength)
+            );
+        }
+        #[cfg(feature = "gcm")]
+        assert_eq!("AES256GCM".parse(), Ok(Algorithm::Aes256Gcm));
+        #[cfg(feature = "cbc")]
+        assert_eq!("A128CBC-HS256".parse(), Ok(Algorithm::Aes128CbcHs256));
+        assert_eq!("rot13".parse::<Algorithm>(), Err(Error::InvalidParameters));
+        assert_eq!(Algorithm::from_id(0), Err(Error::InvalidParameters));
+    }
+}
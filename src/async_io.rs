@@ -0,0 +1,615 @@
+//! Asynchronous streaming encryption with the STREAM construction.
+//!
+//! Requires the `async` feature.
+//!
+//! `AsyncEncryptingWriter` and `AsyncDecryptingReader` wrap asynchronous writers and readers,
+//! and implement the `AsyncWrite` and `AsyncRead` traits of both `futures-io` and `tokio`.
+//! `EncryptingStream` and `DecryptingStream` transform a `Stream` of `Bytes`, such as an HTTP
+//! body, as it is being streamed.
+//!
+//! The wire format is the same as `io::EncryptingWriter::stream`: the nonce prefix, followed by
+//! the ciphertext segments of the `stream` module, with no additional data.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(feature = "aegis")] {
+//! use aes_wasm::async_io::{AsyncDecryptingReader, AsyncEncryptingWriter};
+//! use aes_wasm::Algorithm;
+//! use futures::io::{AsyncReadExt, AsyncWriteExt};
+//! # futures::executor::block_on(async {
+//! let alg = Algorithm::Aegis256;
+//! let key = alg.generate_key();
+//!
+//! let mut writer = AsyncEncryptingWriter::new(Vec::new(), alg, &key, 4096).unwrap();
+//! writer.write_all(b"hello world").await.unwrap();
+//! writer.close().await.unwrap();
+//! let ciphertext = writer.into_inner();
+//!
+//! let mut reader = AsyncDecryptingReader::new(&ciphertext[..], alg, &key, 4096).unwrap();
+//! let mut plaintext = Vec::new();
+//! reader.read_to_end(&mut plaintext).await.unwrap();
+//! assert_eq!(plaintext, b"hello world");
+//! # });
+//! # }
+//! ```
+
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+use futures_core::Stream;
+
+use crate::stream::{nonce_prefix_len, Decryptor, Encryptor};
+pub use crate::*;
+
+/// Incremental encryptor, buffering plaintext until complete segments are available.
+struct SegmentEncoder {
+    encryptor: Option<Encryptor>,
+    buffer: Vec<u8>,
+    header: Option<Vec<u8>>,
+}
+
+impl SegmentEncoder {
+    fn new(alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        let encryptor = Encryptor::new(alg, key, segment_size)?;
+        Ok(SegmentEncoder {
+            header: Some(encryptor.nonce_prefix().to_vec()),
+            encryptor: Some(encryptor),
+            buffer: Vec::with_capacity(segment_size + 1),
+        })
+    }
+
+    /// Returns the ciphertext for the segments completed by `data`.
+    fn push(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        let encryptor = self.encryptor.as_mut().ok_or(Error::InvalidParameters)?;
+        let segment_size = encryptor.segment_size();
+        let mut out = self.header.take().unwrap_or_default();
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() > segment_size {
+            out.extend(encryptor.encrypt_next(&self.buffer[..segment_size], b"")?);
+            self.buffer.drain(..segment_size);
+        }
+        Ok(out)
+    }
+
+    /// Returns the ciphertext for the last segment.
+    fn finish(&mut self) -> Result<Vec<u8>, Error> {
+        let encryptor = self.encryptor.take().ok_or(Error::InvalidParameters)?;
+        let mut out = self.header.take().unwrap_or_default();
+        out.extend(encryptor.encrypt_last(&self.buffer, b"")?);
+        self.buffer.clear();
+        Ok(out)
+    }
+}
+
+/// Incremental decryptor, returning plaintext once segments have been verified.
+struct SegmentDecoder {
+    alg: Algorithm,
+    key: Vec<u8>,
+    segment_size: usize,
+    decryptor: Option<Decryptor>,
+    buffer: Vec<u8>,
+}
+
+impl SegmentDecoder {
+    fn new(alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        // Validate the parameters before any data is received.
+        let nonce_prefix = vec![0u8; nonce_prefix_len(alg)];
+        Decryptor::new(alg, key, &nonce_prefix, segment_size)?;
+        Ok(SegmentDecoder {
+            alg,
+            key: key.to_vec(),
+            segment_size,
+            decryptor: None,
+            buffer: Vec::new(),
+        })
+    }
+
+    /// Returns the plaintext of the segments completed by `data`.
+    ///
+    /// A segment is only decrypted once more data follows, as the last segment is decrypted
+    /// differently.
+    fn push(&mut self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.buffer.extend_from_slice(data);
+        if self.decryptor.is_none() {
+            let nonce_prefix_len = nonce_prefix_len(self.alg);
+            if self.buffer.len() < nonce_prefix_len {
+                return Ok(Vec::new());
+            }
+            let nonce_prefix = &self.buffer[..nonce_prefix_len];
+            let decryptor = Decryptor::new(self.alg, &self.key, nonce_prefix, self.segment_size)?;
+            self.decryptor = Some(decryptor);
+            self.buffer.drain(..nonce_prefix_len);
+        }
+        let decryptor = self.decryptor.as_mut().unwrap();
+        let ciphertext_segment_size = decryptor.ciphertext_segment_size();
+        let mut out = Vec::new();
+        while self.buffer.len() > ciphertext_segment_size {
+            out.extend(decryptor.decrypt_next(&self.buffer[..ciphertext_segment_size], b"")?);
+            self.buffer.drain(..ciphertext_segment_size);
+        }
+        Ok(out)
+    }
+
+    /// Returns the plaintext of the last segment, once all the data has been received.
+    fn finish(&mut self) -> Result<Vec<u8>, Error> {
+        let decryptor = self.decryptor.take().ok_or(Error::VerificationFailed)?;
+        let out = decryptor.decrypt_last(&self.buffer, b"")?;
+        self.buffer.clear();
+        Ok(out)
+    }
+}
+
+/// An asynchronous writer that encrypts data before writing it to an inner writer.
+///
+/// The stream must be closed (`close` with `futures-io`, `shutdown` with `tokio`) to write the
+/// last segment, without which it cannot be decrypted.
+pub struct AsyncEncryptingWriter<W> {
+    inner: W,
+    encoder: SegmentEncoder,
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl<W> AsyncEncryptingWriter<W> {
+    /// Creates a writer encrypting with an AEAD and a random nonce prefix.
+    ///
+    /// # Arguments
+    /// * `inner` - The writer receiving the ciphertext.
+    /// * `alg` - The AEAD algorithm.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    /// * `segment_size` - The plaintext segment size.
+    ///
+    /// # Returns
+    /// The writer, or an error if the parameters are invalid.
+    pub fn new(inner: W, alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        Ok(AsyncEncryptingWriter {
+            inner,
+            encoder: SegmentEncoder::new(alg, key, segment_size)?,
+            pending: Vec::new(),
+            pos: 0,
+        })
+    }
+
+    /// Returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn poll_pending(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut write: impl FnMut(&mut W, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+    ) -> Poll<std::io::Result<()>> {
+        while self.pos < self.pending.len() {
+            match ready!(write(&mut self.inner, cx, &self.pending[self.pos..]))? {
+                0 => return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into())),
+                n => self.pos += n,
+            }
+        }
+        self.pending.clear();
+        self.pos = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_write_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+        write: impl FnMut(&mut W, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+    ) -> Poll<std::io::Result<usize>> {
+        ready!(self.poll_pending(cx, write))?;
+        self.pending = self.encoder.push(buf).map_err(to_io_error)?;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_close_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut write: impl FnMut(&mut W, &mut Context<'_>, &[u8]) -> Poll<std::io::Result<usize>>,
+    ) -> Poll<std::io::Result<()>> {
+        ready!(self.poll_pending(cx, &mut write))?;
+        if self.encoder.encryptor.is_some() {
+            self.pending = self.encoder.finish().map_err(to_io_error)?;
+            ready!(self.poll_pending(cx, &mut write))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<W: futures_io::AsyncWrite + Unpin> futures_io::AsyncWrite for AsyncEncryptingWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut()
+            .poll_write_with(cx, buf, |w, cx, buf| Pin::new(w).poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx, |w, cx, buf| Pin::new(w).poll_write(cx, buf)))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_close_with(cx, |w, cx, buf| Pin::new(w).poll_write(cx, buf)))?;
+        Pin::new(&mut this.inner).poll_close(cx)
+    }
+}
+
+impl<W: tokio::io::AsyncWrite + Unpin> tokio::io::AsyncWrite for AsyncEncryptingWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut()
+            .poll_write_with(cx, buf, |w, cx, buf| Pin::new(w).poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_pending(cx, |w, cx, buf| Pin::new(w).poll_write(cx, buf)))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_close_with(cx, |w, cx, buf| Pin::new(w).poll_write(cx, buf)))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+/// An asynchronous reader that decrypts data read from an inner reader.
+///
+/// Only authenticated data is returned. A read error with kind `InvalidData` is returned if a
+/// segment fails to verify, including if the stream was truncated. Verification errors are
+/// sticky: every subsequent read returns the same error.
+pub struct AsyncDecryptingReader<R> {
+    inner: R,
+    decoder: SegmentDecoder,
+    scratch: Vec<u8>,
+    plaintext: Vec<u8>,
+    pos: usize,
+    finished: bool,
+    error: Option<Error>,
+}
+
+impl<R> AsyncDecryptingReader<R> {
+    /// Creates a reader decrypting a stream written by `AsyncEncryptingWriter`.
+    ///
+    /// # Arguments
+    /// * `inner` - The reader providing the ciphertext.
+    /// * `alg` - The AEAD algorithm.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    /// * `segment_size` - The plaintext segment size given to the writer.
+    ///
+    /// # Returns
+    /// The reader, or an error if the parameters are invalid.
+    pub fn new(inner: R, alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        Ok(AsyncDecryptingReader {
+            inner,
+            decoder: SegmentDecoder::new(alg, key, segment_size)?,
+            scratch: vec![0u8; alg.ciphertext_len(segment_size)],
+            plaintext: Vec::new(),
+            pos: 0,
+            finished: false,
+            error: None,
+        })
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn poll_read_with(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        mut read: impl FnMut(&mut R, &mut Context<'_>, &mut [u8]) -> Poll<std::io::Result<usize>>,
+    ) -> Poll<std::io::Result<usize>> {
+        while self.pos == self.plaintext.len() {
+            if let Some(err) = self.error {
+                return Poll::Ready(Err(to_io_error(err)));
+            }
+            if self.finished {
+                return Poll::Ready(Ok(0));
+            }
+            let n = ready!(read(&mut self.inner, cx, &mut self.scratch))?;
+            let plaintext = match n {
+                0 => self.decoder.finish().inspect(|_| self.finished = true),
+                n => self.decoder.push(&self.scratch[..n]),
+            };
+            self.plaintext = plaintext.map_err(|err| {
+                self.error = Some(err);
+                to_io_error(err)
+            })?;
+            self.pos = 0;
+        }
+        let len = buf.len().min(self.plaintext.len() - self.pos);
+        buf[..len].copy_from_slice(&self.plaintext[self.pos..self.pos + len]);
+        self.pos += len;
+        Poll::Ready(Ok(len))
+    }
+}
+
+impl<R: futures_io::AsyncRead + Unpin> futures_io::AsyncRead for AsyncDecryptingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.get_mut()
+            .poll_read_with(cx, buf, |r, cx, buf| Pin::new(r).poll_read(cx, buf))
+    }
+}
+
+impl<R: tokio::io::AsyncRead + Unpin> tokio::io::AsyncRead for AsyncDecryptingReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let len = ready!(self.get_mut().poll_read_with(
+            cx,
+            buf.initialize_unfilled(),
+            |r, cx, scratch| {
+                let mut scratch = tokio::io::ReadBuf::new(scratch);
+                ready!(Pin::new(r).poll_read(cx, &mut scratch))?;
+                Poll::Ready(Ok(scratch.filled().len()))
+            }
+        ))?;
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// A stream of encrypted chunks, produced from a stream of plaintext chunks.
+///
+/// The first chunk is the nonce prefix, and every following chunk is a ciphertext segment. An
+/// error is returned if the maximum number of segments is exceeded; the stream ends after an
+/// error.
+pub struct EncryptingStream<S> {
+    inner: S,
+    encoder: SegmentEncoder,
+    chunks: VecDeque<Bytes>,
+    finished: bool,
+}
+
+impl<S> EncryptingStream<S> {
+    /// Creates a stream encrypting the chunks of `inner` with an AEAD and a random nonce prefix.
+    ///
+    /// # Returns
+    /// The stream, or an error if the parameters are invalid.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "gcm")] {
+    /// use aes_wasm::async_io::{DecryptingStream, EncryptingStream};
+    /// use aes_wasm::Algorithm;
+    /// use bytes::Bytes;
+    /// use futures::stream::{self, TryStreamExt};
+    /// # futures::executor::block_on(async {
+    /// let alg = Algorithm::Aes256Gcm;
+    /// let key = alg.generate_key();
+    /// let body = stream::iter([Bytes::from_static(b"hello "), Bytes::from_static(b"world")]);
+    ///
+    /// let encrypted = EncryptingStream::new(body, alg, &key, 4096).unwrap();
+    /// let chunks: Vec<Bytes> = encrypted.try_collect().await.unwrap();
+    /// let decrypted = DecryptingStream::new(stream::iter(chunks), alg, &key, 4096).unwrap();
+    /// let chunks: Vec<Bytes> = decrypted.try_collect().await.unwrap();
+    /// assert_eq!(chunks.concat(), b"hello world");
+    /// # });
+    /// # }
+    /// ```
+    pub fn new(inner: S, alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        Ok(EncryptingStream {
+            inner,
+            encoder: SegmentEncoder::new(alg, key, segment_size)?,
+            chunks: VecDeque::new(),
+            finished: false,
+        })
+    }
+}
+
+impl<S: Stream<Item = Bytes> + Unpin> Stream for EncryptingStream<S> {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(chunk) = this.chunks.pop_front() {
+                return Poll::Ready(Some(Ok(chunk)));
+            }
+            if this.finished {
+                return Poll::Ready(None);
+            }
+            let ciphertext = match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(chunk) => this.encoder.push(&chunk),
+                None => {
+                    this.finished = true;
+                    this.encoder.finish()
+                }
+            };
+            match ciphertext {
+                Ok(ciphertext) if ciphertext.is_empty() => continue,
+                Ok(ciphertext) => this.chunks.push_back(Bytes::from(ciphertext)),
+                Err(err) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+        }
+    }
+}
+
+/// A stream of decrypted chunks, produced from a stream of encrypted chunks.
+///
+/// Chunks are only returned once they have been verified. An error is returned if a segment
+/// fails to verify, including if the stream was truncated; the stream ends after an error.
+pub struct DecryptingStream<S> {
+    inner: S,
+    decoder: SegmentDecoder,
+    finished: bool,
+}
+
+impl<S> DecryptingStream<S> {
+    /// Creates a stream decrypting the chunks of `inner`, as produced by `EncryptingStream`.
+    ///
+    /// # Returns
+    /// The stream, or an error if the parameters are invalid.
+    pub fn new(inner: S, alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        Ok(DecryptingStream {
+            inner,
+            decoder: SegmentDecoder::new(alg, key, segment_size)?,
+            finished: false,
+        })
+    }
+}
+
+impl<S: Stream<Item = Bytes> + Unpin> Stream for DecryptingStream<S> {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        while !this.finished {
+            let plaintext = match ready!(Pin::new(&mut this.inner).poll_next(cx)) {
+                Some(chunk) => this.decoder.push(&chunk),
+                None => {
+                    this.finished = true;
+                    this.decoder.finish()
+                }
+            };
+            match plaintext {
+                Ok(plaintext) if plaintext.is_empty() => continue,
+                Ok(plaintext) => return Poll::Ready(Some(Ok(Bytes::from(plaintext)))),
+                Err(err) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::stream::{iter, StreamExt, TryStreamExt};
+
+    #[test]
+    fn async_futures_io() {
+        use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+        let alg = Algorithm::ALL[0];
+        let key = alg.generate_key();
+        let msg: Vec<u8> = (0..100).collect();
+        block_on(async {
+            let mut writer = AsyncEncryptingWriter::new(Vec::new(), alg, &key, 16).unwrap();
+            for chunk in msg.chunks(7) {
+                writer.write_all(chunk).await.unwrap();
+            }
+            writer.close().await.unwrap();
+            let ciphertext = writer.into_inner();
+            assert_eq!(
+                stream::decrypt(alg, &key, 16, &ciphertext, b"").unwrap(),
+                msg
+            );
+
+            let mut reader = AsyncDecryptingReader::new(&ciphertext[..], alg, &key, 16).unwrap();
+            let mut plaintext = Vec::new();
+            reader.read_to_end(&mut plaintext).await.unwrap();
+            assert_eq!(plaintext, msg);
+
+            let truncated = &ciphertext[..ciphertext.len() - 16];
+            let mut reader = AsyncDecryptingReader::new(truncated, alg, &key, 16).unwrap();
+            let err = reader.read_to_end(&mut Vec::new()).await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            let err = reader.read(&mut [0u8; 16]).await.unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        });
+    }
+
+    #[test]
+    fn async_tokio() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let alg = Algorithm::ALL[Algorithm::ALL.len() - 1];
+        let key = alg.generate_key();
+        let msg: Vec<u8> = (0..100).collect();
+        block_on(async {
+            let mut writer = AsyncEncryptingWriter::new(Vec::new(), alg, &key, 32).unwrap();
+            writer.write_all(&msg).await.unwrap();
+            writer.shutdown().await.unwrap();
+            let ciphertext = writer.into_inner();
+            assert_eq!(
+                stream::decrypt(alg, &key, 32, &ciphertext, b"").unwrap(),
+                msg
+            );
+
+            let mut reader = AsyncDecryptingReader::new(&ciphertext[..], alg, &key, 32).unwrap();
+            let mut plaintext = Vec::new();
+            reader.read_to_end(&mut plaintext).await.unwrap();
+            assert_eq!(plaintext, msg);
+        });
+    }
+
+    #[test]
+    fn async_stream() {
+        let alg = Algorithm::ALL[0];
+        let key = alg.generate_key();
+        let msg: Vec<u8> = (0..100).collect();
+        let body = iter(
+            msg.chunks(9)
+                .map(Bytes::copy_from_slice)
+                .collect::<Vec<_>>(),
+        );
+        block_on(async {
+            let encrypted: Vec<Bytes> = EncryptingStream::new(body, alg, &key, 16)
+                .unwrap()
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(encrypted[0].len(), stream::nonce_prefix_len(alg));
+            let ciphertext = encrypted.concat();
+            assert_eq!(
+                stream::decrypt(alg, &key, 16, &ciphertext, b"").unwrap(),
+                msg
+            );
+
+            let chunks = ciphertext.chunks(5).map(Bytes::copy_from_slice);
+            let decrypted: Vec<_> = DecryptingStream::new(iter(chunks), alg, &key, 16)
+                .unwrap()
+                .collect()
+                .await;
+            let decrypted: Result<Vec<Bytes>, Error> = decrypted.into_iter().collect();
+            assert_eq!(decrypted.unwrap().concat(), msg);
+
+            // The nonce prefix, a complete segment, and one byte of the next one.
+            let len = stream::nonce_prefix_len(alg) + alg.ciphertext_len(16) + 1;
+            let truncated = iter([Bytes::copy_from_slice(&ciphertext[..len])]);
+            let mut decrypted = DecryptingStream::new(truncated, alg, &key, 16).unwrap();
+            assert_eq!(
+                decrypted.next().await,
+                Some(Ok(Bytes::from(msg[..16].to_vec())))
+            );
+            assert_eq!(decrypted.next().await, Some(Err(Error::VerificationFailed)));
+            assert_eq!(decrypted.next().await, None);
+
+            // Exceed the maximum number of segments.
+            let body = iter([Bytes::from(msg.clone())]);
+            let mut encrypted = EncryptingStream::new(body, alg, &key, 16).unwrap();
+            let encryptor = encrypted.encoder.encryptor.as_mut().unwrap();
+            encryptor.set_index(u32::MAX - 2);
+            assert_eq!(encrypted.next().await, Some(Err(Error::InvalidParameters)));
+            assert_eq!(encrypted.next().await, None);
+        });
+    }
+}
@@ -0,0 +1,930 @@
+//! C ABI, for WebAssembly modules written in other languages and for host embeddings.
+//!
+//! Every cipher and MAC module is exported as `extern "C"` functions prefixed with `aes_wasm_`,
+//! and declared in `include/aes_wasm.h`. Functions return `AES_WASM_OK` (0) on success, or one
+//! of the negative `AES_WASM_ERROR_*` codes.
+//!
+//! Inputs are passed as a pointer and a length, and the pointer may be `NULL` if the length is
+//! zero. The lengths of keys, nonces and IVs are checked against the algorithm.
+//!
+//! Outputs are written to a buffer passed with its capacity, and their length is stored in
+//! the `*_len` pointer, unless it is `NULL`. If the buffer is too small,
+//! `AES_WASM_ERROR_BUFFER_TOO_SMALL` is returned and the required length is stored instead.
+//! Output buffers may overlap with the inputs.
+//!
+//! ## Example
+//! ```c
+//! #include "aes_wasm.h"
+//!
+//! uint8_t key[AES_WASM_AEGIS128L_KEY_LEN] = { 0 };
+//! uint8_t nonce[AES_WASM_AEGIS128L_NONCE_LEN] = { 0 };
+//! uint8_t c[5 + AES_WASM_AEGIS128L_TAG_LEN];
+//! size_t c_len;
+//! int ret = aes_wasm_aegis128l_encrypt(c, sizeof c, &c_len, (const uint8_t *) "hello", 5,
+//!                                      NULL, 0, nonce, sizeof nonce, key, sizeof key);
+//! ```
+
+// Which helpers are used depends on the enabled algorithms.
+#![cfg_attr(
+    not(all(
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    )),
+    allow(dead_code, unused_macros)
+)]
+#![allow(clippy::too_many_arguments)]
+
+use alloc::alloc::Layout;
+use core::{ptr, slice};
+
+use crate::Error;
+
+/// The function succeeded.
+pub const AES_WASM_OK: i32 = 0;
+/// Verification of a ciphertext or a tag failed.
+pub const AES_WASM_ERROR_VERIFICATION_FAILED: i32 = -1;
+/// A pointer is `NULL` while its length isn't zero, or a parameter is invalid.
+pub const AES_WASM_ERROR_INVALID_PARAMETERS: i32 = -2;
+/// The key length doesn't match the algorithm.
+pub const AES_WASM_ERROR_INVALID_KEY_LENGTH: i32 = -3;
+/// The nonce or IV length doesn't match the algorithm.
+pub const AES_WASM_ERROR_INVALID_NONCE_LENGTH: i32 = -4;
+/// The output buffer is too small.
+pub const AES_WASM_ERROR_BUFFER_TOO_SMALL: i32 = -5;
+/// The message or the associated data exceeds the algorithm's length limit.
+pub const AES_WASM_ERROR_MESSAGE_TOO_LONG: i32 = -6;
+/// The input is too short, or its length is invalid for the algorithm.
+pub const AES_WASM_ERROR_INVALID_LENGTH: i32 = -7;
+/// The padding of an unauthenticated CBC ciphertext is invalid.
+pub const AES_WASM_ERROR_INVALID_PADDING: i32 = -8;
+/// The cipher implementation failed to encrypt the message.
+pub const AES_WASM_ERROR_ENCRYPTION_FAILED: i32 = -9;
+
+fn code(err: Error) -> i32 {
+    match err {
+        Error::VerificationFailed => AES_WASM_ERROR_VERIFICATION_FAILED,
+        Error::InvalidParameters | Error::ReseedRequired => AES_WASM_ERROR_INVALID_PARAMETERS,
+        Error::InvalidKeyLength => AES_WASM_ERROR_INVALID_KEY_LENGTH,
+        Error::InvalidNonceLength => AES_WASM_ERROR_INVALID_NONCE_LENGTH,
+        Error::MessageTooLong => AES_WASM_ERROR_MESSAGE_TOO_LONG,
+        Error::InvalidLength => AES_WASM_ERROR_INVALID_LENGTH,
+        Error::InvalidPadding => AES_WASM_ERROR_INVALID_PADDING,
+        Error::BufferTooSmall => AES_WASM_ERROR_BUFFER_TOO_SMALL,
+        Error::EncryptionFailed => AES_WASM_ERROR_ENCRYPTION_FAILED,
+    }
+}
+
+fn status(res: Result<(), i32>) -> i32 {
+    match res {
+        Ok(()) => AES_WASM_OK,
+        Err(code) => code,
+    }
+}
+
+/// Allocates `len` bytes, and returns `NULL` if `len` is zero or the allocation fails.
+///
+/// Hosts can use it to allocate the buffers passed to the other functions in the module's linear
+/// memory.
+#[no_mangle]
+pub extern "C" fn aes_wasm_alloc(len: usize) -> *mut u8 {
+    match Layout::array::<u8>(len) {
+        Ok(layout) if len != 0 => unsafe { alloc::alloc::alloc(layout) },
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Frees a buffer returned by `aes_wasm_alloc`.
+///
+/// # Safety
+/// `ptr` must be `NULL`, or have been returned by `aes_wasm_alloc(len)` and not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn aes_wasm_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        alloc::alloc::dealloc(ptr, Layout::array::<u8>(len).unwrap());
+    }
+}
+
+/// Returns the `len` bytes at `ptr`, which may be `NULL` if `len` is zero.
+unsafe fn input<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], i32> {
+    match (ptr.is_null(), len) {
+        (_, 0) => Ok(&[]),
+        (true, _) => Err(AES_WASM_ERROR_INVALID_PARAMETERS),
+        (false, _) => Ok(slice::from_raw_parts(ptr, len)),
+    }
+}
+
+/// Returns the `N` bytes at `ptr`, or `err` if `len` isn't `N`.
+unsafe fn array<const N: usize>(ptr: *const u8, len: usize, err: Error) -> Result<[u8; N], i32> {
+    input(ptr, len)?.try_into().map_err(|_| code(err))
+}
+
+/// Copies `data` to the `capacity` bytes at `out`, and stores its length in `out_len`.
+unsafe fn output(
+    out: *mut u8,
+    capacity: usize,
+    out_len: *mut usize,
+    data: &[u8],
+) -> Result<(), i32> {
+    if !out_len.is_null() {
+        *out_len = data.len();
+    }
+    if data.len() > capacity {
+        return Err(code(Error::BufferTooSmall));
+    }
+    if !data.is_empty() {
+        if out.is_null() {
+            return Err(AES_WASM_ERROR_INVALID_PARAMETERS);
+        }
+        ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    }
+    Ok(())
+}
+
+macro_rules! aead {
+    ($($family:literal => $($m:ident: $encrypt:ident, $decrypt:ident;)*)*) => {
+        $($(
+            #[doc = concat!(
+                "Encrypts a message with `", stringify!($m),
+                "`, and writes the ciphertext with the tag appended to `c`."
+            )]
+            ///
+            /// # Safety
+            /// Every pointer must be `NULL` or valid for its length, and `c_len` must be `NULL` or
+            /// valid for writes.
+            #[cfg(feature = $family)]
+            #[no_mangle]
+            pub unsafe extern "C" fn $encrypt(
+                c: *mut u8,
+                c_capacity: usize,
+                c_len: *mut usize,
+                m: *const u8,
+                m_len: usize,
+                ad: *const u8,
+                ad_len: usize,
+                nonce: *const u8,
+                nonce_len: usize,
+                key: *const u8,
+                key_len: usize,
+            ) -> i32 {
+                let ciphertext = (|| {
+                    let key = array(key, key_len, Error::InvalidKeyLength)?;
+                    let nonce = array(nonce, nonce_len, Error::InvalidNonceLength)?;
+                    crate::$m::try_encrypt(input(m, m_len)?, input(ad, ad_len)?, &key, nonce)
+                        .map_err(code)
+                })();
+                status(ciphertext.and_then(|ciphertext| output(c, c_capacity, c_len, &ciphertext)))
+            }
+
+            #[doc = concat!(
+                "Verifies and decrypts a ciphertext with the tag appended with `", stringify!($m),
+                "`, and writes the plaintext to `m`."
+            )]
+            ///
+            /// # Safety
+            /// Every pointer must be `NULL` or valid for its length, and `m_len` must be `NULL` or
+            /// valid for writes.
+            #[cfg(feature = $family)]
+            #[no_mangle]
+            pub unsafe extern "C" fn $decrypt(
+                m: *mut u8,
+                m_capacity: usize,
+                m_len: *mut usize,
+                c: *const u8,
+                c_len: usize,
+                ad: *const u8,
+                ad_len: usize,
+                nonce: *const u8,
+                nonce_len: usize,
+                key: *const u8,
+                key_len: usize,
+            ) -> i32 {
+                let plaintext = (|| {
+                    let key = array(key, key_len, Error::InvalidKeyLength)?;
+                    let nonce = array(nonce, nonce_len, Error::InvalidNonceLength)?;
+                    crate::$m::decrypt(input(c, c_len)?, input(ad, ad_len)?, &key, nonce)
+                        .map_err(code)
+                })();
+                status(plaintext.and_then(|plaintext| output(m, m_capacity, m_len, &plaintext)))
+            }
+        )*)*
+
+        #[cfg(all(test, feature = "std"))]
+        const AEADS: &[(&str, [usize; 3])] = &[$($(
+            #[cfg(feature = $family)]
+            (
+                stringify!($m),
+                [crate::$m::KEY_LEN, crate::$m::NONCE_LEN, crate::$m::TAG_LEN],
+            ),
+        )*)*];
+    };
+}
+
+aead! {
+    "aegis" =>
+        aegis128l: aes_wasm_aegis128l_encrypt, aes_wasm_aegis128l_decrypt;
+        aegis128x2: aes_wasm_aegis128x2_encrypt, aes_wasm_aegis128x2_decrypt;
+        aegis128x4: aes_wasm_aegis128x4_encrypt, aes_wasm_aegis128x4_decrypt;
+        aegis256: aes_wasm_aegis256_encrypt, aes_wasm_aegis256_decrypt;
+        aegis256x2: aes_wasm_aegis256x2_encrypt, aes_wasm_aegis256x2_decrypt;
+        aegis256x4: aes_wasm_aegis256x4_encrypt, aes_wasm_aegis256x4_decrypt;
+    "gcm" =>
+        aes128gcm: aes_wasm_aes128gcm_encrypt, aes_wasm_aes128gcm_decrypt;
+        aes256gcm: aes_wasm_aes256gcm_encrypt, aes_wasm_aes256gcm_decrypt;
+    "ocb" =>
+        aes128ocb: aes_wasm_aes128ocb_encrypt, aes_wasm_aes128ocb_decrypt;
+        aes256ocb: aes_wasm_aes256ocb_encrypt, aes_wasm_aes256ocb_decrypt;
+    "cbc" =>
+        aes128cbc_hs256: aes_wasm_aes128cbc_hs256_encrypt, aes_wasm_aes128cbc_hs256_decrypt;
+        aes256cbc_hs512: aes_wasm_aes256cbc_hs512_encrypt, aes_wasm_aes256cbc_hs512_decrypt;
+}
+
+macro_rules! cipher {
+    ($($family:literal => $($m:ident: $encrypt:ident, $decrypt:ident, $decrypted:expr;)*)*) => {
+        $($(
+            #[doc = concat!("Encrypts a message with `", stringify!($m), "`, and writes it to `c`.")]
+            ///
+            /// # Safety
+            /// Every pointer must be `NULL` or valid for its length, and `c_len` must be `NULL` or
+            /// valid for writes.
+            #[cfg(feature = $family)]
+            #[no_mangle]
+            pub unsafe extern "C" fn $encrypt(
+                c: *mut u8,
+                c_capacity: usize,
+                c_len: *mut usize,
+                m: *const u8,
+                m_len: usize,
+                iv: *const u8,
+                iv_len: usize,
+                key: *const u8,
+                key_len: usize,
+            ) -> i32 {
+                let ciphertext = (|| {
+                    let key = array(key, key_len, Error::InvalidKeyLength)?;
+                    let iv = array(iv, iv_len, Error::InvalidNonceLength)?;
+                    Ok(crate::$m::encrypt(input(m, m_len)?, &key, iv))
+                })();
+                status(ciphertext.and_then(|ciphertext| output(c, c_capacity, c_len, &ciphertext)))
+            }
+
+            #[doc = concat!(
+                "Decrypts a ciphertext with `", stringify!($m), "`, and writes the plaintext to `m`."
+            )]
+            ///
+            /// # Safety
+            /// Every pointer must be `NULL` or valid for its length, and `m_len` must be `NULL` or
+            /// valid for writes.
+            #[cfg(feature = $family)]
+            #[no_mangle]
+            pub unsafe extern "C" fn $decrypt(
+                m: *mut u8,
+                m_capacity: usize,
+                m_len: *mut usize,
+                c: *const u8,
+                c_len: usize,
+                iv: *const u8,
+                iv_len: usize,
+                key: *const u8,
+                key_len: usize,
+            ) -> i32 {
+                let plaintext = (|| {
+                    let key = array(key, key_len, Error::InvalidKeyLength)?;
+                    let iv = array(iv, iv_len, Error::InvalidNonceLength)?;
+                    $decrypted(crate::$m::decrypt(input(c, c_len)?, &key, iv))
+                })();
+                status(plaintext.and_then(|plaintext| output(m, m_capacity, m_len, &plaintext)))
+            }
+        )*)*
+
+        #[cfg(all(test, feature = "std"))]
+        const CIPHERS: &[(&str, [usize; 2])] = &[$($(
+            #[cfg(feature = $family)]
+            (stringify!($m), [crate::$m::KEY_LEN, crate::$m::IV_LEN]),
+        )*)*];
+    };
+}
+
+fn fallible(res: Result<alloc::vec::Vec<u8>, Error>) -> Result<alloc::vec::Vec<u8>, i32> {
+    res.map_err(code)
+}
+
+cipher! {
+    "cbc" =>
+        aes128cbc: aes_wasm_aes128cbc_encrypt, aes_wasm_aes128cbc_decrypt, fallible;
+        aes256cbc: aes_wasm_aes256cbc_encrypt, aes_wasm_aes256cbc_decrypt, fallible;
+    "ctr" =>
+        aes128ctr: aes_wasm_aes128ctr_encrypt, aes_wasm_aes128ctr_decrypt, Ok;
+        aes256ctr: aes_wasm_aes256ctr_encrypt, aes_wasm_aes256ctr_decrypt, Ok;
+}
+
+macro_rules! mac {
+    ($($family:literal => $($m:ident: $mac:ident, $verify:ident;)*)*) => {
+        $($(
+            #[doc = concat!(
+                "Computes the `", stringify!($m), "` tag of a message, and writes it to `tag`."
+            )]
+            ///
+            /// # Safety
+            /// Every pointer must be `NULL` or valid for its length, and `tag_len` must be `NULL`
+            /// or valid for writes.
+            #[cfg(feature = $family)]
+            #[no_mangle]
+            pub unsafe extern "C" fn $mac(
+                tag: *mut u8,
+                tag_capacity: usize,
+                tag_len: *mut usize,
+                m: *const u8,
+                m_len: usize,
+                key: *const u8,
+                key_len: usize,
+            ) -> i32 {
+                let computed = (|| {
+                    let key = array(key, key_len, Error::InvalidKeyLength)?;
+                    Ok(crate::$m::mac(input(m, m_len)?, &key))
+                })();
+                status(computed.and_then(|computed| output(tag, tag_capacity, tag_len, &computed)))
+            }
+
+            #[doc = concat!(
+                "Verifies the `", stringify!($m), "` tag of a message in constant time."
+            )]
+            ///
+            /// # Safety
+            /// Every pointer must be `NULL` or valid for its length.
+            #[cfg(feature = $family)]
+            #[no_mangle]
+            pub unsafe extern "C" fn $verify(
+                tag: *const u8,
+                tag_len: usize,
+                m: *const u8,
+                m_len: usize,
+                key: *const u8,
+                key_len: usize,
+            ) -> i32 {
+                status((|| {
+                    let key = array(key, key_len, Error::InvalidKeyLength)?;
+                    let computed = crate::$m::mac(input(m, m_len)?, &key);
+                    match crate::ct_eq(&computed, input(tag, tag_len)?) {
+                        true => Ok(()),
+                        false => Err(AES_WASM_ERROR_VERIFICATION_FAILED),
+                    }
+                })())
+            }
+        )*)*
+
+        #[cfg(all(test, feature = "std"))]
+        const MACS: &[(&str, [usize; 2])] = &[$($(
+            #[cfg(feature = $family)]
+            (stringify!($m), [crate::$m::KEY_LEN, crate::$m::TAG_LEN]),
+        )*)*];
+    };
+}
+
+mac! {
+    "cmac" =>
+        cmac_aes128: aes_wasm_cmac_aes128_mac, aes_wasm_cmac_aes128_verify;
+        pmac_aes128: aes_wasm_pmac_aes128_mac, aes_wasm_pmac_aes128_verify;
+        xcbc_aes128: aes_wasm_xcbc_aes128_mac, aes_wasm_xcbc_aes128_verify;
+}
+
+/// Computes the `poly1305_aes` tag of a message, and writes it to `tag`.
+///
+/// # Safety
+/// Every pointer must be `NULL` or valid for its length, and `tag_len` must be `NULL` or valid
+/// for writes.
+#[cfg(feature = "cmac")]
+#[no_mangle]
+pub unsafe extern "C" fn aes_wasm_poly1305_aes_mac(
+    tag: *mut u8,
+    tag_capacity: usize,
+    tag_len: *mut usize,
+    m: *const u8,
+    m_len: usize,
+    nonce: *const u8,
+    nonce_len: usize,
+    key: *const u8,
+    key_len: usize,
+) -> i32 {
+    let computed = (|| {
+        let key = array(key, key_len, Error::InvalidKeyLength)?;
+        let nonce = array(nonce, nonce_len, Error::InvalidNonceLength)?;
+        Ok(crate::poly1305_aes::mac(input(m, m_len)?, &key, nonce))
+    })();
+    status(computed.and_then(|computed| output(tag, tag_capacity, tag_len, &computed)))
+}
+
+/// Verifies the `poly1305_aes` tag of a message in constant time.
+///
+/// # Safety
+/// Every pointer must be `NULL` or valid for its length.
+#[cfg(feature = "cmac")]
+#[no_mangle]
+pub unsafe extern "C" fn aes_wasm_poly1305_aes_verify(
+    tag: *const u8,
+    tag_len: usize,
+    m: *const u8,
+    m_len: usize,
+    nonce: *const u8,
+    nonce_len: usize,
+    key: *const u8,
+    key_len: usize,
+) -> i32 {
+    status((|| {
+        let key = array(key, key_len, Error::InvalidKeyLength)?;
+        let nonce = array(nonce, nonce_len, Error::InvalidNonceLength)?;
+        let computed = crate::poly1305_aes::mac(input(m, m_len)?, &key, nonce);
+        match crate::ct_eq(&computed, input(tag, tag_len)?) {
+            true => Ok(()),
+            false => Err(AES_WASM_ERROR_VERIFICATION_FAILED),
+        }
+    })())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Renders `include/aes_wasm.h`.
+    #[cfg(all(
+        feature = "std",
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    ))]
+    fn render_header() -> String {
+        let mut h = String::from(HEADER_PRELUDE);
+        for (name, value) in [
+            ("AES_WASM_OK", AES_WASM_OK),
+            (
+                "AES_WASM_ERROR_VERIFICATION_FAILED",
+                AES_WASM_ERROR_VERIFICATION_FAILED,
+            ),
+            (
+                "AES_WASM_ERROR_INVALID_PARAMETERS",
+                AES_WASM_ERROR_INVALID_PARAMETERS,
+            ),
+            (
+                "AES_WASM_ERROR_INVALID_KEY_LENGTH",
+                AES_WASM_ERROR_INVALID_KEY_LENGTH,
+            ),
+            (
+                "AES_WASM_ERROR_INVALID_NONCE_LENGTH",
+                AES_WASM_ERROR_INVALID_NONCE_LENGTH,
+            ),
+            (
+                "AES_WASM_ERROR_BUFFER_TOO_SMALL",
+                AES_WASM_ERROR_BUFFER_TOO_SMALL,
+            ),
+            (
+                "AES_WASM_ERROR_MESSAGE_TOO_LONG",
+                AES_WASM_ERROR_MESSAGE_TOO_LONG,
+            ),
+            (
+                "AES_WASM_ERROR_INVALID_LENGTH",
+                AES_WASM_ERROR_INVALID_LENGTH,
+            ),
+            (
+                "AES_WASM_ERROR_INVALID_PADDING",
+                AES_WASM_ERROR_INVALID_PADDING,
+            ),
+            (
+                "AES_WASM_ERROR_ENCRYPTION_FAILED",
+                AES_WASM_ERROR_ENCRYPTION_FAILED,
+            ),
+        ] {
+            h += &format!("#define {name} ({value})\n");
+        }
+        h += "\n/* Memory allocation, for hosts */\n\nuint8_t *aes_wasm_alloc(size_t len);\n";
+        h += "\nvoid aes_wasm_free(uint8_t *ptr, size_t len);\n";
+
+        let defines = |h: &mut String, m: &str, names: &[&str], values: &[usize]| {
+            *h += &format!("\n/* {m} */\n\n");
+            for (name, value) in names.iter().zip(values) {
+                *h += &format!("#define AES_WASM_{}_{name} {value}\n", m.to_uppercase());
+            }
+        };
+        let function = |h: &mut String, m: &str, f: &str, params: &[&str]| {
+            *h += &format!(
+                "\nint aes_wasm_{m}_{f}(\n    {});\n",
+                params.join(",\n    ")
+            );
+        };
+        let key = "const uint8_t *key, size_t key_len";
+        for &(m, lens) in AEADS {
+            defines(&mut h, m, &["KEY_LEN", "NONCE_LEN", "TAG_LEN"], &lens);
+            let nonce = "const uint8_t *nonce, size_t nonce_len";
+            let ad = "const uint8_t *ad, size_t ad_len";
+            let encrypt = [
+                "uint8_t *c, size_t c_capacity, size_t *c_len",
+                "const uint8_t *m, size_t m_len",
+                ad,
+                nonce,
+                key,
+            ];
+            function(&mut h, m, "encrypt", &encrypt);
+            let decrypt = [
+                "uint8_t *m, size_t m_capacity, size_t *m_len",
+                "const uint8_t *c, size_t c_len",
+                ad,
+                nonce,
+                key,
+            ];
+            function(&mut h, m, "decrypt", &decrypt);
+        }
+        for &(m, lens) in CIPHERS {
+            defines(&mut h, m, &["KEY_LEN", "IV_LEN"], &lens);
+            let iv = "const uint8_t *iv, size_t iv_len";
+            let encrypt = [
+                "uint8_t *c, size_t c_capacity, size_t *c_len",
+                "const uint8_t *m, size_t m_len",
+                iv,
+                key,
+            ];
+            function(&mut h, m, "encrypt", &encrypt);
+            let decrypt = [
+                "uint8_t *m, size_t m_capacity, size_t *m_len",
+                "const uint8_t *c, size_t c_len",
+                iv,
+                key,
+            ];
+            function(&mut h, m, "decrypt", &decrypt);
+        }
+        let poly1305 = (
+            "poly1305_aes",
+            [
+                crate::poly1305_aes::KEY_LEN,
+                crate::poly1305_aes::TAG_LEN,
+                crate::poly1305_aes::NONCE_LEN,
+            ],
+        );
+        for (m, lens) in MACS
+            .iter()
+            .map(|&(m, [key_len, tag_len])| (m, vec![key_len, tag_len]))
+            .chain([(poly1305.0, poly1305.1.to_vec())])
+        {
+            let nonce = (lens.len() == 3).then_some("const uint8_t *nonce, size_t nonce_len");
+            defines(&mut h, m, &["KEY_LEN", "TAG_LEN", "NONCE_LEN"], &lens);
+            let mac = ["uint8_t *tag, size_t tag_capacity, size_t *tag_len"];
+            let verify = ["const uint8_t *tag, size_t tag_len"];
+            for (f, out) in [("mac", mac), ("verify", verify)] {
+                let params: Vec<_> = out
+                    .into_iter()
+                    .chain(["const uint8_t *m, size_t m_len"])
+                    .chain(nonce)
+                    .chain([key])
+                    .collect();
+                function(&mut h, m, f, &params);
+            }
+        }
+        h + HEADER_EPILOGUE
+    }
+
+    #[cfg(all(
+        feature = "std",
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    ))]
+    const HEADER_PRELUDE: &str = "\
+/*
+ * C interface of the aes-wasm crate, enabled by its `capi` feature.
+ *
+ * Generated by the `capi::test::header` test, run with AES_WASM_UPDATE_HEADER=1 to update it.
+ *
+ * Functions return AES_WASM_OK on success, or a negative AES_WASM_ERROR_* code.
+ *
+ * Inputs are passed as a pointer and a length, and the pointer may be NULL if the length is
+ * zero. Outputs are written to a buffer passed with its capacity, and their length is stored
+ * in the `*_len` pointer, unless it is NULL. If the buffer is too small,
+ * AES_WASM_ERROR_BUFFER_TOO_SMALL is returned and the required length is stored instead.
+ * Output buffers may overlap with the inputs.
+ *
+ * AEAD ciphertexts have the tag appended.
+ */
+
+#ifndef AES_WASM_H
+#define AES_WASM_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+
+";
+
+    #[cfg(all(
+        feature = "std",
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    ))]
+    const HEADER_EPILOGUE: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif
+";
+
+    #[cfg(all(
+        feature = "std",
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    ))]
+    #[test]
+    fn header() {
+        let expected = render_header();
+        if std::env::var_os("AES_WASM_UPDATE_HEADER").is_some() {
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/include/aes_wasm.h");
+            std::fs::write(path, &expected).unwrap();
+            return;
+        }
+        assert!(
+            include_str!("../include/aes_wasm.h") == expected,
+            "include/aes_wasm.h is outdated, run the `capi::test::header` test with \
+             AES_WASM_UPDATE_HEADER=1 to update it"
+        );
+    }
+
+    #[test]
+    fn alloc() {
+        assert!(aes_wasm_alloc(0).is_null());
+        let buf = aes_wasm_alloc(100);
+        assert!(!buf.is_null());
+        unsafe {
+            buf.write_bytes(0xaa, 100);
+            aes_wasm_free(buf, 100);
+            aes_wasm_free(ptr::null_mut(), 0);
+        }
+    }
+
+    #[cfg(all(feature = "aegis", feature = "gcm"))]
+    #[test]
+    fn aead() {
+        let (key, nonce) = ([1u8; 16], [2u8; 16]);
+        let (msg, ad) = (b"hello world", b"ad");
+        let mut c = [0u8; 64];
+        let mut c_len = 0;
+        unsafe {
+            let ret = aes_wasm_aegis128l_encrypt(
+                c.as_mut_ptr(),
+                c.len(),
+                &mut c_len,
+                msg.as_ptr(),
+                msg.len(),
+                ad.as_ptr(),
+                ad.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_OK);
+            assert_eq!(&c[..c_len], crate::aegis128l::encrypt(msg, ad, &key, nonce));
+
+            let mut m = [0u8; 16];
+            let mut m_len = 0;
+            let ret = aes_wasm_aegis128l_decrypt(
+                m.as_mut_ptr(),
+                m.len(),
+                &mut m_len,
+                c.as_ptr(),
+                c_len,
+                ad.as_ptr(),
+                ad.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_OK);
+            assert_eq!(&m[..m_len], msg);
+
+            // Size query, and a buffer that is too small.
+            let ret = aes_wasm_aegis128l_decrypt(
+                ptr::null_mut(),
+                0,
+                &mut m_len,
+                c.as_ptr(),
+                c_len,
+                ad.as_ptr(),
+                ad.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!((ret, m_len), (AES_WASM_ERROR_BUFFER_TOO_SMALL, msg.len()));
+
+            let ret = aes_wasm_aegis128l_decrypt(
+                m.as_mut_ptr(),
+                m.len(),
+                ptr::null_mut(),
+                c.as_ptr(),
+                c_len,
+                core::ptr::null(),
+                0,
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_ERROR_VERIFICATION_FAILED);
+
+            let ret = aes_wasm_aes256gcm_encrypt(
+                c.as_mut_ptr(),
+                c.len(),
+                &mut c_len,
+                msg.as_ptr(),
+                msg.len(),
+                core::ptr::null(),
+                0,
+                nonce.as_ptr(),
+                12,
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_ERROR_INVALID_KEY_LENGTH);
+            let ret = aes_wasm_aes128gcm_encrypt(
+                c.as_mut_ptr(),
+                c.len(),
+                &mut c_len,
+                msg.as_ptr(),
+                msg.len(),
+                core::ptr::null(),
+                0,
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_ERROR_INVALID_NONCE_LENGTH);
+            let ret = aes_wasm_aes128gcm_encrypt(
+                c.as_mut_ptr(),
+                c.len(),
+                &mut c_len,
+                core::ptr::null(),
+                msg.len(),
+                core::ptr::null(),
+                0,
+                nonce.as_ptr(),
+                12,
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_ERROR_INVALID_PARAMETERS);
+        }
+    }
+
+    #[cfg(all(feature = "cbc", feature = "ctr"))]
+    #[test]
+    fn cipher() {
+        let (key, iv) = ([1u8; 16], [2u8; 16]);
+        let msg = b"hello world";
+        let mut buf = [0u8; 32];
+        buf[..msg.len()].copy_from_slice(msg);
+        let mut len = 0;
+        unsafe {
+            // In place.
+            let ret = aes_wasm_aes128cbc_encrypt(
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut len,
+                buf.as_mut_ptr(),
+                msg.len(),
+                iv.as_ptr(),
+                iv.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!((ret, len), (AES_WASM_OK, 16));
+            assert_eq!(&buf[..len], crate::aes128cbc::encrypt(msg, &key, iv));
+            let ret = aes_wasm_aes128cbc_decrypt(
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut len,
+                buf.as_mut_ptr(),
+                len,
+                iv.as_ptr(),
+                iv.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!((ret, &buf[..len]), (AES_WASM_OK, &msg[..]));
+
+            let ret = aes_wasm_aes128ctr_encrypt(
+                buf.as_mut_ptr(),
+                5,
+                &mut len,
+                msg.as_ptr(),
+                msg.len(),
+                iv.as_ptr(),
+                iv.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!((ret, len), (AES_WASM_ERROR_BUFFER_TOO_SMALL, msg.len()));
+            let ret = aes_wasm_aes128ctr_decrypt(
+                buf.as_mut_ptr(),
+                buf.len(),
+                &mut len,
+                msg.as_ptr(),
+                msg.len(),
+                iv.as_ptr(),
+                8,
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_ERROR_INVALID_NONCE_LENGTH);
+        }
+    }
+
+    #[cfg(feature = "cmac")]
+    #[test]
+    fn mac() {
+        let key = [1u8; 32];
+        let nonce = [2u8; 16];
+        let msg = b"hello world";
+        let mut tag = [0u8; 16];
+        let mut tag_len = 0;
+        unsafe {
+            let ret = aes_wasm_cmac_aes128_mac(
+                tag.as_mut_ptr(),
+                tag.len(),
+                &mut tag_len,
+                msg.as_ptr(),
+                msg.len(),
+                key.as_ptr(),
+                16,
+            );
+            assert_eq!((ret, tag_len), (AES_WASM_OK, 16));
+            assert_eq!(tag, crate::cmac_aes128::mac(msg, &[1u8; 16]));
+            let verify = |tag: &[u8], msg: &[u8]| {
+                aes_wasm_cmac_aes128_verify(
+                    tag.as_ptr(),
+                    tag.len(),
+                    msg.as_ptr(),
+                    msg.len(),
+                    key.as_ptr(),
+                    16,
+                )
+            };
+            assert_eq!(verify(&tag, msg), AES_WASM_OK);
+            assert_eq!(verify(&tag, b"hello"), AES_WASM_ERROR_VERIFICATION_FAILED);
+            assert_eq!(verify(&tag[..8], msg), AES_WASM_ERROR_VERIFICATION_FAILED);
+
+            let ret = aes_wasm_poly1305_aes_mac(
+                tag.as_mut_ptr(),
+                tag.len(),
+                &mut tag_len,
+                msg.as_ptr(),
+                msg.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_OK);
+            let ret = aes_wasm_poly1305_aes_verify(
+                tag.as_ptr(),
+                tag_len,
+                msg.as_ptr(),
+                msg.len(),
+                nonce.as_ptr(),
+                nonce.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_OK);
+            let ret = aes_wasm_xcbc_aes128_mac(
+                tag.as_mut_ptr(),
+                tag.len(),
+                &mut tag_len,
+                msg.as_ptr(),
+                msg.len(),
+                key.as_ptr(),
+                key.len(),
+            );
+            assert_eq!(ret, AES_WASM_ERROR_INVALID_KEY_LENGTH);
+        }
+    }
+}
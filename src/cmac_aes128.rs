@@ -4,18 +4,22 @@
 //!
 //! ## Example
 //! ```rust
-//! use aes_wasm::cmac_aes128::{mac, Key};
-//! let key = Key::default();
+//! use aes_wasm::cmac_aes128::{mac, generate_key};
+//! let key = generate_key();
 //! let msg = b"hello";
 //! let tag = mac(msg, &key);
 //! ```
 
-mod zig {
+#[cfg(zig_backend)]
+mod backend {
     extern "C" {
         pub fn cmac_aes128(tag: *mut u8, m: *const u8, m_len: usize, k: *const u8) -> i32;
     }
 }
 
+#[cfg(not(zig_backend))]
+use crate::soft as backend;
+
 pub use crate::*;
 
 /// The length of the key in bytes.
@@ -32,6 +36,23 @@ pub type Key = [u8; KEY_LEN];
 /// Tag type for CMAC-AES128 (16 bytes).
 pub type Tag = [u8; TAG_LEN];
 
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::cmac_aes128::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
 /// Computes the CMAC (Cipher-based Message Authentication Code) for a message using AES-128.
 ///
 /// # Arguments
@@ -49,12 +70,34 @@ pub type Tag = [u8; TAG_LEN];
 /// let tag = mac(msg, &key);
 /// ```
 pub fn mac(msg: impl AsRef<[u8]>, key: &Key) -> Tag {
+    try_mac(msg, key).expect("CMAC-AES-128 MAC computation failed")
+}
+
+/// Computes the CMAC for a message using AES-128, reporting backend failures.
+///
+/// # Arguments
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// Authentication tag as a 16-byte array, or
+/// `Err(Error::EncryptionFailed)` if the cipher implementation failed.
+///
+/// # Example
+/// ```
+/// use aes_wasm::cmac_aes128::{try_mac, Key};
+/// let key = Key::default();
+/// let tag = try_mac(b"hello", &key).unwrap();
+/// ```
+pub fn try_mac(msg: impl AsRef<[u8]>, key: &Key) -> Result<Tag, Error> {
     let msg = msg.as_ref();
     let mut tag = Tag::default();
-    unsafe {
-        zig::cmac_aes128(tag.as_mut_ptr(), msg.as_ptr(), msg.len(), key.as_ptr());
-    };
-    tag
+    let res =
+        unsafe { backend::cmac_aes128(tag.as_mut_ptr(), msg.as_ptr(), msg.len(), key.as_ptr()) };
+    if res != 0 {
+        return Err(Error::EncryptionFailed);
+    }
+    Ok(tag)
 }
 
 #[cfg(test)]
@@ -0,0 +1,573 @@
+//! AES-256 CTR_DRBG deterministic random bit generator (NIST SP 800-90A) for WASI.
+//!
+//! Provides a seedable cryptographically secure random bit generator, with or without
+//! the block cipher derivation function, supporting reseeding, prediction resistance,
+//! personalization strings and additional input.
+//!
+//! With the `rand_core` cargo feature, `CtrDrbg` implements `rand_core::RngCore`,
+//! `rand_core::CryptoRng` and `rand_core::SeedableRng`.
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::drbg::CtrDrbg;
+//! let mut drbg = CtrDrbg::from_entropy(b"my application");
+//! let mut out = [0u8; 32];
+//! drbg.fill(&mut out).unwrap();
+//! ```
+
+use crate::soft::Aes;
+pub use crate::*;
+
+/// The length of the seed in bytes (key length + block length).
+///
+/// This is also the length of the entropy input without derivation function.
+pub const SEED_LEN: usize = 48;
+/// The minimum length of the entropy input with derivation function, in bytes.
+pub const MIN_ENTROPY_LEN: usize = 32;
+/// The minimum length of the nonce with derivation function, in bytes.
+pub const MIN_NONCE_LEN: usize = 16;
+/// The maximum number of bytes that can be requested by a single call to `generate`.
+pub const MAX_REQUEST_LEN: usize = 1 << 16;
+/// The maximum number of `generate` requests between reseeds.
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+
+/// Seed type for CTR_DRBG without derivation function (48 bytes).
+pub type Seed = [u8; SEED_LEN];
+
+type Block = [u8; 16];
+
+fn xor(a: &mut [u8], b: &[u8]) {
+    a.iter_mut().zip(b).for_each(|(a, b)| *a ^= b);
+}
+
+fn increment(v: &Block, n: u128) -> Block {
+    u128::from_be_bytes(*v).wrapping_add(n).to_be_bytes()
+}
+
+/// BCC with AES-256: CBC-MAC over the concatenation of `parts`, padded with zeros.
+fn bcc<'a>(aes: &Aes, parts: impl Iterator<Item = &'a [u8]>) -> Block {
+    let mut chaining = Block::default();
+    let mut pos = 0;
+    for &b in parts.flat_map(|part| part.iter()) {
+        if pos == 16 {
+            aes.encrypt_block(&mut chaining);
+            pos = 0;
+        }
+        chaining[pos] ^= b;
+        pos += 1;
+    }
+    aes.encrypt_block(&mut chaining);
+    chaining
+}
+
+/// Block_Cipher_df with AES-256, returning `SEED_LEN` bytes.
+fn derivation_function(input: &[&[u8]]) -> Seed {
+    let input_len: usize = input.iter().map(|x| x.len()).sum();
+    let mut lengths = [0u8; 8];
+    lengths[..4].copy_from_slice(&(input_len as u32).to_be_bytes());
+    lengths[4..].copy_from_slice(&(SEED_LEN as u32).to_be_bytes());
+
+    let k: aes256ctr::Key = core::array::from_fn(|i| i as u8);
+    let aes = Aes::new(&k);
+    let mut temp = [0u8; SEED_LEN];
+    for (i, out) in temp.chunks_mut(16).enumerate() {
+        let mut iv = Block::default();
+        iv[..4].copy_from_slice(&(i as u32).to_be_bytes());
+        let s = [&iv[..], &lengths[..]]
+            .into_iter()
+            .chain(input.iter().copied())
+            .chain([&[0x80u8][..]]);
+        out.copy_from_slice(&bcc(&aes, s));
+    }
+    let aes = Aes::new(&temp[..32]);
+    let mut x: Block = temp[32..].try_into().unwrap();
+    for out in temp.chunks_mut(16) {
+        aes.encrypt_block(&mut x);
+        out.copy_from_slice(&x);
+    }
+    temp
+}
+
+/// AES-256 CTR_DRBG state.
+///
+/// # Example
+/// ```
+/// use aes_wasm::drbg::CtrDrbg;
+/// let entropy = [0x42u8; 32];
+/// let nonce = [0x17u8; 16];
+/// let mut drbg = CtrDrbg::new(entropy, nonce, b"personalization").unwrap();
+/// let mut out = [0u8; 64];
+/// drbg.generate(&mut out, b"additional input").unwrap();
+/// ```
+pub struct CtrDrbg {
+    key: aes256ctr::Key,
+    aes: Aes,
+    v: Block,
+    reseed_counter: u64,
+    use_df: bool,
+    system_seeded: bool,
+}
+
+impl CtrDrbg {
+    fn instantiate(seed_material: &Seed, use_df: bool) -> Self {
+        let mut drbg = CtrDrbg {
+            key: aes256ctr::Key::default(),
+            aes: Aes::new(&aes256ctr::Key::default()),
+            v: Block::default(),
+            reseed_counter: 1,
+            use_df,
+            system_seeded: false,
+        };
+        drbg.update(seed_material);
+        drbg
+    }
+
+    fn update(&mut self, provided_data: &Seed) {
+        let mut blocks = [Block::default(); SEED_LEN / 16];
+        for block in blocks.iter_mut() {
+            self.v = increment(&self.v, 1);
+            *block = self.v;
+        }
+        self.aes.encrypt_blocks(&mut blocks);
+        let mut temp: Seed = blocks.as_flattened().try_into().unwrap();
+        xor(&mut temp, provided_data);
+        self.key.copy_from_slice(&temp[..32]);
+        self.aes = Aes::new(&self.key);
+        self.v.copy_from_slice(&temp[32..]);
+    }
+
+    fn derive_input(&self, input: &[u8]) -> Result<Seed, Error> {
+        if self.use_df {
+            return Ok(derivation_function(&[input]));
+        }
+        if input.len() > SEED_LEN {
+            return Err(Error::InvalidParameters);
+        }
+        let mut seed = [0u8; SEED_LEN];
+        seed[..input.len()].copy_from_slice(input);
+        Ok(seed)
+    }
+
+    /// Instantiates a CTR_DRBG with the derivation function.
+    ///
+    /// # Arguments
+    /// * `entropy_input` - Entropy input, at least `MIN_ENTROPY_LEN` bytes.
+    /// * `nonce` - Nonce, at least `MIN_NONCE_LEN` bytes.
+    /// * `personalization` - Optional personalization string. May be empty.
+    ///
+    /// # Returns
+    /// The new generator, or `Err(Error::InvalidParameters)` if the entropy input or the nonce
+    /// is too short.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::drbg::CtrDrbg;
+    /// let drbg = CtrDrbg::new([0x42u8; 32], [0x17u8; 16], b"").unwrap();
+    /// ```
+    pub fn new(
+        entropy_input: impl AsRef<[u8]>,
+        nonce: impl AsRef<[u8]>,
+        personalization: impl AsRef<[u8]>,
+    ) -> Result<Self, Error> {
+        let (entropy_input, nonce) = (entropy_input.as_ref(), nonce.as_ref());
+        if entropy_input.len() < MIN_ENTROPY_LEN || nonce.len() < MIN_NONCE_LEN {
+            return Err(Error::InvalidParameters);
+        }
+        let seed = derivation_function(&[entropy_input, nonce, personalization.as_ref()]);
+        Ok(Self::instantiate(&seed, true))
+    }
+
+    /// Instantiates a CTR_DRBG without the derivation function.
+    ///
+    /// The entropy input must be full-entropy.
+    ///
+    /// # Arguments
+    /// * `entropy_input` - Reference to the 48-byte entropy input.
+    /// * `personalization` - Optional personalization string, up to `SEED_LEN` bytes.
+    ///
+    /// # Returns
+    /// The new generator, or `Err(Error::InvalidParameters)` if the personalization
+    /// string is too long.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::drbg::{CtrDrbg, Seed};
+    /// let drbg = CtrDrbg::new_without_df(&[0x42u8; 48], b"").unwrap();
+    /// ```
+    pub fn new_without_df(
+        entropy_input: &Seed,
+        personalization: impl AsRef<[u8]>,
+    ) -> Result<Self, Error> {
+        let personalization = personalization.as_ref();
+        if personalization.len() > SEED_LEN {
+            return Err(Error::InvalidParameters);
+        }
+        let mut seed = *entropy_input;
+        xor(&mut seed, personalization);
+        Ok(Self::instantiate(&seed, false))
+    }
+
+    /// Instantiates a CTR_DRBG with the derivation function, seeded by the system's
+    /// secure random number generator.
+    ///
+    /// The generator automatically reseeds itself from the system when the reseed
+    /// interval is reached.
+    ///
+    /// # Arguments
+    /// * `personalization` - Optional personalization string. May be empty.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::drbg::CtrDrbg;
+    /// let drbg = CtrDrbg::from_entropy(b"");
+    /// ```
+    #[cfg(feature = "getrandom")]
+    pub fn from_entropy(personalization: impl AsRef<[u8]>) -> Self {
+        let mut entropy_input = [0u8; MIN_ENTROPY_LEN + MIN_NONCE_LEN];
+        random_bytes(&mut entropy_input);
+        let (entropy_input, nonce) = entropy_input.split_at(MIN_ENTROPY_LEN);
+        let mut drbg = Self::new(entropy_input, nonce, personalization).unwrap();
+        drbg.system_seeded = true;
+        drbg
+    }
+
+    /// Reseeds the generator.
+    ///
+    /// # Arguments
+    /// * `entropy_input` - Entropy input: at least `MIN_ENTROPY_LEN` bytes with the derivation
+    ///   function, exactly `SEED_LEN` bytes without.
+    /// * `additional_input` - Optional additional input. May be empty. Up to `SEED_LEN` bytes
+    ///   without the derivation function.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if an input has an invalid length.
+    pub fn reseed(
+        &mut self,
+        entropy_input: impl AsRef<[u8]>,
+        additional_input: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let (entropy_input, additional_input) = (entropy_input.as_ref(), additional_input.as_ref());
+        let seed = if self.use_df {
+            if entropy_input.len() < MIN_ENTROPY_LEN {
+                return Err(Error::InvalidParameters);
+            }
+            derivation_function(&[entropy_input, additional_input])
+        } else {
+            if entropy_input.len() != SEED_LEN {
+                return Err(Error::InvalidParameters);
+            }
+            let mut seed = self.derive_input(additional_input)?;
+            xor(&mut seed, entropy_input);
+            seed
+        };
+        self.update(&seed);
+        self.reseed_counter = 1;
+        Ok(())
+    }
+
+    /// Generates pseudorandom bytes.
+    ///
+    /// # Arguments
+    /// * `out` - Output buffer, up to `MAX_REQUEST_LEN` bytes.
+    /// * `additional_input` - Optional additional input. May be empty. Up to `SEED_LEN` bytes
+    ///   without the derivation function.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, `Err(Error::InvalidParameters)` if an input has an invalid length,
+    /// or `Err(Error::ReseedRequired)` if the reseed interval was reached.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::drbg::CtrDrbg;
+    /// let mut drbg = CtrDrbg::new([0x42u8; 32], [0x17u8; 16], b"").unwrap();
+    /// let mut out = [0u8; 64];
+    /// drbg.generate(&mut out, b"").unwrap();
+    /// ```
+    pub fn generate(
+        &mut self,
+        out: &mut [u8],
+        additional_input: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let additional_input = additional_input.as_ref();
+        if out.len() > MAX_REQUEST_LEN {
+            return Err(Error::InvalidParameters);
+        }
+        if self.reseed_counter > RESEED_INTERVAL {
+            if !self.system_seeded {
+                return Err(Error::ReseedRequired);
+            }
+            #[cfg(feature = "getrandom")]
+            {
+                let mut entropy_input = [0u8; SEED_LEN];
+                random_bytes(&mut entropy_input);
+                self.reseed(entropy_input, [])?;
+            }
+        }
+        let mut additional = [0u8; SEED_LEN];
+        if !additional_input.is_empty() {
+            additional = self.derive_input(additional_input)?;
+            self.update(&additional);
+        }
+        out.fill(0);
+        aes256ctr::apply_keystream(out, &self.key, increment(&self.v, 1));
+        self.v = increment(&self.v, out.len().div_ceil(16) as u128);
+        self.update(&additional);
+        self.reseed_counter += 1;
+        Ok(())
+    }
+
+    /// Generates pseudorandom bytes with prediction resistance.
+    ///
+    /// The generator is reseeded with fresh entropy and the additional input before
+    /// producing the output.
+    ///
+    /// # Arguments
+    /// * `out` - Output buffer, up to `MAX_REQUEST_LEN` bytes.
+    /// * `entropy_input` - Fresh entropy input, with the same requirements as for `reseed`.
+    /// * `additional_input` - Optional additional input. May be empty.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if an input has an invalid length.
+    pub fn generate_with_prediction_resistance(
+        &mut self,
+        out: &mut [u8],
+        entropy_input: impl AsRef<[u8]>,
+        additional_input: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        if out.len() > MAX_REQUEST_LEN {
+            return Err(Error::InvalidParameters);
+        }
+        self.reseed(entropy_input, additional_input)?;
+        self.generate(out, [])
+    }
+
+    /// Fills a buffer of any length with pseudorandom bytes.
+    ///
+    /// Large buffers are split into multiple requests of at most `MAX_REQUEST_LEN` bytes.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::ReseedRequired)` if the reseed interval was reached.
+    pub fn fill(&mut self, out: &mut [u8]) -> Result<(), Error> {
+        out.chunks_mut(MAX_REQUEST_LEN)
+            .try_for_each(|chunk| self.generate(chunk, []))
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::RngCore for CtrDrbg {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.fill(dest).expect("CTR_DRBG reseed required")
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill(dest).map_err(|_| {
+            let code = core::num::NonZeroU32::new(rand_core::Error::CUSTOM_START).unwrap();
+            rand_core::Error::from(code)
+        })
+    }
+}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::CryptoRng for CtrDrbg {}
+
+#[cfg(feature = "rand_core")]
+impl rand_core::SeedableRng for CtrDrbg {
+    type Seed = [u8; MIN_ENTROPY_LEN];
+
+    /// Instantiates a CTR_DRBG with the derivation function, using `seed` as the entropy
+    /// input and an all-zero nonce.
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(seed, [0u8; MIN_NONCE_LEN], []).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // NIST CAVP CTR_DRBG vectors for AES-256. The vectors with a derivation function come from
+    // the CAVS 14.3 files, as shipped with Mbed TLS's test suite, and the reseeded vector
+    // without derivation function from BoringSSL's CTR_DRBG test. CAVP procedure: instantiate,
+    // (reseed), generate twice, and check the output of the second call.
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn ctr_drbg_df() {
+        // [AES-256 use df], no reseed, without and with additional input.
+        let mut drbg = CtrDrbg::new(
+            hex("36401940fa8b1fba91a1661f211d78a0b9389a74e5bccfece8d766af1a6d3b14"),
+            hex("496f25b0f1301b4f501be30380a137eb"),
+            [],
+        )
+        .unwrap();
+        let mut out = [0u8; 64];
+        drbg.generate(&mut out, []).unwrap();
+        drbg.generate(&mut out, []).unwrap();
+        assert_eq!(
+            out[..],
+            hex(
+                "5862eb38bd558dd978a696e6df164782ddd887e7e9a6c9f3f1fbafb78941b535\
+                 a64912dfd224c6dc7454e5250b3d97165e16260c2faf1cc7735cb75fb4f07e1d"
+            )
+        );
+
+        let mut drbg = CtrDrbg::new(
+            hex("8148d65d86513ce7d38923ec2f26b9e7c677dcc8997e325b7372619e753ed944"),
+            hex("41c71a24d17d974190982bb7515ce7f5"),
+            [],
+        )
+        .unwrap();
+        drbg.generate(
+            &mut out,
+            hex("55b446046c2d14bdd0cdba4b71873fd4762650695a11507949462da8d964ab6a"),
+        )
+        .unwrap();
+        drbg.generate(
+            &mut out,
+            hex("91468f1a097d99ee339462ca916cb4a10f63d53850a4f17f598eac490299b02e"),
+        )
+        .unwrap();
+        assert_eq!(
+            out[..],
+            hex(
+                "54603d1a506132bbfa05b153a04f22a1d516cc46323cef15111af221f030f38d\
+                 6841d4670518b4914a4631af682e7421dffaac986a38e94d92bfa758e2eb101f"
+            )
+        );
+
+        // [AES-256 use df] [PredictionResistance = False], with reseed.
+        let entropy = hex(
+            "2d4c9f46b981c6a0b2b5d8c69391e569ff13851437ebc0fc00d616340252fed5\
+             93500fae4fa32b86033b7a7bac9d37e710dcc67ca266bc8607d665937766d207",
+        );
+        let mut drbg =
+            CtrDrbg::new(&entropy[..32], hex("0bf814b411f65ec4866be1abb59d3c32"), []).unwrap();
+        drbg.reseed(&entropy[32..], []).unwrap();
+        drbg.generate(&mut out, []).unwrap();
+        drbg.generate(&mut out, []).unwrap();
+        assert_eq!(
+            out[..],
+            hex(
+                "322dd28670e75c0ea638f3cb68d6a9d6e50ddfd052b772a7b1d78263a7b8978b\
+                 6740c2b65a9550c3a76325866fa97e16d74006bc96f26249b9f0a90d076f08e5"
+            )
+        );
+
+        assert_eq!(
+            CtrDrbg::new([0u8; 31], [0u8; 16], []).err(),
+            Some(Error::InvalidParameters)
+        );
+    }
+
+    #[test]
+    fn ctr_drbg_no_df() {
+        // [AES-256 no df], no reseed.
+        let entropy = hex("df5d73faa468649edda33b5cca79b0b05600419ccb7a879d\
+             dfec9db32ee494e5531b51de16a30f769262474c73bec010");
+        let mut drbg = CtrDrbg::new_without_df(entropy[..].try_into().unwrap(), []).unwrap();
+        let mut out = [0u8; 64];
+        drbg.generate(&mut out, []).unwrap();
+        drbg.generate(&mut out, []).unwrap();
+        assert_eq!(
+            out[..],
+            hex(
+                "d1c07cd95af8a7f11012c84ce48bb8cb87189e99d40fccb1771c619bdf82ab22\
+                 80b1dc2f2581f39164f7ac0c510494b3a43c41b7db17514c87b107ae793e01c5"
+            )
+        );
+
+        // [AES-256 no df], with reseed.
+        let entropy = hex(
+            "e4bc23c5089a19d86f4119cb3fa08c0a4991e0a1def17e101e4c14d9c323460a\
+             7c2fb58e0b086c6c57b55f56cae25bad",
+        );
+        let mut drbg = CtrDrbg::new_without_df(entropy[..].try_into().unwrap(), []).unwrap();
+        drbg.reseed(
+            hex(
+                "fd85a836bba85019881e8c6bad23c9061adc75477659acaea8e4a01dfe07a183\
+                 2dad1c136f59d70f8653a5dc118663d6",
+            ),
+            [],
+        )
+        .unwrap();
+        drbg.generate(&mut out, []).unwrap();
+        drbg.generate(&mut out, []).unwrap();
+        assert_eq!(
+            out[..],
+            hex(
+                "b2cb8905c05e5950ca31895096be29ea3d5a3b82b269495554eb80fe07de43e1\
+                 93b9e7c3ece73b80e062b1c1f68202fbb1c52a040ea2478864295282234aaada"
+            )
+        );
+        assert_eq!(
+            drbg.generate(&mut out, [0u8; 49]),
+            Err(Error::InvalidParameters)
+        );
+        assert_eq!(drbg.reseed([0u8; 32], []), Err(Error::InvalidParameters));
+    }
+
+    #[test]
+    fn ctr_drbg_prediction_resistance() {
+        // [AES-256 use df] [PredictionResistance = True].
+        let entropy = hex(
+            "16a1f035388cd8d956026e3b0117cb524dd3eb563f9a7720bb7dcb0fc6fbe743\
+             cf140bcd4d7130e7e3ea14046c56442b57c43b34ad219553e7105c18f6e561af\
+             e27c9f0be60d82d6cc474efb7fc737b16a6895d9a3a45b971d19b743c1a4ac8f",
+        );
+        let mut drbg =
+            CtrDrbg::new(&entropy[..32], hex("a2d015f22d854e29de278d910c573de5"), []).unwrap();
+        let mut out = [0u8; 64];
+        drbg.generate_with_prediction_resistance(&mut out, &entropy[32..64], [])
+            .unwrap();
+        drbg.generate_with_prediction_resistance(&mut out, &entropy[64..], [])
+            .unwrap();
+        assert_eq!(
+            out[..],
+            hex(
+                "b4e8395bcb7503410a94633f70e9904a5b30e62c35bc6dd2a03496c4a49932e1\
+                 84fbffdbcf1de1c72c50d36dc2ae8f04f40f96aae159c3fb816ca16df99b6c3e"
+            )
+        );
+    }
+
+    #[test]
+    fn ctr_drbg_reseed_interval() {
+        let mut drbg = CtrDrbg::new_without_df(&[0u8; 48], []).unwrap();
+        drbg.reseed_counter = RESEED_INTERVAL + 1;
+        let mut out = [0u8; 16];
+        assert_eq!(drbg.generate(&mut out, []), Err(Error::ReseedRequired));
+        drbg.reseed([0u8; 48], []).unwrap();
+        assert!(drbg.generate(&mut out, []).is_ok());
+
+        let mut drbg = CtrDrbg::from_entropy([]);
+        drbg.reseed_counter = RESEED_INTERVAL + 1;
+        let mut out = vec![0u8; MAX_REQUEST_LEN + 1];
+        assert!(drbg.fill(&mut out).is_ok());
+        assert_eq!(drbg.generate(&mut out, []), Err(Error::InvalidParameters));
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn ctr_drbg_rand_core() {
+        use rand_core::{RngCore, SeedableRng};
+        let mut rng = CtrDrbg::from_seed([0u8; 32]);
+        let mut drbg = CtrDrbg::new([0u8; 32], [0u8; 16], []).unwrap();
+        let mut expected = [0u8; 8];
+        drbg.generate(&mut expected, []).unwrap();
+        assert_eq!(rng.next_u64(), u64::from_le_bytes(expected));
+    }
+}
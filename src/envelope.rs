@@ -0,0 +1,341 @@
+//! Envelope encryption.
+//!
+//! `seal` generates a fresh data encryption key (DEK), encrypts the message with it, wraps the
+//! DEK with a key encryption key (KEK), and serializes everything into a single envelope.
+//! `open` unwraps the DEK with the same KEK and decrypts the message.
+//!
+//! KEKs implement the `KeyEncryptionKey` trait. `LocalKek` wraps DEKs with a key held in
+//! memory; a remote key management service can be used by implementing the trait on top of
+//! its encrypt and decrypt operations, so that the KEK never leaves the service.
+//!
+//! ## Format
+//!
+//! | length | content                                   |
+//! | -----: | :---------------------------------------- |
+//! |      1 | format version (`1`)                      |
+//! |      1 | algorithm identifier (see `sealed`)       |
+//! |      1 | KEK identifier length `k`                 |
+//! |      k | KEK identifier                            |
+//! |      2 | wrapped DEK length `w` (big-endian)       |
+//! |      w | wrapped DEK                               |
+//! |      n | nonce                                     |
+//! |        | ciphertext and tag                        |
+//!
+//! The DEK is wrapped with the version, algorithm identifier and KEK identifier as additional
+//! data. The message is encrypted with the whole header, up to the wrapped DEK, followed by
+//! the caller's additional data.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(all(feature = "aegis", feature = "gcm"))] {
+//! use aes_wasm::envelope::{open, seal, LocalKek};
+//! use aes_wasm::Algorithm;
+//! let kek = LocalKek::generate(b"kek-2024", Algorithm::Aes256Gcm).unwrap();
+//! let envelope = seal(&kek, Algorithm::Aegis256, b"hello", b"ad").unwrap();
+//! let plaintext = open(&kek, &envelope, b"ad").unwrap();
+//! assert_eq!(plaintext, b"hello");
+//! # }
+//! ```
+
+pub use crate::*;
+
+/// The format version written by `seal`.
+pub const VERSION: u8 = 1;
+/// The maximum length of a KEK identifier in bytes.
+pub const MAX_KEK_ID_LEN: usize = 255;
+/// The maximum length of a wrapped DEK in bytes.
+pub const MAX_WRAPPED_DEK_LEN: usize = 65535;
+
+/// A key encryption key, used to wrap and unwrap data encryption keys.
+///
+/// Implementations can keep the key locally (see `LocalKek`), or delegate to a remote key
+/// management service.
+pub trait KeyEncryptionKey {
+    /// Returns the identifier of the KEK, stored in envelopes in the clear.
+    ///
+    /// The identifier must not exceed `MAX_KEK_ID_LEN` bytes.
+    fn kek_id(&self) -> &[u8];
+
+    /// Wraps (encrypts) a data encryption key.
+    ///
+    /// # Arguments
+    /// * `dek` - The data encryption key.
+    /// * `ad` - Additional authenticated data, that must be given to `unwrap` as well.
+    ///
+    /// # Returns
+    /// The wrapped key, at most `MAX_WRAPPED_DEK_LEN` bytes long.
+    fn wrap(&self, dek: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error>;
+
+    /// Unwraps (decrypts) a data encryption key.
+    ///
+    /// # Arguments
+    /// * `wrapped_dek` - The output of `wrap`.
+    /// * `ad` - Additional authenticated data, as given to `wrap`.
+    ///
+    /// # Returns
+    /// `Ok(dek)` if verification succeeds, or an error otherwise.
+    fn unwrap(&self, wrapped_dek: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error>;
+}
+
+/// A key encryption key held in memory.
+///
+/// DEKs are wrapped using the `sealed` format.
+#[derive(Clone)]
+pub struct LocalKek {
+    kek_id: Vec<u8>,
+    alg: Algorithm,
+    key: Vec<u8>,
+}
+
+impl LocalKek {
+    /// Creates a KEK from an existing key.
+    ///
+    /// # Arguments
+    /// * `kek_id` - The identifier of the KEK, at most `MAX_KEK_ID_LEN` bytes long.
+    /// * `alg` - The AEAD algorithm used to wrap DEKs.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    ///
+    /// # Returns
+    /// The KEK, `Err(Error::InvalidKeyLength)` if the key length is invalid, or
+    /// `Err(Error::InvalidParameters)` if the identifier is too long.
+    pub fn new(kek_id: impl AsRef<[u8]>, alg: Algorithm, key: &[u8]) -> Result<Self, Error> {
+        let kek_id = kek_id.as_ref();
+        if kek_id.len() > MAX_KEK_ID_LEN {
+            return Err(Error::InvalidParameters);
+        }
+        if key.len() != alg.key_len() {
+            return Err(Error::InvalidKeyLength);
+        }
+        Ok(LocalKek {
+            kek_id: kek_id.to_vec(),
+            alg,
+            key: key.to_vec(),
+        })
+    }
+
+    /// Creates a KEK with a random key.
+    ///
+    /// # Returns
+    /// The KEK, or `Err(Error::InvalidParameters)` if the identifier is too long.
+    pub fn generate(kek_id: impl AsRef<[u8]>, alg: Algorithm) -> Result<Self, Error> {
+        Self::new(kek_id, alg, &alg.generate_key())
+    }
+}
+
+impl KeyEncryptionKey for LocalKek {
+    fn kek_id(&self) -> &[u8] {
+        &self.kek_id
+    }
+
+    fn wrap(&self, dek: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+        sealed::seal(self.alg, dek, ad, &self.key)
+    }
+
+    fn unwrap(&self, wrapped_dek: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+        sealed::open(wrapped_dek, ad, &self.key)
+    }
+}
+
+fn wrap_ad(alg: Algorithm, kek_id: &[u8]) -> Vec<u8> {
+    [&[VERSION, alg.id()][..], kek_id].concat()
+}
+
+struct Parsed<'a> {
+    alg: Algorithm,
+    kek_id: &'a [u8],
+    wrapped_dek: &'a [u8],
+    header: &'a [u8],
+    nonce: &'a [u8],
+    ciphertext_and_tag: &'a [u8],
+}
+
+fn parse(envelope: &[u8]) -> Result<Parsed<'_>, Error> {
+    let (alg, kek_id_len) = match envelope {
+        [VERSION, id, kek_id_len, ..] => (Algorithm::from_id(*id)?, *kek_id_len as usize),
+        _ => return Err(Error::InvalidParameters),
+    };
+    let kek_id = envelope
+        .get(3..3 + kek_id_len)
+        .ok_or(Error::InvalidParameters)?;
+    let pos = 3 + kek_id_len;
+    let wrapped_dek_len = match envelope.get(pos..pos + 2) {
+        Some(len) => u16::from_be_bytes([len[0], len[1]]) as usize,
+        None => return Err(Error::InvalidParameters),
+    };
+    let header_len = pos + 2 + wrapped_dek_len;
+    if envelope.len() < header_len + alg.nonce_len() + alg.tag_len() {
+        return Err(Error::InvalidParameters);
+    }
+    let (header, rest) = envelope.split_at(header_len);
+    let (nonce, ciphertext_and_tag) = rest.split_at(alg.nonce_len());
+    Ok(Parsed {
+        alg,
+        kek_id,
+        wrapped_dek: &header[pos + 2..],
+        header,
+        nonce,
+        ciphertext_and_tag,
+    })
+}
+
+/// Returns the identifier of the KEK needed to open an envelope.
+///
+/// # Returns
+/// The KEK identifier, or `Err(Error::InvalidParameters)` if the envelope is malformed.
+pub fn kek_id(envelope: &[u8]) -> Result<&[u8], Error> {
+    parse(envelope).map(|parsed| parsed.kek_id)
+}
+
+/// Encrypts a message with a fresh DEK, wrapped by the given KEK.
+///
+/// # Arguments
+/// * `kek` - The key encryption key.
+/// * `alg` - The AEAD algorithm used to encrypt the message.
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD), not included in the output.
+///
+/// # Returns
+/// The serialized envelope, `Err(Error::InvalidParameters)` if the KEK identifier or the
+/// wrapped DEK is too long, or the error returned by the KEK.
+pub fn seal<K: KeyEncryptionKey + ?Sized>(
+    kek: &K,
+    alg: Algorithm,
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let kek_id = kek.kek_id();
+    if kek_id.len() > MAX_KEK_ID_LEN {
+        return Err(Error::InvalidParameters);
+    }
+    let dek = alg.generate_key();
+    let wrapped_dek = kek.wrap(&dek, &wrap_ad(alg, kek_id))?;
+    if wrapped_dek.len() > MAX_WRAPPED_DEK_LEN {
+        return Err(Error::InvalidParameters);
+    }
+    let mut envelope = vec![VERSION, alg.id(), kek_id.len() as u8];
+    envelope.extend_from_slice(kek_id);
+    envelope.extend_from_slice(&(wrapped_dek.len() as u16).to_be_bytes());
+    envelope.extend_from_slice(&wrapped_dek);
+    let nonce = alg.generate_nonce();
+    let header_and_ad = [&envelope[..], ad.as_ref()].concat();
+    let ciphertext_and_tag = alg.encrypt(msg, header_and_ad, &dek, &nonce)?;
+    envelope.reserve(nonce.len() + ciphertext_and_tag.len());
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext_and_tag);
+    Ok(envelope)
+}
+
+/// Decrypts an envelope.
+///
+/// # Arguments
+/// * `kek` - The key encryption key used by `seal`.
+/// * `envelope` - The serialized envelope.
+/// * `ad` - Additional authenticated data (AAD), as given to `seal`.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidParameters)` if the envelope
+/// is malformed, or `Err(Error::VerificationFailed)` if the envelope was not sealed with this
+/// KEK or if verification fails.
+pub fn open<K: KeyEncryptionKey + ?Sized>(
+    kek: &K,
+    envelope: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let parsed = parse(envelope.as_ref())?;
+    if parsed.kek_id != kek.kek_id() {
+        return Err(Error::VerificationFailed);
+    }
+    let dek = kek.unwrap(parsed.wrapped_dek, &wrap_ad(parsed.alg, parsed.kek_id))?;
+    if dek.len() != parsed.alg.key_len() {
+        return Err(Error::VerificationFailed);
+    }
+    let header_and_ad = [parsed.header, ad.as_ref()].concat();
+    parsed
+        .alg
+        .decrypt(parsed.ciphertext_and_tag, header_and_ad, &dek, parsed.nonce)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    #[cfg(feature = "gcm")]
+    use core::cell::Cell;
+
+    // Stands in for a remote key management service: the KEK is only reachable
+    // through encrypt and decrypt calls.
+    #[cfg(feature = "gcm")]
+    struct StubKms {
+        key: aes256gcm::Key,
+        calls: Cell<usize>,
+    }
+
+    #[cfg(feature = "gcm")]
+    impl KeyEncryptionKey for StubKms {
+        fn kek_id(&self) -> &[u8] {
+            b"projects/test/keys/kek"
+        }
+
+        fn wrap(&self, dek: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(aes256gcm::seal(dek, ad, &self.key))
+        }
+
+        fn unwrap(&self, wrapped_dek: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+            self.calls.set(self.calls.get() + 1);
+            aes256gcm::open(wrapped_dek, ad, &self.key)
+        }
+    }
+
+    #[test]
+    fn envelope() {
+        let msg = b"hello world";
+        let ad = b"ad";
+        let [first, .., last] = Algorithm::ALL;
+        let kek = LocalKek::generate(b"local", last).unwrap();
+        for alg in Algorithm::ALL {
+            let mut envelope = seal(&kek, alg, msg, ad).unwrap();
+            assert_eq!(kek_id(&envelope), Ok(&b"local"[..]));
+            assert_eq!(open(&kek, &envelope, ad).unwrap(), msg);
+            assert_eq!(open(&kek, &envelope, b""), Err(Error::VerificationFailed));
+            let last = envelope.len() - 1;
+            envelope[last] ^= 1;
+            assert_eq!(open(&kek, &envelope, ad), Err(Error::VerificationFailed));
+        }
+
+        let other = LocalKek::generate(b"other", last).unwrap();
+        let envelope = seal(&kek, last, msg, ad).unwrap();
+        assert_eq!(open(&other, &envelope, ad), Err(Error::VerificationFailed));
+        let mut tampered = envelope.clone();
+        tampered[1] = first.id();
+        assert_eq!(open(&kek, &tampered, ad), Err(Error::VerificationFailed));
+        assert_eq!(
+            open(&kek, &envelope[..envelope.len() - 20], ad),
+            Err(Error::InvalidParameters)
+        );
+        assert_eq!(open(&kek, [VERSION], ad), Err(Error::InvalidParameters));
+
+        assert_eq!(
+            LocalKek::generate([0u8; MAX_KEK_ID_LEN + 1], last).err(),
+            Some(Error::InvalidParameters)
+        );
+        assert_eq!(
+            LocalKek::new(b"short", last, &last.generate_key()[1..]).err(),
+            Some(Error::InvalidKeyLength)
+        );
+    }
+
+    #[cfg(feature = "gcm")]
+    #[test]
+    fn envelope_remote_kek() {
+        let msg = b"hello world";
+        let kms = StubKms {
+            key: aes256gcm::generate_key(),
+            calls: Cell::new(0),
+        };
+        let envelope = seal(&kms, Algorithm::Aes256Gcm, msg, b"").unwrap();
+        assert_eq!(kek_id(&envelope), Ok(kms.kek_id()));
+        let kek: &dyn KeyEncryptionKey = &kms;
+        assert_eq!(open(kek, &envelope, b"").unwrap(), msg);
+        assert_eq!(kms.calls.get(), 2);
+    }
+}
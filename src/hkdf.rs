@@ -0,0 +1,243 @@
+//! HKDF key derivation (RFC 5869) with HMAC-SHA-256 and HMAC-SHA-512.
+//!
+//! Provides the `extract` and `expand` steps, and `derive_key` helpers that output
+//! correctly sized keys and nonces for any module of this crate.
+//!
+//! The functions at the root of this module use HMAC-SHA-256. The [`sha512`] module
+//! provides the same API with HMAC-SHA-512.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(feature = "gcm")] {
+//! use aes_wasm::{aes256gcm, hkdf};
+//! let ikm = b"input keying material";
+//! let key = hkdf::derive_key::<aes256gcm::Key>(ikm, b"salt", b"info");
+//! let (key, nonce): (aes256gcm::Key, aes256gcm::Nonce) =
+//!     hkdf::derive_key_and_nonce(ikm, b"salt", b"info");
+//! # }
+//! ```
+
+pub use crate::*;
+
+pub use sha256::{derive_key, derive_key_and_nonce, expand, extract, Prk, PRK_LEN};
+
+macro_rules! hkdf {
+    ($name:ident, $hmac:ident, $hash:literal, $prk_len:literal) => {
+        #[doc = concat!("HKDF with HMAC-", $hash, ".")]
+        pub mod $name {
+            use super::Okm;
+            use $hmac::{HKDF, HMAC};
+
+            /// The length of the pseudorandom key in bytes.
+            ///
+            /// This constant is used for PRK array sizing.
+            pub const PRK_LEN: usize = $prk_len;
+
+            #[doc = concat!(
+                "Pseudorandom key type for HKDF-", $hash, " (", stringify!($prk_len), " bytes)."
+            )]
+            pub type Prk = [u8; PRK_LEN];
+
+            /// Extracts a pseudorandom key from input keying material.
+            ///
+            /// # Arguments
+            /// * `salt` - Optional, non-secret salt. May be empty.
+            /// * `ikm` - Input keying material.
+            ///
+            /// # Returns
+            #[doc = concat!("Pseudorandom key as a ", stringify!($prk_len), "-byte array.")]
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use aes_wasm::hkdf::", stringify!($name), "::extract;")]
+            /// let prk = extract(b"salt", b"input keying material");
+            /// ```
+            pub fn extract(salt: impl AsRef<[u8]>, ikm: impl AsRef<[u8]>) -> Prk {
+                HKDF::extract(salt, ikm)
+            }
+
+            /// Expands a pseudorandom key into output keying material.
+            ///
+            /// # Arguments
+            /// * `out` - Output buffer, filled with keying material.
+            /// * `prk` - Reference to the pseudorandom key.
+            /// * `info` - Context and application-specific information.
+            ///
+            /// # Panics
+            #[doc = concat!(
+                "Panics if `out` is longer than 255 * ", stringify!($prk_len), " bytes."
+            )]
+            ///
+            /// # Example
+            /// ```
+            #[doc = concat!("use aes_wasm::hkdf::", stringify!($name), "::{expand, extract};")]
+            /// let prk = extract(b"salt", b"input keying material");
+            /// let mut okm = [0u8; 64];
+            /// expand(&mut okm, &prk, b"info");
+            /// ```
+            pub fn expand(out: &mut [u8], prk: &Prk, info: impl AsRef<[u8]>) {
+                expand_split(out, &mut [], prk, info.as_ref())
+            }
+
+            /// Expands a pseudorandom key into two consecutive outputs, as if they were a single
+            /// buffer.
+            fn expand_split(first: &mut [u8], second: &mut [u8], prk: &Prk, info: &[u8]) {
+                let len = first.len() + second.len();
+                assert!(len <= 255 * PRK_LEN, "HKDF output too long");
+                let mut out = first.iter_mut().chain(second.iter_mut());
+                let mut t = [0u8; PRK_LEN];
+                for counter in 1..=len.div_ceil(PRK_LEN) as u8 {
+                    let mut hmac = HMAC::new(prk);
+                    if counter > 1 {
+                        hmac.update(t);
+                    }
+                    hmac.update(info);
+                    hmac.update([counter]);
+                    t = hmac.finalize();
+                    t.iter().zip(out.by_ref()).for_each(|(&b, o)| *o = b);
+                }
+            }
+
+            /// Derives a key (or nonce) of the required size from input keying material.
+            ///
+            /// # Arguments
+            /// * `ikm` - Input keying material.
+            /// * `salt` - Optional, non-secret salt. May be empty.
+            /// * `info` - Context and application-specific information.
+            ///
+            /// # Returns
+            /// Derived output, typically the `Key` type of a module.
+            ///
+            /// # Example
+            /// ```
+            /// # #[cfg(feature = "aegis")] {
+            #[doc = concat!("use aes_wasm::{aegis256, hkdf::", stringify!($name), "::derive_key};")]
+            /// let key = derive_key::<aegis256::Key>(b"input keying material", b"salt", b"info");
+            /// # }
+            /// ```
+            pub fn derive_key<K: Okm>(
+                ikm: impl AsRef<[u8]>,
+                salt: impl AsRef<[u8]>,
+                info: impl AsRef<[u8]>,
+            ) -> K {
+                let prk = extract(salt, ikm);
+                let mut key = K::zeroed();
+                expand(key.as_mut_bytes(), &prk, info);
+                key
+            }
+
+            /// Derives both a key and a nonce from input keying material.
+            ///
+            /// The key and the nonce are consecutive slices of the same HKDF output.
+            ///
+            /// # Arguments
+            /// * `ikm` - Input keying material.
+            /// * `salt` - Optional, non-secret salt. May be empty.
+            /// * `info` - Context and application-specific information.
+            ///
+            /// # Returns
+            /// Tuple of the derived key and nonce.
+            ///
+            /// # Example
+            /// ```
+            /// # #[cfg(feature = "gcm")] {
+            #[doc = concat!(
+                "use aes_wasm::{aes256gcm, hkdf::", stringify!($name), "::derive_key_and_nonce};"
+            )]
+            /// let (key, nonce): (aes256gcm::Key, aes256gcm::Nonce) =
+            ///     derive_key_and_nonce(b"input keying material", b"salt", b"info");
+            /// # }
+            /// ```
+            pub fn derive_key_and_nonce<K: Okm, N: Okm>(
+                ikm: impl AsRef<[u8]>,
+                salt: impl AsRef<[u8]>,
+                info: impl AsRef<[u8]>,
+            ) -> (K, N) {
+                let prk = extract(salt, ikm);
+                let (mut key, mut nonce) = (K::zeroed(), N::zeroed());
+                expand_split(
+                    key.as_mut_bytes(),
+                    nonce.as_mut_bytes(),
+                    &prk,
+                    info.as_ref(),
+                );
+                (key, nonce)
+            }
+        }
+    };
+}
+
+hkdf!(sha256, hmac_sha256, "SHA-256", 32);
+hkdf!(sha512, hmac_sha512, "SHA-512", 64);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const IKM: [u8; 22] = [0x0b; 22];
+    const SALT: [u8; 13] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+    ];
+    const INFO: [u8; 10] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9];
+
+    #[test]
+    fn hkdf_sha256_rfc5869() {
+        let okm: [u8; 42] = derive_key(IKM, SALT, INFO);
+        assert_eq!(
+            okm,
+            [
+                0x3c, 0xb2, 0x5f, 0x25, 0xfa, 0xac, 0xd5, 0x7a, 0x90, 0x43, 0x4f, 0x64, 0xd0, 0x36,
+                0x2f, 0x2a, 0x2d, 0x2d, 0x0a, 0x90, 0xcf, 0x1a, 0x5a, 0x4c, 0x5d, 0xb0, 0x2d, 0x56,
+                0xec, 0xc4, 0xc5, 0xbf, 0x34, 0x00, 0x72, 0x08, 0xd5, 0xb8, 0x87, 0x18, 0x58, 0x65
+            ]
+        );
+        let (key, nonce): ([u8; 32], [u8; 12]) = derive_key_and_nonce(IKM, SALT, INFO);
+        assert_eq!(key, okm[..32]);
+        assert_eq!(nonce[..10], okm[32..]);
+    }
+
+    #[test]
+    fn hkdf_max_len() {
+        let prk = extract(SALT, IKM);
+        let mut okm = [0u8; 255 * PRK_LEN];
+        expand(&mut okm, &prk, INFO);
+        let mut expected = [0u8; 254 * PRK_LEN];
+        hmac_sha256::HKDF::expand(&mut expected, prk, INFO);
+        assert_eq!(okm[..expected.len()], expected);
+
+        let prk = sha512::extract(SALT, IKM);
+        let mut okm = [0u8; 255 * sha512::PRK_LEN];
+        sha512::expand(&mut okm, &prk, INFO);
+        let mut expected = [0u8; 254 * sha512::PRK_LEN];
+        hmac_sha512::HKDF::expand(&mut expected, prk, INFO);
+        assert_eq!(okm[..expected.len()], expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn hkdf_too_long() {
+        let mut okm = [0u8; 255 * PRK_LEN + 1];
+        expand(&mut okm, &extract(SALT, IKM), INFO);
+    }
+
+    #[test]
+    fn hkdf_sha512() {
+        let prk = sha512::extract(SALT, IKM);
+        assert_eq!(
+            prk[..16],
+            [
+                0x66, 0x57, 0x99, 0x82, 0x37, 0x37, 0xde, 0xd0, 0x4a, 0x88, 0xe4, 0x7e, 0x54, 0xa5,
+                0x89, 0x0b
+            ]
+        );
+        let okm: [u8; 42] = sha512::derive_key(IKM, SALT, INFO);
+        assert_eq!(
+            okm,
+            [
+                0x83, 0x23, 0x90, 0x08, 0x6c, 0xda, 0x71, 0xfb, 0x47, 0x62, 0x5b, 0xb5, 0xce, 0xb1,
+                0x68, 0xe4, 0xc8, 0xe2, 0x6a, 0x1a, 0x16, 0xed, 0x34, 0xd9, 0xfc, 0x7f, 0xe9, 0x2c,
+                0x14, 0x81, 0x57, 0x93, 0x38, 0xda, 0x36, 0x2c, 0xb8, 0xd9, 0xf9, 0x25, 0xd7, 0xcb
+            ]
+        );
+    }
+}
@@ -0,0 +1,507 @@
+//! `std::io` adapters for streaming encryption.
+//!
+//! `EncryptingWriter` encrypts everything written to it before passing it to an inner writer,
+//! and `DecryptingReader` decrypts everything read from an inner reader. Both support:
+//!
+//! - AES-128-CTR and AES-256-CTR, with no ciphertext expansion. These are not authenticated,
+//!   and implement `Seek` when the inner reader or writer does.
+//! - Any AEAD of this crate through the `stream` module. The nonce prefix is written first,
+//!   followed by the ciphertext segments. Truncation is detected when the last segment is
+//!   read.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(feature = "gcm")] {
+//! use aes_wasm::io::{DecryptingReader, EncryptingWriter};
+//! use aes_wasm::stream::DEFAULT_SEGMENT_SIZE;
+//! use aes_wasm::Algorithm;
+//! use std::io::{self, Read};
+//! let (alg, segment_size) = (Algorithm::Aes256Gcm, DEFAULT_SEGMENT_SIZE);
+//! let key = alg.generate_key();
+//!
+//! let mut writer = EncryptingWriter::stream(Vec::new(), alg, &key, segment_size).unwrap();
+//! io::copy(&mut &b"hello world"[..], &mut writer).unwrap();
+//! let ciphertext = writer.finish().unwrap();
+//!
+//! let mut reader = DecryptingReader::stream(&ciphertext[..], alg, &key, segment_size).unwrap();
+//! let mut plaintext = Vec::new();
+//! reader.read_to_end(&mut plaintext).unwrap();
+//! assert_eq!(plaintext, b"hello world");
+//! # }
+//! ```
+
+use std::io::{ErrorKind, Read, Seek, SeekFrom, Write};
+
+use crate::stream::{nonce_prefix_len, Decryptor, Encryptor};
+pub use crate::*;
+
+#[derive(Clone)]
+enum CtrKey {
+    Aes128(aes128ctr::Key),
+    Aes256(aes256ctr::Key),
+}
+
+#[derive(Clone)]
+struct Ctr {
+    key: CtrKey,
+    iv: [u8; 16],
+    offset: u64,
+}
+
+impl Ctr {
+    /// XORs `data` with the keystream at the current offset, and advances the offset.
+    fn apply(&mut self, data: &[u8]) -> Vec<u8> {
+        let skip = (self.offset % 16) as usize;
+        let counter = u128::from_be_bytes(self.iv).wrapping_add((self.offset / 16) as u128);
+        let iv = counter.to_be_bytes();
+        let mut padded = vec![0u8; skip + data.len()];
+        padded[skip..].copy_from_slice(data);
+        let mut out = match &self.key {
+            CtrKey::Aes128(key) => aes128ctr::encrypt(&padded, key, iv),
+            CtrKey::Aes256(key) => aes256ctr::encrypt(&padded, key, iv),
+        };
+        out.drain(..skip);
+        self.offset += data.len() as u64;
+        out
+    }
+}
+
+/// Reads from `reader` until `buf` holds `len` bytes or the end of the stream is reached.
+///
+/// Bytes read before an error are kept in `buf`, so the read can be resumed.
+fn fill_buffer(reader: &mut impl Read, buf: &mut Vec<u8>, len: usize) -> std::io::Result<()> {
+    while buf.len() < len {
+        let filled = buf.len();
+        buf.resize(len, 0);
+        match reader.read(&mut buf[filled..]) {
+            Ok(len) => {
+                buf.truncate(filled + len);
+                if len == 0 {
+                    break;
+                }
+            }
+            Err(e) => {
+                buf.truncate(filled);
+                if e.kind() != ErrorKind::Interrupted {
+                    return Err(e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+enum WriterMode {
+    Ctr(Ctr),
+    Stream {
+        encryptor: Encryptor,
+        buffer: Vec<u8>,
+    },
+}
+
+/// A writer that encrypts data before writing it to an inner writer.
+///
+/// `finish` must be called once all the data has been written. With an AEAD, it writes the
+/// last segment, without which the stream cannot be decrypted.
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    mode: WriterMode,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    /// Creates a writer encrypting with AES-128-CTR.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::aes128ctr::{generate_iv, generate_key};
+    /// use aes_wasm::io::EncryptingWriter;
+    /// use std::io::Write;
+    /// let (key, iv) = (generate_key(), generate_iv());
+    /// let mut writer = EncryptingWriter::aes128ctr(Vec::new(), &key, iv);
+    /// writer.write_all(b"hello").unwrap();
+    /// let ciphertext = writer.finish().unwrap();
+    /// assert_eq!(ciphertext, aes_wasm::aes128ctr::encrypt(b"hello", &key, iv));
+    /// ```
+    pub fn aes128ctr(inner: W, key: &aes128ctr::Key, iv: aes128ctr::IV) -> Self {
+        let key = CtrKey::Aes128(*key);
+        let ctr = Ctr { key, iv, offset: 0 };
+        EncryptingWriter {
+            inner,
+            mode: WriterMode::Ctr(ctr),
+        }
+    }
+
+    /// Creates a writer encrypting with AES-256-CTR.
+    pub fn aes256ctr(inner: W, key: &aes256ctr::Key, iv: aes256ctr::IV) -> Self {
+        let key = CtrKey::Aes256(*key);
+        let ctr = Ctr { key, iv, offset: 0 };
+        EncryptingWriter {
+            inner,
+            mode: WriterMode::Ctr(ctr),
+        }
+    }
+
+    /// Creates a writer encrypting with an AEAD, using the STREAM construction.
+    ///
+    /// A random nonce prefix is generated and immediately written to the inner writer.
+    ///
+    /// # Arguments
+    /// * `inner` - The writer receiving the ciphertext.
+    /// * `alg` - The AEAD algorithm.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    /// * `segment_size` - The plaintext segment size.
+    ///
+    /// # Returns
+    /// The writer, or an error if the parameters are invalid or the nonce prefix cannot be
+    /// written.
+    pub fn stream(
+        mut inner: W,
+        alg: Algorithm,
+        key: &[u8],
+        segment_size: usize,
+    ) -> std::io::Result<Self> {
+        let encryptor = Encryptor::new(alg, key, segment_size).map_err(to_io_error)?;
+        inner.write_all(encryptor.nonce_prefix())?;
+        Ok(EncryptingWriter {
+            inner,
+            mode: WriterMode::Stream {
+                encryptor,
+                buffer: Vec::with_capacity(segment_size + 1),
+            },
+        })
+    }
+
+    /// Writes any buffered data, flushes the inner writer and returns it.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        if let WriterMode::Stream { encryptor, buffer } = self.mode {
+            let segment = encryptor.encrypt_last(buffer, b"").map_err(to_io_error)?;
+            self.inner.write_all(&segment)?;
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match &mut self.mode {
+            WriterMode::Ctr(ctr) => self.inner.write_all(&ctr.apply(buf))?,
+            WriterMode::Stream { encryptor, buffer } => {
+                // A full segment is only encrypted once more data follows,
+                // as the last segment is encrypted differently.
+                let segment_size = encryptor.segment_size();
+                buffer.extend_from_slice(buf);
+                while buffer.len() > segment_size {
+                    let segment = encryptor
+                        .encrypt_next(&buffer[..segment_size], b"")
+                        .map_err(to_io_error)?;
+                    self.inner.write_all(&segment)?;
+                    buffer.drain(..segment_size);
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    /// Flushes the inner writer. Data buffered for an incomplete segment is not written.
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Seeking is only supported with CTR. Plaintext and ciphertext offsets are identical.
+impl<W: Write + Seek> Seek for EncryptingWriter<W> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match &mut self.mode {
+            WriterMode::Ctr(ctr) => {
+                ctr.offset = self.inner.seek(pos)?;
+                Ok(ctr.offset)
+            }
+            WriterMode::Stream { .. } => Err(ErrorKind::Unsupported.into()),
+        }
+    }
+}
+
+enum ReaderMode {
+    Ctr(Ctr),
+    Stream {
+        decryptor: Option<Decryptor>,
+        buffer: Vec<u8>,
+        error: Option<Error>,
+    },
+}
+
+/// A reader that decrypts data read from an inner reader.
+///
+/// With an AEAD, only authenticated data is returned, one segment at a time. A read error with
+/// kind `InvalidData` is returned if a segment fails to verify, including if the stream was
+/// truncated. Verification errors are sticky: every subsequent read returns the same error.
+///
+/// Other errors of the inner reader, such as `WouldBlock`, are returned as-is, and reading can
+/// be resumed afterwards.
+pub struct DecryptingReader<R: Read> {
+    inner: R,
+    mode: ReaderMode,
+    plaintext: Vec<u8>,
+    pos: usize,
+}
+
+impl<R: Read> DecryptingReader<R> {
+    fn new(inner: R, mode: ReaderMode) -> Self {
+        DecryptingReader {
+            inner,
+            mode,
+            plaintext: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Creates a reader decrypting with AES-128-CTR.
+    pub fn aes128ctr(inner: R, key: &aes128ctr::Key, iv: aes128ctr::IV) -> Self {
+        let key = CtrKey::Aes128(*key);
+        Self::new(inner, ReaderMode::Ctr(Ctr { key, iv, offset: 0 }))
+    }
+
+    /// Creates a reader decrypting with AES-256-CTR.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::aes256ctr::{encrypt, generate_iv, generate_key};
+    /// use aes_wasm::io::DecryptingReader;
+    /// use std::io::{Cursor, Read, Seek, SeekFrom};
+    /// let (key, iv) = (generate_key(), generate_iv());
+    /// let ciphertext = encrypt(b"hello world", &key, iv);
+    /// let mut reader = DecryptingReader::aes256ctr(Cursor::new(ciphertext), &key, iv);
+    /// reader.seek(SeekFrom::Start(6)).unwrap();
+    /// let mut plaintext = String::new();
+    /// reader.read_to_string(&mut plaintext).unwrap();
+    /// assert_eq!(plaintext, "world");
+    /// ```
+    pub fn aes256ctr(inner: R, key: &aes256ctr::Key, iv: aes256ctr::IV) -> Self {
+        let key = CtrKey::Aes256(*key);
+        Self::new(inner, ReaderMode::Ctr(Ctr { key, iv, offset: 0 }))
+    }
+
+    /// Creates a reader decrypting a stream written by `EncryptingWriter::stream`.
+    ///
+    /// The nonce prefix is immediately read from the inner reader.
+    ///
+    /// # Arguments
+    /// * `inner` - The reader providing the ciphertext.
+    /// * `alg` - The AEAD algorithm.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    /// * `segment_size` - The plaintext segment size given to the writer.
+    ///
+    /// # Returns
+    /// The reader, or an error if the parameters are invalid or the nonce prefix cannot be read.
+    pub fn stream(
+        mut inner: R,
+        alg: Algorithm,
+        key: &[u8],
+        segment_size: usize,
+    ) -> std::io::Result<Self> {
+        let mut nonce_prefix = vec![0u8; nonce_prefix_len(alg)];
+        inner.read_exact(&mut nonce_prefix)?;
+        let decryptor =
+            Decryptor::new(alg, key, &nonce_prefix, segment_size).map_err(to_io_error)?;
+        let buffer = Vec::with_capacity(decryptor.ciphertext_segment_size() + 1);
+        let mode = ReaderMode::Stream {
+            decryptor: Some(decryptor),
+            buffer,
+            error: None,
+        };
+        Ok(Self::new(inner, mode))
+    }
+
+    /// Returns the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decrypts the next segment. Returns `false` once the last segment has been read.
+    fn next_segment(&mut self) -> std::io::Result<bool> {
+        let ReaderMode::Stream {
+            decryptor,
+            buffer,
+            error,
+        } = &mut self.mode
+        else {
+            unreachable!()
+        };
+        if let Some(err) = *error {
+            return Err(to_io_error(err));
+        }
+        let Some(d) = decryptor else {
+            return Ok(false);
+        };
+        // Read one byte past the segment, to tell whether it is the last one.
+        let ciphertext_segment_size = d.ciphertext_segment_size();
+        fill_buffer(&mut self.inner, buffer, ciphertext_segment_size + 1)?;
+        // The decryptor is only advanced or dropped once its segment has been verified.
+        let res = if buffer.len() > ciphertext_segment_size {
+            d.decrypt_next(&buffer[..ciphertext_segment_size], b"")
+                .inspect(|_| drop(buffer.drain(..ciphertext_segment_size)))
+        } else {
+            d.clone()
+                .decrypt_last(&buffer[..], b"")
+                .inspect(|_| *decryptor = None)
+        };
+        self.plaintext = res.map_err(|err| {
+            *error = Some(err);
+            to_io_error(err)
+        })?;
+        self.pos = 0;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if let ReaderMode::Ctr(ctr) = &mut self.mode {
+            let len = self.inner.read(buf)?;
+            let plaintext = ctr.apply(&buf[..len]);
+            buf[..len].copy_from_slice(&plaintext);
+            return Ok(len);
+        }
+        while self.pos == self.plaintext.len() {
+            if !self.next_segment()? {
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.plaintext.len() - self.pos);
+        buf[..len].copy_from_slice(&self.plaintext[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Seeking is only supported with CTR. Plaintext and ciphertext offsets are identical.
+impl<R: Read + Seek> Seek for DecryptingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match &mut self.mode {
+            ReaderMode::Ctr(ctr) => {
+                ctr.offset = self.inner.seek(pos)?;
+                Ok(ctr.offset)
+            }
+            ReaderMode::Stream { .. } => Err(ErrorKind::Unsupported.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn ctr() {
+        let key = aes256ctr::generate_key();
+        let iv = [0xff; 16];
+        let msg: Vec<u8> = (0..100).collect();
+        let ciphertext = aes256ctr::encrypt(&msg, &key, iv);
+
+        let mut writer = EncryptingWriter::aes256ctr(Cursor::new(Vec::new()), &key, iv);
+        for chunk in msg.chunks(7) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap().into_inner(), ciphertext);
+
+        let mut reader = DecryptingReader::aes256ctr(Cursor::new(&ciphertext), &key, iv);
+        for offset in [33, 0, 17, 99, 64] {
+            reader.seek(SeekFrom::Start(offset)).unwrap();
+            let mut plaintext = Vec::new();
+            reader.read_to_end(&mut plaintext).unwrap();
+            assert_eq!(plaintext, msg[offset as usize..]);
+        }
+
+        let mut writer = EncryptingWriter::aes256ctr(Cursor::new(vec![0; 100]), &key, iv);
+        writer.seek(SeekFrom::Start(50)).unwrap();
+        writer.write_all(&msg[50..]).unwrap();
+        assert_eq!(
+            writer.finish().unwrap().into_inner()[50..],
+            ciphertext[50..]
+        );
+    }
+
+    #[test]
+    fn stream() {
+        for alg in Algorithm::ALL {
+            let key = alg.generate_key();
+            for len in [0, 1, 16, 17, 100] {
+                let msg: Vec<u8> = (0..len as u8).collect();
+                let mut writer = EncryptingWriter::stream(Vec::new(), alg, &key, 16).unwrap();
+                for chunk in msg.chunks(5) {
+                    writer.write_all(chunk).unwrap();
+                }
+                let ciphertext = writer.finish().unwrap();
+                assert_eq!(
+                    stream::decrypt(alg, &key, 16, &ciphertext, b"").unwrap(),
+                    msg
+                );
+
+                let mut reader = DecryptingReader::stream(&ciphertext[..], alg, &key, 16).unwrap();
+                let mut plaintext = Vec::new();
+                reader.read_to_end(&mut plaintext).unwrap();
+                assert_eq!(plaintext, msg);
+
+                let truncated = &ciphertext[..ciphertext.len() - 1];
+                let mut reader = DecryptingReader::stream(truncated, alg, &key, 16).unwrap();
+                let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::InvalidData);
+                let err = reader.read(&mut [0u8; 16]).unwrap_err();
+                assert_eq!(err.kind(), ErrorKind::InvalidData);
+            }
+        }
+    }
+
+    /// Returns `WouldBlock` before every other read.
+    struct Flaky<R> {
+        inner: R,
+        block: bool,
+    }
+
+    impl<R: Read> Read for Flaky<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.block = !self.block;
+            if self.block {
+                return Err(ErrorKind::WouldBlock.into());
+            }
+            let len = buf.len().min(7);
+            self.inner.read(&mut buf[..len])
+        }
+    }
+
+    #[test]
+    fn stream_retry() {
+        let alg = Algorithm::ALL[0];
+        let key = alg.generate_key();
+        let msg: Vec<u8> = (0..100).collect();
+        let ciphertext = stream::encrypt(alg, &key, 16, &msg, b"").unwrap();
+        let (prefix, segments) = ciphertext.split_at(nonce_prefix_len(alg));
+        let inner = prefix.chain(Flaky {
+            inner: segments,
+            block: false,
+        });
+        let mut reader = DecryptingReader::stream(inner, alg, &key, 16).unwrap();
+        let (mut plaintext, mut buf) = (Vec::new(), [0u8; 10]);
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => plaintext.extend_from_slice(&buf[..len]),
+                Err(e) => assert_eq!(e.kind(), ErrorKind::WouldBlock),
+            }
+        }
+        assert_eq!(plaintext, msg);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        let mut forged = ciphertext.clone();
+        *forged.last_mut().unwrap() ^= 1;
+        let mut reader = DecryptingReader::stream(&forged[..], alg, &key, 16).unwrap();
+        let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        for _ in 0..2 {
+            let err = reader.read(&mut buf).unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+//! JavaScript bindings, generated with `wasm-bindgen`.
+//!
+//! Every cipher module is exposed as `<module>_encrypt` and `<module>_decrypt` functions taking
+//! and returning `Uint8Array`s, and CMAC-AES-128 as `cmac_aes128_mac`. Invalid key or nonce
+//! lengths, and failed verifications, are thrown as JavaScript `Error`s.
+//!
+//! ## Example
+//! ```js
+//! import { aegis128l_encrypt, aegis128l_decrypt } from "aes-wasm";
+//! const key = crypto.getRandomValues(new Uint8Array(16));
+//! const nonce = crypto.getRandomValues(new Uint8Array(16));
+//! const msg = new TextEncoder().encode("hello");
+//! const ciphertext = aegis128l_encrypt(msg, new Uint8Array(), key, nonce);
+//! const plaintext = aegis128l_decrypt(ciphertext, new Uint8Array(), key, nonce);
+//! ```
+
+// Which helpers are used depends on the enabled algorithms.
+#![cfg_attr(
+    not(all(
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    )),
+    allow(dead_code, unused_macros)
+)]
+
+use wasm_bindgen::prelude::*;
+
+use crate::Error;
+
+fn js_error(err: Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+fn fallible(res: Result<Vec<u8>, Error>) -> Result<Vec<u8>, JsError> {
+    res.map_err(js_error)
+}
+
+fn array<const N: usize>(bytes: &[u8], err: Error) -> Result<[u8; N], JsError> {
+    bytes.try_into().map_err(|_| js_error(err))
+}
+
+macro_rules! aead {
+    ($m:ident, $encrypt:ident, $decrypt:ident) => {
+        #[doc = concat!(
+            "Encrypts a message with `", stringify!($m),
+            "`, and returns the ciphertext with the tag appended."
+        )]
+        #[wasm_bindgen]
+        pub fn $encrypt(
+            msg: &[u8],
+            ad: &[u8],
+            key: &[u8],
+            nonce: &[u8],
+        ) -> Result<Vec<u8>, JsError> {
+            let key = array(key, Error::InvalidKeyLength)?;
+            let nonce = array(nonce, Error::InvalidNonceLength)?;
+            fallible(crate::$m::try_encrypt(msg, ad, &key, nonce))
+        }
+
+        #[doc = concat!(
+            "Verifies and decrypts a ciphertext with the tag appended, using `", stringify!($m),
+            "`."
+        )]
+        #[wasm_bindgen]
+        pub fn $decrypt(
+            ciphertext: &[u8],
+            ad: &[u8],
+            key: &[u8],
+            nonce: &[u8],
+        ) -> Result<Vec<u8>, JsError> {
+            let key = array(key, Error::InvalidKeyLength)?;
+            let nonce = array(nonce, Error::InvalidNonceLength)?;
+            fallible(crate::$m::decrypt(ciphertext, ad, &key, nonce))
+        }
+    };
+}
+
+#[cfg(feature = "aegis")]
+aead!(aegis128l, aegis128l_encrypt, aegis128l_decrypt);
+#[cfg(feature = "aegis")]
+aead!(aegis128x2, aegis128x2_encrypt, aegis128x2_decrypt);
+#[cfg(feature = "aegis")]
+aead!(aegis128x4, aegis128x4_encrypt, aegis128x4_decrypt);
+#[cfg(feature = "aegis")]
+aead!(aegis256, aegis256_encrypt, aegis256_decrypt);
+#[cfg(feature = "aegis")]
+aead!(aegis256x2, aegis256x2_encrypt, aegis256x2_decrypt);
+#[cfg(feature = "aegis")]
+aead!(aegis256x4, aegis256x4_encrypt, aegis256x4_decrypt);
+#[cfg(feature = "gcm")]
+aead!(aes128gcm, aes128gcm_encrypt, aes128gcm_decrypt);
+#[cfg(feature = "gcm")]
+aead!(aes256gcm, aes256gcm_encrypt, aes256gcm_decrypt);
+#[cfg(feature = "ocb")]
+aead!(aes128ocb, aes128ocb_encrypt, aes128ocb_decrypt);
+#[cfg(feature = "ocb")]
+aead!(aes256ocb, aes256ocb_encrypt, aes256ocb_decrypt);
+#[cfg(feature = "cbc")]
+aead!(
+    aes128cbc_hs256,
+    aes128cbc_hs256_encrypt,
+    aes128cbc_hs256_decrypt
+);
+#[cfg(feature = "cbc")]
+aead!(
+    aes256cbc_hs512,
+    aes256cbc_hs512_encrypt,
+    aes256cbc_hs512_decrypt
+);
+
+macro_rules! cipher {
+    ($m:ident, $encrypt:ident, $decrypt:ident, $decrypted:expr) => {
+        #[doc = concat!("Encrypts a message with `", stringify!($m), "`.")]
+        #[wasm_bindgen]
+        pub fn $encrypt(msg: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, JsError> {
+            let key = array(key, Error::InvalidKeyLength)?;
+            let iv = array(iv, Error::InvalidNonceLength)?;
+            Ok(crate::$m::encrypt(msg, &key, iv))
+        }
+
+        #[doc = concat!("Decrypts a ciphertext with `", stringify!($m), "`.")]
+        #[wasm_bindgen]
+        pub fn $decrypt(ciphertext: &[u8], key: &[u8], iv: &[u8]) -> Result<Vec<u8>, JsError> {
+            let key = array(key, Error::InvalidKeyLength)?;
+            let iv = array(iv, Error::InvalidNonceLength)?;
+            $decrypted(crate::$m::decrypt(ciphertext, &key, iv))
+        }
+    };
+}
+
+#[cfg(feature = "cbc")]
+cipher!(aes128cbc, aes128cbc_encrypt, aes128cbc_decrypt, fallible);
+#[cfg(feature = "cbc")]
+cipher!(aes256cbc, aes256cbc_encrypt, aes256cbc_decrypt, fallible);
+#[cfg(feature = "ctr")]
+cipher!(aes128ctr, aes128ctr_encrypt, aes128ctr_decrypt, Ok);
+#[cfg(feature = "ctr")]
+cipher!(aes256ctr, aes256ctr_encrypt, aes256ctr_decrypt, Ok);
+
+/// Computes the CMAC-AES-128 tag of a message.
+#[cfg(feature = "cmac")]
+#[wasm_bindgen]
+pub fn cmac_aes128_mac(msg: &[u8], key: &[u8]) -> Result<Vec<u8>, JsError> {
+    let key = array(key, Error::InvalidKeyLength)?;
+    fallible(crate::cmac_aes128::try_mac(msg, &key).map(|tag| tag.to_vec()))
+}
+
+#[cfg(all(test, target_arch = "wasm32", target_os = "unknown"))]
+mod test {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| i as u8).collect()
+    }
+
+    macro_rules! check_aead {
+        ($m:ident, $encrypt:ident, $decrypt:ident) => {{
+            let (msg, ad) = (input(100), input(10));
+            let key = input(crate::$m::KEY_LEN);
+            let nonce = input(crate::$m::NONCE_LEN);
+            let ciphertext = $encrypt(&msg, &ad, &key, &nonce).unwrap();
+            let expected = crate::$m::encrypt(
+                &msg,
+                &ad,
+                key.as_slice().try_into().unwrap(),
+                nonce.as_slice().try_into().unwrap(),
+            );
+            assert_eq!(ciphertext, expected);
+            assert_eq!($decrypt(&ciphertext, &ad, &key, &nonce).unwrap(), msg);
+            assert!($decrypt(&ciphertext, b"", &key, &nonce).is_err());
+            assert!($encrypt(&msg, &ad, &key[1..], &nonce).is_err());
+            assert!($encrypt(&msg, &ad, &key, &nonce[1..]).is_err());
+        }};
+    }
+
+    #[wasm_bindgen_test]
+    fn aead() {
+        check_aead!(aegis128l, aegis128l_encrypt, aegis128l_decrypt);
+        check_aead!(aegis128x2, aegis128x2_encrypt, aegis128x2_decrypt);
+        check_aead!(aegis128x4, aegis128x4_encrypt, aegis128x4_decrypt);
+        check_aead!(aegis256, aegis256_encrypt, aegis256_decrypt);
+        check_aead!(aegis256x2, aegis256x2_encrypt, aegis256x2_decrypt);
+        check_aead!(aegis256x4, aegis256x4_encrypt, aegis256x4_decrypt);
+        check_aead!(aes128gcm, aes128gcm_encrypt, aes128gcm_decrypt);
+        check_aead!(aes256gcm, aes256gcm_encrypt, aes256gcm_decrypt);
+        check_aead!(aes128ocb, aes128ocb_encrypt, aes128ocb_decrypt);
+        check_aead!(aes256ocb, aes256ocb_encrypt, aes256ocb_decrypt);
+        check_aead!(
+            aes128cbc_hs256,
+            aes128cbc_hs256_encrypt,
+            aes128cbc_hs256_decrypt
+        );
+        check_aead!(
+            aes256cbc_hs512,
+            aes256cbc_hs512_encrypt,
+            aes256cbc_hs512_decrypt
+        );
+    }
+
+    macro_rules! check_cipher {
+        ($encrypt:ident, $decrypt:ident, $key_len:expr, $ciphertext_len:expr) => {{
+            let (msg, key, iv) = (input(100), input($key_len), input(16));
+            let ciphertext = $encrypt(&msg, &key, &iv).unwrap();
+            assert_eq!(ciphertext.len(), $ciphertext_len);
+            assert_eq!($decrypt(&ciphertext, &key, &iv).unwrap(), msg);
+            assert!($encrypt(&msg, &key[1..], &iv).is_err());
+            assert!($decrypt(&ciphertext, &key, &iv[1..]).is_err());
+        }};
+    }
+
+    #[wasm_bindgen_test]
+    fn cipher() {
+        check_cipher!(aes128cbc_encrypt, aes128cbc_decrypt, 16, 112);
+        check_cipher!(aes256cbc_encrypt, aes256cbc_decrypt, 32, 112);
+        check_cipher!(aes128ctr_encrypt, aes128ctr_decrypt, 16, 100);
+        check_cipher!(aes256ctr_encrypt, aes256ctr_decrypt, 32, 100);
+        assert!(aes128cbc_decrypt(&input(100), &input(16), &input(16)).is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn cmac() {
+        let (msg, key) = (input(100), input(16));
+        let tag = cmac_aes128_mac(&msg, &key).unwrap();
+        assert_eq!(
+            tag,
+            crate::cmac_aes128::mac(&msg, key.as_slice().try_into().unwrap())
+        );
+        assert!(cmac_aes128_mac(&msg, &key[1..]).is_err());
+    }
+}
@@ -0,0 +1,557 @@
+//! Keyring with key identifiers and key rotation.
+//!
+//! A `Keyring` holds multiple keys, each tagged with a 32-bit key identifier and an
+//! `Algorithm`. New messages are encrypted with the primary key, and the key identifier is
+//! embedded in the ciphertext so that `decrypt` can select the right key, even after the
+//! primary key has been rotated.
+//!
+//! ## Format
+//!
+//! | offset     | length | content                      |
+//! | :--------- | -----: | :--------------------------- |
+//! | 0          |      1 | format version (`1`)         |
+//! | 1          |      4 | key identifier (big-endian)  |
+//! | 5          |      n | nonce                        |
+//! | 5 + n      |        | ciphertext and tag           |
+//!
+//! The 5-byte prefix is authenticated: the additional data given to the cipher is the
+//! prefix followed by the caller's additional data.
+//!
+//! ## Keyset format
+//!
+//! `Keyring::export` serializes the keys, so that a keyring can be stored and restored with
+//! `Keyring::import`:
+//!
+//! | offset | length | content                                              |
+//! | :----- | -----: | :--------------------------------------------------- |
+//! | 0      |      1 | keyset format version (`1`)                          |
+//! | 1      |      1 | `1` if a primary key is set, `0` otherwise           |
+//! | 2      |      4 | primary key identifier (big-endian), `0` if unset    |
+//! | 6      |      4 | number of keys (big-endian)                          |
+//! | 10     |        | keys                                                 |
+//!
+//! Each key is encoded as:
+//!
+//! | offset | length | content                                              |
+//! | :----- | -----: | :--------------------------------------------------- |
+//! | 0      |      4 | key identifier (big-endian)                          |
+//! | 4      |      1 | algorithm identifier of the `sealed` format          |
+//! | 5      |      1 | status: `1` = enabled, `2` = disabled                |
+//! | 6      |      n | key, `Algorithm::key_len()` bytes                    |
+//!
+//! The exported keyset contains the secret keys in plaintext, and should itself be encrypted,
+//! for example with `sealed::seal` under a key-encryption key.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(all(feature = "aegis", feature = "gcm"))] {
+//! use aes_wasm::keyring::Keyring;
+//! use aes_wasm::Algorithm;
+//! let mut keyring = Keyring::new();
+//! keyring.rotate(Algorithm::Aes256Gcm);
+//! let old = keyring.encrypt(b"old record", b"ad").unwrap();
+//! keyring.rotate(Algorithm::Aegis256);
+//! let new = keyring.encrypt(b"new record", b"ad").unwrap();
+//! assert_eq!(keyring.decrypt(&old, b"ad").unwrap(), b"old record");
+//! assert_eq!(keyring.decrypt(&new, b"ad").unwrap(), b"new record");
+//! # }
+//! ```
+
+pub use crate::*;
+
+/// The format version written by `Keyring::encrypt`.
+pub const VERSION: u8 = 1;
+/// The length of the prefix (version and key identifier) in bytes.
+pub const PREFIX_LEN: usize = 5;
+/// The keyset format version written by `Keyring::export`.
+pub const KEYSET_VERSION: u8 = 1;
+
+const KEYSET_HEADER_LEN: usize = 10;
+const KEY_HEADER_LEN: usize = 6;
+
+/// Status of a key in a keyring.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum KeyStatus {
+    /// The key can be used for decryption, and for encryption if it is the primary key.
+    Enabled,
+    /// The key is kept in the keyring, but cannot be used.
+    Disabled,
+}
+
+impl KeyStatus {
+    fn id(&self) -> u8 {
+        match self {
+            KeyStatus::Enabled => 1,
+            KeyStatus::Disabled => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(KeyStatus::Enabled),
+            2 => Ok(KeyStatus::Disabled),
+            _ => Err(Error::InvalidParameters),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct KeyEntry {
+    id: u32,
+    alg: Algorithm,
+    key: Vec<u8>,
+    status: KeyStatus,
+}
+
+/// Set of keys tagged with key identifiers, with a primary key used for encryption.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "gcm")] {
+/// use aes_wasm::keyring::Keyring;
+/// use aes_wasm::Algorithm;
+/// let mut keyring = Keyring::new();
+/// let key = Algorithm::Aes128Gcm.generate_key();
+/// keyring.add_key(42, Algorithm::Aes128Gcm, &key).unwrap();
+/// keyring.set_primary(42).unwrap();
+/// let ciphertext = keyring.encrypt(b"hello", b"ad").unwrap();
+/// assert_eq!(aes_wasm::keyring::key_id(&ciphertext), Some(42));
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct Keyring {
+    keys: Vec<KeyEntry>,
+    primary: Option<u32>,
+}
+
+impl Keyring {
+    /// Creates an empty keyring.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn entry(&self, id: u32) -> Option<&KeyEntry> {
+        self.keys.iter().find(|entry| entry.id == id)
+    }
+
+    fn entry_mut(&mut self, id: u32) -> Result<&mut KeyEntry, Error> {
+        self.keys
+            .iter_mut()
+            .find(|entry| entry.id == id)
+            .ok_or(Error::InvalidParameters)
+    }
+
+    /// Adds a key to the keyring.
+    ///
+    /// # Arguments
+    /// * `id` - The key identifier, unique within the keyring.
+    /// * `alg` - The AEAD algorithm the key is used with.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, `Err(Error::InvalidKeyLength)` if the key length is invalid,
+    /// or `Err(Error::InvalidParameters)` if the identifier is already in use.
+    pub fn add_key(&mut self, id: u32, alg: Algorithm, key: &[u8]) -> Result<(), Error> {
+        if key.len() != alg.key_len() {
+            return Err(Error::InvalidKeyLength);
+        }
+        if self.entry(id).is_some() {
+            return Err(Error::InvalidParameters);
+        }
+        self.keys.push(KeyEntry {
+            id,
+            alg,
+            key: key.to_vec(),
+            status: KeyStatus::Enabled,
+        });
+        Ok(())
+    }
+
+    /// Generates a random key with a random, unused identifier, and adds it to the keyring.
+    ///
+    /// # Returns
+    /// The identifier of the new key.
+    pub fn generate_key(&mut self, alg: Algorithm) -> u32 {
+        let id = loop {
+            let mut id = [0u8; 4];
+            random_bytes(&mut id);
+            let id = u32::from_be_bytes(id);
+            if self.entry(id).is_none() {
+                break id;
+            }
+        };
+        self.add_key(id, alg, &alg.generate_key()).unwrap();
+        id
+    }
+
+    /// Generates a new key and makes it the primary key.
+    ///
+    /// Previous keys are kept in the keyring, so that existing ciphertexts can still be decrypted.
+    ///
+    /// # Returns
+    /// The identifier of the new primary key.
+    pub fn rotate(&mut self, alg: Algorithm) -> u32 {
+        let id = self.generate_key(alg);
+        self.primary = Some(id);
+        id
+    }
+
+    /// Sets the primary key, used to encrypt new messages.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if the key doesn't exist
+    /// or is disabled.
+    pub fn set_primary(&mut self, id: u32) -> Result<(), Error> {
+        match self.entry(id) {
+            Some(entry) if entry.status == KeyStatus::Enabled => {
+                self.primary = Some(id);
+                Ok(())
+            }
+            _ => Err(Error::InvalidParameters),
+        }
+    }
+
+    /// Returns the identifier of the primary key, if any.
+    pub fn primary(&self) -> Option<u32> {
+        self.primary
+    }
+
+    /// Returns the identifiers of all the keys in the keyring.
+    pub fn key_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.keys.iter().map(|entry| entry.id)
+    }
+
+    /// Returns the algorithm and status of a key, if it exists.
+    pub fn key_info(&self, id: u32) -> Option<(Algorithm, KeyStatus)> {
+        self.entry(id).map(|entry| (entry.alg, entry.status))
+    }
+
+    /// Enables a key for decryption.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if the key doesn't exist.
+    pub fn enable_key(&mut self, id: u32) -> Result<(), Error> {
+        self.entry_mut(id)?.status = KeyStatus::Enabled;
+        Ok(())
+    }
+
+    /// Disables a key, that can then no longer be used to decrypt messages.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if the key doesn't exist or is
+    /// the primary key.
+    pub fn disable_key(&mut self, id: u32) -> Result<(), Error> {
+        if self.primary == Some(id) {
+            return Err(Error::InvalidParameters);
+        }
+        self.entry_mut(id)?.status = KeyStatus::Disabled;
+        Ok(())
+    }
+
+    /// Removes a key from the keyring.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if the key doesn't exist or is
+    /// the primary key.
+    pub fn remove_key(&mut self, id: u32) -> Result<(), Error> {
+        if self.primary == Some(id) || self.entry(id).is_none() {
+            return Err(Error::InvalidParameters);
+        }
+        self.keys.retain(|entry| entry.id != id);
+        Ok(())
+    }
+
+    /// Encrypts a message with the primary key and a random nonce.
+    ///
+    /// # Arguments
+    /// * `msg` - The plaintext message to encrypt.
+    /// * `ad` - Additional authenticated data (AAD), not included in the output.
+    ///
+    /// # Returns
+    /// The prefix, nonce, ciphertext and tag, or `Err(Error::InvalidParameters)` if the keyring
+    /// has no primary key.
+    pub fn encrypt(&self, msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>) -> Result<Vec<u8>, Error> {
+        let entry = self
+            .primary
+            .and_then(|id| self.entry(id))
+            .ok_or(Error::InvalidParameters)?;
+        let nonce = entry.alg.generate_nonce();
+        let ad = prefix_and_ad(entry.id, ad.as_ref());
+        let ciphertext_and_tag = entry.alg.encrypt(msg, &ad, &entry.key, &nonce)?;
+        let mut out = Vec::with_capacity(PREFIX_LEN + nonce.len() + ciphertext_and_tag.len());
+        out.extend_from_slice(&ad[..PREFIX_LEN]);
+        out.extend_from_slice(&nonce);
+        out.extend_from_slice(&ciphertext_and_tag);
+        Ok(out)
+    }
+
+    /// Decrypts a message, using the key identified by its prefix.
+    ///
+    /// # Arguments
+    /// * `ciphertext` - The output of `encrypt`.
+    /// * `ad` - Additional authenticated data (AAD), as given to `encrypt`.
+    ///
+    /// # Returns
+    /// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidLength)` if the ciphertext
+    /// is too short, or `Err(Error::VerificationFailed)` if the key is unknown or disabled, or
+    /// if verification fails.
+    pub fn decrypt(
+        &self,
+        ciphertext: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        let ciphertext = ciphertext.as_ref();
+        let entry = key_id(ciphertext)
+            .and_then(|id| self.entry(id))
+            .filter(|entry| entry.status == KeyStatus::Enabled)
+            .ok_or(Error::VerificationFailed)?;
+        let nonce_len = entry.alg.nonce_len();
+        if ciphertext.len() < PREFIX_LEN + nonce_len {
+            return Err(Error::InvalidLength);
+        }
+        let (nonce, ciphertext_and_tag) = ciphertext[PREFIX_LEN..].split_at(nonce_len);
+        let ad = prefix_and_ad(entry.id, ad.as_ref());
+        entry.alg.decrypt(ciphertext_and_tag, ad, &entry.key, nonce)
+    }
+}
+
+impl Keyring {
+    /// Serializes the keyring, including the secret keys, in the keyset format.
+    ///
+    /// # Returns
+    /// The serialized keyring, which must be kept secret.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(all(feature = "aegis", feature = "gcm"))] {
+    /// use aes_wasm::keyring::Keyring;
+    /// use aes_wasm::sealed::{open, seal};
+    /// use aes_wasm::Algorithm;
+    /// let mut keyring = Keyring::new();
+    /// keyring.rotate(Algorithm::Aegis256);
+    /// let ciphertext = keyring.encrypt(b"record", b"").unwrap();
+    ///
+    /// // Store the keyset encrypted under a key-encryption key.
+    /// let (alg, kek) = (Algorithm::Aes256Gcm, Algorithm::Aes256Gcm.generate_key());
+    /// let stored = seal(alg, keyring.export(), b"keyset", &kek).unwrap();
+    ///
+    /// let keyring = Keyring::import(open(&stored, b"keyset", &kek).unwrap()).unwrap();
+    /// assert_eq!(keyring.decrypt(&ciphertext, b"").unwrap(), b"record");
+    /// # }
+    /// ```
+    pub fn export(&self) -> Vec<u8> {
+        let keys_len: usize = self
+            .keys
+            .iter()
+            .map(|entry| KEY_HEADER_LEN + entry.key.len())
+            .sum();
+        let mut out = Vec::with_capacity(KEYSET_HEADER_LEN + keys_len);
+        out.push(KEYSET_VERSION);
+        out.push(self.primary.is_some() as u8);
+        out.extend_from_slice(&self.primary.unwrap_or(0).to_be_bytes());
+        out.extend_from_slice(&(self.keys.len() as u32).to_be_bytes());
+        for entry in &self.keys {
+            out.extend_from_slice(&entry.id.to_be_bytes());
+            out.push(entry.alg.id());
+            out.push(entry.status.id());
+            out.extend_from_slice(&entry.key);
+        }
+        out
+    }
+
+    /// Restores a keyring serialized by `export`.
+    ///
+    /// # Arguments
+    /// * `keyset` - The output of `export`.
+    ///
+    /// # Returns
+    /// The keyring, `Err(Error::InvalidLength)` if the keyset is truncated or has trailing data,
+    /// or `Err(Error::InvalidParameters)` if the keyset is invalid, or uses an algorithm that is
+    /// not enabled.
+    pub fn import(keyset: impl AsRef<[u8]>) -> Result<Self, Error> {
+        let keyset = keyset.as_ref();
+        if keyset.len() < KEYSET_HEADER_LEN {
+            return Err(Error::InvalidLength);
+        }
+        let (header, mut rest) = keyset.split_at(KEYSET_HEADER_LEN);
+        if header[0] != KEYSET_VERSION || header[1] > 1 {
+            return Err(Error::InvalidParameters);
+        }
+        let primary = u32::from_be_bytes(header[2..6].try_into().unwrap());
+        let count = u32::from_be_bytes(header[6..10].try_into().unwrap());
+        let mut keyring = Keyring::new();
+        for _ in 0..count {
+            if rest.len() < KEY_HEADER_LEN {
+                return Err(Error::InvalidLength);
+            }
+            let id = u32::from_be_bytes(rest[..4].try_into().unwrap());
+            let alg = Algorithm::from_id(rest[4])?;
+            let status = KeyStatus::from_id(rest[5])?;
+            let key_end = KEY_HEADER_LEN + alg.key_len();
+            if rest.len() < key_end {
+                return Err(Error::InvalidLength);
+            }
+            keyring.add_key(id, alg, &rest[KEY_HEADER_LEN..key_end])?;
+            keyring.entry_mut(id)?.status = status;
+            rest = &rest[key_end..];
+        }
+        if !rest.is_empty() {
+            return Err(Error::InvalidLength);
+        }
+        if header[1] == 1 {
+            keyring.set_primary(primary)?;
+        } else if primary != 0 {
+            return Err(Error::InvalidParameters);
+        }
+        Ok(keyring)
+    }
+}
+
+fn prefix_and_ad(id: u32, ad: &[u8]) -> Vec<u8> {
+    let mut prefix_and_ad = Vec::with_capacity(PREFIX_LEN + ad.len());
+    prefix_and_ad.push(VERSION);
+    prefix_and_ad.extend_from_slice(&id.to_be_bytes());
+    prefix_and_ad.extend_from_slice(ad);
+    prefix_and_ad
+}
+
+/// Returns the identifier of the key used to encrypt a message, without decrypting it.
+///
+/// # Returns
+/// The key identifier, or `None` if the prefix is invalid.
+pub fn key_id(ciphertext: impl AsRef<[u8]>) -> Option<u32> {
+    match ciphertext.as_ref() {
+        [VERSION, a, b, c, d, ..] => Some(u32::from_be_bytes([*a, *b, *c, *d])),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, feature = "gcm", feature = "aegis", feature = "ocb"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn keyring() {
+        let msg = b"hello world";
+        let ad = b"ad";
+        let mut keyring = Keyring::new();
+        assert_eq!(keyring.encrypt(msg, ad), Err(Error::InvalidParameters));
+
+        let id1 = keyring.rotate(Algorithm::Aes128Gcm);
+        let c1 = keyring.encrypt(msg, ad).unwrap();
+        assert_eq!(key_id(&c1), Some(id1));
+        let id2 = keyring.rotate(Algorithm::Aegis256);
+        let c2 = keyring.encrypt(msg, ad).unwrap();
+        assert_eq!(key_id(&c2), Some(id2));
+        assert_eq!(keyring.primary(), Some(id2));
+        assert_eq!(keyring.key_ids().count(), 2);
+        assert_eq!(keyring.decrypt(&c1, ad).unwrap(), msg);
+        assert_eq!(keyring.decrypt(&c2, ad).unwrap(), msg);
+        assert_eq!(keyring.decrypt(&c2, b""), Err(Error::VerificationFailed));
+
+        let mut tampered = c1.clone();
+        tampered[1..PREFIX_LEN].copy_from_slice(&id2.to_be_bytes());
+        // Too short for AEGIS-256.
+        assert_eq!(keyring.decrypt(&tampered, ad), Err(Error::InvalidLength));
+
+        assert_eq!(keyring.disable_key(id2), Err(Error::InvalidParameters));
+        keyring.disable_key(id1).unwrap();
+        assert_eq!(keyring.decrypt(&c1, ad), Err(Error::VerificationFailed));
+        assert_eq!(keyring.set_primary(id1), Err(Error::InvalidParameters));
+        keyring.enable_key(id1).unwrap();
+        assert_eq!(keyring.decrypt(&c1, ad).unwrap(), msg);
+        keyring.remove_key(id1).unwrap();
+        assert_eq!(keyring.decrypt(&c1, ad), Err(Error::VerificationFailed));
+        assert_eq!(keyring.remove_key(id1), Err(Error::InvalidParameters));
+
+        let key = [0u8; 16];
+        assert_eq!(
+            keyring.add_key(7, Algorithm::Aes256Gcm, &key),
+            Err(Error::InvalidKeyLength)
+        );
+        keyring.add_key(7, Algorithm::Aes128Ocb, &key).unwrap();
+        assert_eq!(
+            keyring.add_key(7, Algorithm::Aes128Ocb, &key),
+            Err(Error::InvalidParameters)
+        );
+        keyring.set_primary(7).unwrap();
+        let c3 = keyring.encrypt(msg, ad).unwrap();
+        assert_eq!(c3[..PREFIX_LEN], [VERSION, 0, 0, 0, 7]);
+        assert_eq!(keyring.decrypt(&c3, ad).unwrap(), msg);
+    }
+
+    #[test]
+    fn export_import() {
+        let (msg, ad) = (b"hello world", b"ad");
+        let keyring = Keyring::import(Keyring::new().export()).unwrap();
+        assert_eq!(keyring.primary(), None);
+        assert_eq!(keyring.key_ids().count(), 0);
+
+        let mut keyring = Keyring::new();
+        let id1 = keyring.rotate(Algorithm::Aes128Gcm);
+        let c1 = keyring.encrypt(msg, ad).unwrap();
+        let id2 = keyring.rotate(Algorithm::Aegis256);
+        let c2 = keyring.encrypt(msg, ad).unwrap();
+        keyring
+            .add_key(7, Algorithm::Aes128Ocb, &[7u8; 16])
+            .unwrap();
+        keyring.disable_key(7).unwrap();
+
+        let keyset = keyring.export();
+        assert_eq!(
+            keyset.len(),
+            KEYSET_HEADER_LEN + 3 * KEY_HEADER_LEN + 16 + 32 + 16
+        );
+        let imported = Keyring::import(&keyset).unwrap();
+        assert_eq!(imported.export(), keyset);
+        assert_eq!(imported.primary(), Some(id2));
+        assert_eq!(imported.key_ids().collect::<Vec<_>>(), [id1, id2, 7]);
+        assert_eq!(
+            imported.key_info(7),
+            Some((Algorithm::Aes128Ocb, KeyStatus::Disabled))
+        );
+        assert_eq!(imported.decrypt(&c1, ad).unwrap(), msg);
+        assert_eq!(imported.decrypt(&c2, ad).unwrap(), msg);
+        assert_eq!(
+            keyring
+                .decrypt(imported.encrypt(msg, ad).unwrap(), ad)
+                .unwrap(),
+            msg
+        );
+
+        for len in [
+            0,
+            KEYSET_HEADER_LEN - 1,
+            KEYSET_HEADER_LEN + 5,
+            keyset.len() - 1,
+        ] {
+            assert_eq!(
+                Keyring::import(&keyset[..len]).err(),
+                Some(Error::InvalidLength)
+            );
+        }
+        let mut trailing = keyset.clone();
+        trailing.push(0);
+        assert_eq!(Keyring::import(&trailing).err(), Some(Error::InvalidLength));
+        for (pos, value) in [
+            (0, 2),
+            (1, 2),
+            (KEYSET_HEADER_LEN + 4, 0),
+            (KEYSET_HEADER_LEN + 5, 3),
+        ] {
+            let mut invalid = keyset.clone();
+            invalid[pos] = value;
+            assert_eq!(
+                Keyring::import(&invalid).err(),
+                Some(Error::InvalidParameters)
+            );
+        }
+        // The primary key must exist and be enabled.
+        let mut invalid = keyset.clone();
+        invalid[2..6].copy_from_slice(&7u32.to_be_bytes());
+        assert_eq!(
+            Keyring::import(&invalid).err(),
+            Some(Error::InvalidParameters)
+        );
+    }
+}
@@ -2,10 +2,11 @@
 //!
 //! High-performance AEAD, stream cipher, and MAC primitives for WebAssembly/WASI.
 //!
-//! This crate provides a simple, dependency-free API for cryptography in WASI environments.
+//! This crate provides a simple API with minimal dependencies for cryptography in WASI environments.
 //!
 //! ## Example: AES-128-GCM
 //! ```rust
+//! # #[cfg(feature = "gcm")] {
 //! use aes_wasm::aes128gcm::{encrypt, decrypt, Key, Nonce};
 //! let key = Key::default();
 //! let nonce = Nonce::default();
@@ -14,6 +15,7 @@
 //! let ciphertext = encrypt(msg, ad, &key, nonce);
 //! let plaintext = decrypt(ciphertext, ad, &key, nonce).unwrap();
 //! assert_eq!(plaintext, msg);
+//! # }
 //! ```
 //!
 //! AEAD ciphers for WebAssembly, including AEGIS, AES-GCM, AES-OCB, AES-CBC, AES-CTR, and CMAC.
@@ -23,6 +25,7 @@
 //!
 //! # Example
 //! ```
+//! # #[cfg(feature = "gcm")] {
 //! use aes_wasm::aes128gcm::{encrypt, decrypt, Key, Nonce};
 //! let key = Key::default();
 //! let nonce = Nonce::default();
@@ -31,8 +34,68 @@
 //! let ciphertext = encrypt(msg, ad, &key, nonce);
 //! let plaintext = decrypt(ciphertext, ad, &key, nonce).unwrap();
 //! assert_eq!(plaintext, msg);
+//! # }
 //! ```
+//!
+//! ## Cargo features
+//!
+//! - `std` (default): implements `std::error::Error` and enables the `io` module. Implies `alloc`
+//!   and `getrandom`.
+//! - `alloc`: enables the functions returning a `Vec`, and the modules built on top of them.
+//! - `getrandom`: enables key and nonce generation using the system's random number generator.
+//! - `aegis`, `gcm`, `ocb`, `cbc`, `ctr`, `cmac` (all enabled by default): enable the modules of
+//!   each algorithm family, and the modules built on top of them. `Algorithm` only includes the
+//!   variants of the enabled AEAD families.
+//! - `hkdf` (default): enables the [`hkdf`] module. It and `cbc` pull in the `hmac-sha256` and
+//!   `hmac-sha512` crates.
+//! - `build-from-source`: on WebAssembly, builds the Zig library from `wasm-libs` with a local Zig
+//!   toolchain instead of linking the prebuilt archive.
+//! - `wasm-bindgen`: exposes the ciphers to JavaScript on `wasm32-unknown-unknown`, see
+//!   [`js`].
+//! - `capi`: exports the ciphers and MACs as `extern "C"` functions, declared in
+//!   `include/aes_wasm.h`, see [`capi`]. Implies `alloc`.
+//! - `pure-rust`: uses the pure-Rust backend on every target, so that no static library is
+//!   linked. Takes precedence over `build-from-source`.
+//!
+//! With neither, the crate is `#![no_std]` and only the in-place and slice-based APIs are
+//! available.
+//!
+//! ## Backends
+//!
+//! On `wasm32-wasi` and `wasm32-unknown-unknown` targets, the ciphers are implemented by a Zig
+//! library. On every other target, or with the `pure-rust` feature, a pure-Rust implementation
+//! with the same behavior is used instead. On WebAssembly, it uses `simd128` instructions when
+//! that target feature is enabled, e.g. with `RUSTFLAGS="-C target-feature=+simd128"`.
+//!
+//! The prebuilt library computes AES-OCB tags that don't match RFC 7253 when associated data is
+//! present, and can't encrypt in place. These messages are always processed by the pure-Rust
+//! implementation, so that both backends produce the same output. Messages encrypted with
+//! associated data by version 0.1.17 or earlier on WebAssembly can be decrypted with
+//! [`aes128ocb::decrypt_legacy`] and [`aes256ocb::decrypt_legacy`].
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
+#[cfg(all(
+    feature = "alloc",
+    not(feature = "std"),
+    any(feature = "getrandom", feature = "cbc")
+))]
+use alloc::vec;
+#[cfg(all(
+    feature = "alloc",
+    not(feature = "std"),
+    any(
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr"
+    )
+))]
+use alloc::vec::Vec;
 use core::fmt::{self, Display};
 
 /// Error type for AEAD operations.
@@ -41,44 +104,385 @@ use core::fmt::{self, Display};
 ///
 /// # Example
 /// ```
+/// # #[cfg(feature = "gcm")] {
 /// use aes_wasm::aes128gcm::{decrypt, Key, Nonce};
 /// use aes_wasm::Error;
 /// let key = Key::default();
 /// let nonce = Nonce::default();
 /// let ad = b"ad";
 /// // Intentionally use invalid ciphertext
-/// let ciphertext = b"invalid";
+/// let ciphertext = [0u8; 32];
 /// let result = decrypt(ciphertext, ad, &key, nonce);
 /// assert_eq!(result, Err(Error::VerificationFailed));
+/// # }
 /// ```
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     /// Ciphertext verification failed.
     VerificationFailed,
+    /// Invalid or unsupported parameters.
+    InvalidParameters,
+    /// The random bit generator must be reseeded before producing more output.
+    ReseedRequired,
+    /// The key length doesn't match the algorithm.
+    InvalidKeyLength,
+    /// The nonce length doesn't match the algorithm.
+    InvalidNonceLength,
+    /// The message or the associated data exceeds the algorithm's length limit.
+    MessageTooLong,
+    /// The input is shorter than the algorithm requires, or its length is invalid.
+    ///
+    /// Lengths are not secret, so this is returned before any verification takes place.
+    InvalidLength,
+    /// The padding of an unauthenticated CBC ciphertext is invalid.
+    ///
+    /// Authenticated ciphers report padding errors as `VerificationFailed`.
+    InvalidPadding,
+    /// The output buffer is too small.
+    BufferTooSmall,
+    /// The cipher implementation failed to encrypt the message.
+    ///
+    /// This isn't expected with lengths accepted by the length checks, and indicates a bug.
+    EncryptionFailed,
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for Error {}
 
 impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Error::VerificationFailed => write!(f, "Verification failed"),
+            Error::InvalidParameters => write!(f, "Invalid parameters"),
+            Error::ReseedRequired => write!(f, "Reseed required"),
+            Error::InvalidKeyLength => write!(f, "Invalid key length"),
+            Error::InvalidNonceLength => write!(f, "Invalid nonce length"),
+            Error::MessageTooLong => write!(f, "Message too long"),
+            Error::InvalidLength => write!(f, "Invalid length"),
+            Error::InvalidPadding => write!(f, "Invalid padding"),
+            Error::BufferTooSmall => write!(f, "Buffer too small"),
+            Error::EncryptionFailed => write!(f, "Encryption failed"),
+        }
+    }
+}
+
+/// Converts an error to a `std::io::Error` for the `io` and `async_io` adapters.
+#[cfg(all(
+    feature = "std",
+    any(feature = "ctr", feature = "async"),
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub(crate) fn to_io_error(err: Error) -> std::io::Error {
+    let kind = match err {
+        Error::VerificationFailed | Error::InvalidLength | Error::InvalidPadding => {
+            std::io::ErrorKind::InvalidData
         }
+        _ => std::io::ErrorKind::InvalidInput,
+    };
+    std::io::Error::new(kind, err)
+}
+
+/// Fixed-size output keying material, such as the `Key` or `Nonce` type of any module.
+///
+/// This is the output type of the `hkdf` and `password` key derivation functions.
+#[cfg(any(feature = "hkdf", feature = "password"))]
+pub trait Okm: Sized {
+    /// Returns an all-zero output, to be overwritten with keying material.
+    fn zeroed() -> Self;
+
+    /// Returns the bytes of the output, to be filled with keying material.
+    fn as_mut_bytes(&mut self) -> &mut [u8];
+}
+
+#[cfg(any(feature = "hkdf", feature = "password"))]
+impl<const N: usize> Okm for [u8; N] {
+    fn zeroed() -> Self {
+        [0u8; N]
+    }
+
+    fn as_mut_bytes(&mut self) -> &mut [u8] {
+        self
     }
 }
 
+/// Fills a buffer with random bytes from the system's secure random number generator.
+///
+/// Panics if no secure random number generator is available.
+#[cfg(all(
+    feature = "getrandom",
+    any(
+        feature = "aegis",
+        feature = "gcm",
+        feature = "ocb",
+        feature = "cbc",
+        feature = "ctr",
+        feature = "cmac"
+    )
+))]
+pub(crate) fn random_bytes(buf: &mut [u8]) {
+    getrandom::fill(buf).expect("secure random number generator unavailable");
+}
+
+/// Compares two byte slices in constant time.
+#[cfg(any(
+    feature = "cmac",
+    all(
+        feature = "cbc",
+        any(
+            feature = "alloc",
+            all(zig_backend, not(feature = "build-from-source"))
+        )
+    ),
+    feature = "ocb",
+    all(not(zig_backend), any(feature = "aegis", feature = "gcm"))
+))]
+pub(crate) fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let d = a.iter().zip(b).fold(0u8, |d, (x, y)| d | (x ^ y));
+    core::hint::black_box(d) == 0
+}
+
+/// Defines the `MAX_MSG_LEN` and `MAX_AD_LEN` constants of an AEAD module, and a
+/// `check_lengths` function that checks a message and its associated data against them.
+#[cfg(any(
+    feature = "aegis",
+    feature = "gcm",
+    feature = "ocb",
+    all(feature = "alloc", feature = "cbc")
+))]
+macro_rules! length_limits {
+    (aegis) => {
+        length_limits!(
+            (1 << 61) - 1,
+            "The length of messages in bits must fit in 64 bits.",
+            (1 << 61) - 1,
+            "The length of the associated data in bits must fit in 64 bits."
+        );
+    };
+    (gcm) => {
+        length_limits!(
+            (1 << 36) - 32,
+            "NIST SP 800-38D limits plaintexts to 2^39 - 256 bits.",
+            (1 << 61) - 1,
+            "NIST SP 800-38D limits the associated data to 2^64 - 1 bits."
+        );
+    };
+    (ocb) => {
+        length_limits!(
+            u64::MAX,
+            "RFC 7253 doesn't limit the length of messages.",
+            u64::MAX,
+            "RFC 7253 doesn't limit the length of the associated data."
+        );
+    };
+    (cbc_hmac) => {
+        length_limits!(
+            (1 << 61) - 1,
+            "The length of messages in bits must fit in 64 bits.",
+            (1 << 61) - 1,
+            "The length of the associated data in bits is encoded as a 64-bit integer."
+        );
+    };
+    ($max_msg_len:expr, $msg_reason:literal, $max_ad_len:expr, $ad_reason:literal) => {
+        /// The maximum length of a message in bytes.
+        ///
+        #[doc = $msg_reason]
+        pub const MAX_MSG_LEN: u64 = $max_msg_len;
+        /// The maximum length of the associated data in bytes.
+        ///
+        #[doc = $ad_reason]
+        pub const MAX_AD_LEN: u64 = $max_ad_len;
+
+        /// Returns `Err(Error::MessageTooLong)` if `msg_len` exceeds `MAX_MSG_LEN`, or `ad_len`
+        /// exceeds `MAX_AD_LEN`.
+        #[cfg_attr(not(feature = "alloc"), allow(dead_code))]
+        fn check_lengths(msg_len: usize, ad_len: usize) -> Result<(), Error> {
+            if msg_len as u64 > MAX_MSG_LEN || ad_len as u64 > MAX_AD_LEN {
+                return Err(Error::MessageTooLong);
+            }
+            Ok(())
+        }
+    };
+}
+
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub use algorithm::Algorithm;
+
+#[cfg(feature = "aegis")]
 pub mod aegis128l;
+#[cfg(feature = "aegis")]
 pub mod aegis128x2;
+#[cfg(feature = "aegis")]
 pub mod aegis128x4;
+#[cfg(feature = "aegis")]
 pub mod aegis256;
+#[cfg(feature = "aegis")]
 pub mod aegis256x2;
+#[cfg(feature = "aegis")]
 pub mod aegis256x4;
+#[cfg(feature = "cbc")]
 pub mod aes128cbc;
+#[cfg(all(feature = "alloc", feature = "cbc"))]
+pub mod aes128cbc_hs256;
+#[cfg(feature = "ctr")]
 pub mod aes128ctr;
+#[cfg(feature = "gcm")]
 pub mod aes128gcm;
+#[cfg(feature = "ocb")]
 pub mod aes128ocb;
+#[cfg(feature = "cbc")]
 pub mod aes256cbc;
+#[cfg(all(feature = "alloc", feature = "cbc"))]
+pub mod aes256cbc_hs512;
+#[cfg(feature = "ctr")]
 pub mod aes256ctr;
+#[cfg(feature = "gcm")]
 pub mod aes256gcm;
+#[cfg(feature = "ocb")]
 pub mod aes256ocb;
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod algorithm;
+#[cfg(all(
+    feature = "async",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod async_io;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "cmac")]
 pub mod cmac_aes128;
+#[cfg(feature = "ctr")]
+pub mod drbg;
+#[cfg(all(
+    feature = "alloc",
+    feature = "getrandom",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod envelope;
+#[cfg(feature = "hkdf")]
+pub mod hkdf;
+#[cfg(all(
+    feature = "std",
+    feature = "ctr",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod io;
+#[cfg(feature = "wasm-bindgen")]
+pub mod js;
+#[cfg(feature = "cmac")]
+pub mod kdf;
+#[cfg(all(
+    feature = "alloc",
+    feature = "getrandom",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod keyring;
+#[cfg(feature = "password")]
+pub mod password;
+#[cfg(feature = "cmac")]
+pub mod pmac_aes128;
+#[cfg(feature = "cmac")]
+pub mod poly1305_aes;
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod sealed;
+#[cfg(all(feature = "alloc", feature = "aegis"))]
+pub mod secretstream;
+#[cfg(any(
+    not(zig_backend),
+    test,
+    feature = "ocb",
+    feature = "ctr",
+    feature = "cmac"
+))]
+mod soft;
+#[cfg(all(
+    feature = "alloc",
+    any(feature = "aegis", feature = "gcm", feature = "ocb", feature = "cbc")
+))]
+pub mod stream;
+#[cfg(feature = "cmac")]
+pub mod xcbc_aes128;
+
+#[cfg(all(test, zig_backend))]
+mod test {
+    /// Returns the symbols listed in the index of a GNU `ar` archive.
+    fn archive_symbols(archive: &[u8]) -> Vec<&str> {
+        assert_eq!(&archive[..8], b"!<arch>\n");
+        let (header, index) = archive[8..].split_at(60);
+        assert_eq!(&header[..2], b"/ ");
+        let count = u32::from_be_bytes(index[..4].try_into().unwrap()) as usize;
+        let names = &index[4 + 4 * count..];
+        let mut symbols: Vec<_> = names
+            .split(|&b| b == 0)
+            .take(count)
+            .map(|name| core::str::from_utf8(name).unwrap())
+            .collect();
+        symbols.sort_unstable();
+        symbols
+    }
+
+    const PREBUILT: &[u8] = include_bytes!("../wasm-libs/libaes.a");
+
+    #[test]
+    fn prebuilt_symbols() {
+        let mut expected = [
+            "_aegis128l_decrypt",
+            "_aegis128l_encrypt",
+            "_aegis128x2_decrypt",
+            "_aegis128x2_encrypt",
+            "_aegis128x4_decrypt",
+            "_aegis128x4_encrypt",
+            "_aegis256_decrypt",
+            "_aegis256_encrypt",
+            "_aegis256x2_decrypt",
+            "_aegis256x2_encrypt",
+            "_aegis256x4_decrypt",
+            "_aegis256x4_encrypt",
+            "aes128cbc_decrypt",
+            "aes128cbc_encrypt",
+            "aes128ctr",
+            "aes128gcm_decrypt",
+            "aes128gcm_encrypt",
+            "aes128ocb_decrypt",
+            "aes128ocb_encrypt",
+            "aes256cbc_decrypt",
+            "aes256cbc_encrypt",
+            "aes256ctr",
+            "aes256gcm_decrypt",
+            "aes256gcm_encrypt",
+            "aes256ocb_decrypt",
+            "aes256ocb_encrypt",
+            "cmac_aes128",
+        ];
+        expected.sort_unstable();
+        assert_eq!(archive_symbols(PREBUILT), expected);
+    }
+
+    /// The tests of the other modules check the behavior of the library that was built; this
+    /// checks that the committed one was built from the same source.
+    ///
+    /// Zig builds are reproducible, but only with the same Zig release and optimization mode:
+    /// regenerate `wasm-libs/libaes.a` with `zig build -Doptimize=ReleaseFast` and Zig 0.14.
+    #[cfg(feature = "build-from-source")]
+    #[test]
+    fn prebuilt_is_reproducible() {
+        let built: &[u8] = include_bytes!(env!("AES_WASM_ZIG_LIB"));
+        assert_eq!(archive_symbols(built), archive_symbols(PREBUILT));
+        assert!(
+            built == PREBUILT,
+            "wasm-libs/libaes.a wasn't built from wasm-libs/aes.zig"
+        );
+    }
+}
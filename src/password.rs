@@ -0,0 +1,521 @@
+//! Password-based encryption for WASI (WebAssembly System Interface).
+//!
+//! Derives keys from passphrases using PBKDF2-HMAC-SHA-256, scrypt or Argon2id, and
+//! encrypts data with AEGIS-256 or AES-256-GCM under a key derived from a fresh random salt.
+//!
+//! Requires the `password` cargo feature.
+//!
+//! The KDFs are provided by the RustCrypto `pbkdf2`, `scrypt` and `argon2` crates, rather than
+//! by the implementations of Zig's standard library (`std.crypto.pwhash`) exported from the Zig
+//! library like the ciphers. They must also be available with the pure-Rust backend, which
+//! would otherwise need a second implementation of each, and they are built from hash
+//! functions, so they wouldn't share any code with the AES implementation.
+//!
+//! ## Sealed format
+//!
+//! `seal_with_password` outputs a self-describing header followed by the AEAD ciphertext:
+//!
+//! | offset   | length | content                                                      |
+//! | :------- | -----: | :----------------------------------------------------------- |
+//! | 0        |      1 | format version (`1`)                                         |
+//! | 1        |      1 | KDF: `1` = PBKDF2-HMAC-SHA-256, `2` = scrypt, `3` = Argon2id  |
+//! | 2        |     12 | KDF parameters, as three big-endian `u32` values             |
+//! | 14       |      1 | cipher: `1` = AES-256-GCM, `2` = AEGIS-256                   |
+//! | 15       |     16 | salt                                                         |
+//! | 31       |      n | nonce (12 bytes for AES-256-GCM, 32 bytes for AEGIS-256)     |
+//! | 31 + n   |        | ciphertext and tag                                           |
+//!
+//! The KDF parameters are `[iterations, 0, 0]` for PBKDF2, `[log_n, r, p]` for scrypt and
+//! `[m_cost, t_cost, p_cost]` for Argon2id. The whole header is used as additional data.
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::password::{open_with_password, seal_with_password, Cipher, Kdf};
+//! let kdf = Kdf::Argon2id { m_cost: 1024, t_cost: 2, p_cost: 1 };
+//! let sealed = seal_with_password(b"hello", b"passphrase", kdf, Cipher::Aegis256).unwrap();
+//! let msg = open_with_password(&sealed, b"passphrase").unwrap();
+//! assert_eq!(msg, b"hello");
+//! ```
+
+pub use crate::*;
+
+/// The format version written by `seal_with_password`.
+pub const VERSION: u8 = 1;
+/// The length of the salt in bytes.
+///
+/// This constant is used for salt array sizing.
+pub const SALT_LEN: usize = 16;
+
+/// Salt type (16 bytes).
+pub type Salt = [u8; SALT_LEN];
+
+const HEADER_FIXED_LEN: usize = 1 + 1 + 12 + 1 + SALT_LEN;
+
+const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+const MAX_MEMORY_KIB: u64 = 1 << 20;
+const MAX_TIME_COST: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// Password-based key derivation function, with its cost parameters.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Kdf {
+    /// PBKDF2 with HMAC-SHA-256.
+    Pbkdf2Sha256 {
+        /// Number of iterations.
+        iterations: u32,
+    },
+    /// scrypt (RFC 7914).
+    Scrypt {
+        /// Base-2 logarithm of the CPU/memory cost `N`.
+        log_n: u8,
+        /// Block size.
+        r: u32,
+        /// Parallelization.
+        p: u32,
+    },
+    /// Argon2id, version 0x13 (RFC 9106).
+    Argon2id {
+        /// Memory size, in KiB.
+        m_cost: u32,
+        /// Number of passes.
+        t_cost: u32,
+        /// Degree of parallelism.
+        p_cost: u32,
+    },
+}
+
+impl Default for Kdf {
+    /// Argon2id with 19 MiB of memory and 2 passes.
+    fn default() -> Self {
+        Kdf::Argon2id {
+            m_cost: 19 * 1024,
+            t_cost: 2,
+            p_cost: 1,
+        }
+    }
+}
+
+impl Kdf {
+    fn id(&self) -> u8 {
+        match self {
+            Kdf::Pbkdf2Sha256 { .. } => 1,
+            Kdf::Scrypt { .. } => 2,
+            Kdf::Argon2id { .. } => 3,
+        }
+    }
+
+    fn params(&self) -> [u32; 3] {
+        match *self {
+            Kdf::Pbkdf2Sha256 { iterations } => [iterations, 0, 0],
+            Kdf::Scrypt { log_n, r, p } => [log_n as u32, r, p],
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => [m_cost, t_cost, p_cost],
+        }
+    }
+
+    fn from_params(id: u8, params: [u32; 3]) -> Result<Self, Error> {
+        let [a, b, c] = params;
+        match id {
+            1 if b == 0 && c == 0 => Ok(Kdf::Pbkdf2Sha256 { iterations: a }),
+            2 => Ok(Kdf::Scrypt {
+                log_n: a.try_into().map_err(|_| Error::InvalidParameters)?,
+                r: b,
+                p: c,
+            }),
+            3 => Ok(Kdf::Argon2id {
+                m_cost: a,
+                t_cost: b,
+                p_cost: c,
+            }),
+            _ => Err(Error::InvalidParameters),
+        }
+    }
+
+    fn check_limits(&self) -> Result<(), Error> {
+        let ok = match *self {
+            Kdf::Pbkdf2Sha256 { iterations } => iterations <= MAX_PBKDF2_ITERATIONS,
+            Kdf::Scrypt { log_n, r, p } => {
+                log_n < 32
+                    && (r as u64) << log_n >> 3 <= MAX_MEMORY_KIB
+                    && r as u64 * p as u64 <= 1 << 20
+                    && p <= MAX_PARALLELISM
+            }
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                m_cost as u64 <= MAX_MEMORY_KIB
+                    && t_cost <= MAX_TIME_COST
+                    && p_cost <= MAX_PARALLELISM
+            }
+        };
+        if !ok {
+            return Err(Error::InvalidParameters);
+        }
+        Ok(())
+    }
+
+    /// Derives keying material from a password and a salt.
+    ///
+    /// # Arguments
+    /// * `out` - Output buffer, filled with keying material.
+    /// * `password` - The password.
+    /// * `salt` - The salt, which should be unique for each password.
+    ///
+    /// # Returns
+    /// `Ok(())` on success, or `Err(Error::InvalidParameters)` if the cost parameters
+    /// or the output length are not supported by the KDF.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::password::Kdf;
+    /// let kdf = Kdf::Pbkdf2Sha256 { iterations: 1000 };
+    /// let mut out = [0u8; 32];
+    /// kdf.derive(&mut out, b"password", b"salt").unwrap();
+    /// ```
+    pub fn derive(
+        &self,
+        out: &mut [u8],
+        password: impl AsRef<[u8]>,
+        salt: impl AsRef<[u8]>,
+    ) -> Result<(), Error> {
+        let (password, salt) = (password.as_ref(), salt.as_ref());
+        match *self {
+            Kdf::Pbkdf2Sha256 { iterations } => {
+                if iterations == 0 {
+                    return Err(Error::InvalidParameters);
+                }
+                pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password, salt, iterations, out);
+            }
+            Kdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, out.len())
+                    .map_err(|_| Error::InvalidParameters)?;
+                scrypt::scrypt(password, salt, &params, out)
+                    .map_err(|_| Error::InvalidParameters)?;
+            }
+            Kdf::Argon2id {
+                m_cost,
+                t_cost,
+                p_cost,
+            } => {
+                let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(out.len()))
+                    .map_err(|_| Error::InvalidParameters)?;
+                argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+                    .hash_password_into(password, salt, out)
+                    .map_err(|_| Error::InvalidParameters)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Derives a key of the required size from a password and a salt.
+    ///
+    /// # Arguments
+    /// * `password` - The password.
+    /// * `salt` - The salt, which should be unique for each password.
+    ///
+    /// # Returns
+    /// The derived key, typically the `Key` type of a module.
+    ///
+    /// # Example
+    /// ```
+    /// use aes_wasm::{aes256gcm, password::Kdf};
+    /// let kdf = Kdf::Scrypt { log_n: 10, r: 8, p: 1 };
+    /// let key: aes256gcm::Key = kdf.derive_key(b"password", b"salt").unwrap();
+    /// ```
+    pub fn derive_key<K: Okm>(
+        &self,
+        password: impl AsRef<[u8]>,
+        salt: impl AsRef<[u8]>,
+    ) -> Result<K, Error> {
+        let mut key = K::zeroed();
+        self.derive(key.as_mut_bytes(), password, salt)?;
+        Ok(key)
+    }
+}
+
+/// AEAD used to encrypt the data in the sealed format.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum Cipher {
+    /// AES-256-GCM.
+    Aes256Gcm,
+    /// AEGIS-256.
+    #[default]
+    Aegis256,
+}
+
+impl Cipher {
+    fn id(&self) -> u8 {
+        match self {
+            Cipher::Aes256Gcm => 1,
+            Cipher::Aegis256 => 2,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self, Error> {
+        match id {
+            1 => Ok(Cipher::Aes256Gcm),
+            2 => Ok(Cipher::Aegis256),
+            _ => Err(Error::InvalidParameters),
+        }
+    }
+
+    fn nonce_len(&self) -> usize {
+        match self {
+            Cipher::Aes256Gcm => aes256gcm::NONCE_LEN,
+            Cipher::Aegis256 => aegis256::NONCE_LEN,
+        }
+    }
+}
+
+/// Encrypts a message with a key derived from a password.
+///
+/// A random salt and a random nonce are generated for each message.
+///
+/// # Arguments
+/// * `msg` - The plaintext message to encrypt.
+/// * `password` - The password.
+/// * `kdf` - The key derivation function and its cost parameters.
+/// * `cipher` - The AEAD used to encrypt the message.
+///
+/// # Returns
+/// The header followed by the ciphertext and tag, `Err(Error::InvalidParameters)`
+/// if the KDF parameters are invalid or exceed the limits of `open_with_password`, or
+/// `Err(Error::MessageTooLong)` if the message exceeds the cipher's length limit.
+///
+/// # Example
+/// ```
+/// use aes_wasm::password::{seal_with_password, Cipher, Kdf};
+/// let kdf = Kdf::Pbkdf2Sha256 { iterations: 1000 };
+/// let sealed = seal_with_password(b"hello", b"passphrase", kdf, Cipher::Aes256Gcm).unwrap();
+/// ```
+pub fn seal_with_password(
+    msg: impl AsRef<[u8]>,
+    password: impl AsRef<[u8]>,
+    kdf: Kdf,
+    cipher: Cipher,
+) -> Result<Vec<u8>, Error> {
+    // Don't produce data that `open_with_password` would refuse to decrypt.
+    kdf.check_limits()?;
+    let mut salt = Salt::default();
+    random_bytes(&mut salt);
+    let key: [u8; 32] = kdf.derive_key(password, salt)?;
+
+    let mut header = Vec::with_capacity(HEADER_FIXED_LEN + cipher.nonce_len());
+    header.push(VERSION);
+    header.push(kdf.id());
+    kdf.params()
+        .iter()
+        .for_each(|x| header.extend_from_slice(&x.to_be_bytes()));
+    header.push(cipher.id());
+    header.extend_from_slice(&salt);
+    let nonce_pos = header.len();
+    header.resize(nonce_pos + cipher.nonce_len(), 0);
+    random_bytes(&mut header[nonce_pos..]);
+
+    let nonce = &header[nonce_pos..];
+    let ciphertext = match cipher {
+        Cipher::Aes256Gcm => aes256gcm::try_encrypt(msg, &header, &key, nonce.try_into().unwrap()),
+        Cipher::Aegis256 => aegis256::try_encrypt(msg, &header, &key, nonce.try_into().unwrap()),
+    }?;
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Decrypts data produced by `seal_with_password`.
+///
+/// Headers requesting more than 1 GiB of memory, more than 64 Argon2 passes, more than
+/// 10 million PBKDF2 iterations, or a parallelism above 16 are rejected.
+///
+/// # Arguments
+/// * `sealed` - The header followed by the ciphertext and tag.
+/// * `password` - The password.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidParameters)` if the header
+/// is not supported, `Err(Error::InvalidLength)` if the input is too short, or
+/// `Err(Error::VerificationFailed)` if the password is wrong or the data was altered.
+///
+/// # Example
+/// ```
+/// use aes_wasm::password::{open_with_password, seal_with_password, Cipher, Kdf};
+/// let kdf = Kdf::Pbkdf2Sha256 { iterations: 1000 };
+/// let sealed = seal_with_password(b"hello", b"passphrase", kdf, Cipher::Aes256Gcm).unwrap();
+/// let msg = open_with_password(&sealed, b"passphrase").unwrap();
+/// ```
+pub fn open_with_password(
+    sealed: impl AsRef<[u8]>,
+    password: impl AsRef<[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let sealed = sealed.as_ref();
+    if sealed.len() < HEADER_FIXED_LEN {
+        return Err(Error::InvalidLength);
+    }
+    if sealed[0] != VERSION {
+        return Err(Error::InvalidParameters);
+    }
+    let params: [u32; 3] =
+        core::array::from_fn(|i| u32::from_be_bytes(sealed[2 + i * 4..][..4].try_into().unwrap()));
+    let kdf = Kdf::from_params(sealed[1], params)?;
+    kdf.check_limits()?;
+    let cipher = Cipher::from_id(sealed[14])?;
+    let salt = &sealed[15..HEADER_FIXED_LEN];
+    let header_len = HEADER_FIXED_LEN + cipher.nonce_len();
+    if sealed.len() < header_len {
+        return Err(Error::InvalidLength);
+    }
+    let (header, ciphertext) = sealed.split_at(header_len);
+    let nonce = &header[HEADER_FIXED_LEN..];
+
+    let key: [u8; 32] = kdf.derive_key(password, salt)?;
+    match cipher {
+        Cipher::Aes256Gcm => {
+            aes256gcm::decrypt(ciphertext, header, &key, nonce.try_into().unwrap())
+        }
+        Cipher::Aegis256 => aegis256::decrypt(ciphertext, header, &key, nonce.try_into().unwrap()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn kdfs() {
+        let out: [u8; 32] = Kdf::Pbkdf2Sha256 { iterations: 1 }
+            .derive_key(b"password", b"salt")
+            .unwrap();
+        assert_eq!(
+            out,
+            [
+                0x12, 0x0f, 0xb6, 0xcf, 0xfc, 0xf8, 0xb3, 0x2c, 0x43, 0xe7, 0x22, 0x52, 0x56, 0xc4,
+                0xf8, 0x37, 0xa8, 0x65, 0x48, 0xc9, 0x2c, 0xcc, 0x35, 0x48, 0x08, 0x05, 0x98, 0x7c,
+                0xb7, 0x0b, 0xe1, 0x7b
+            ]
+        );
+
+        let out: [u8; 64] = Kdf::Scrypt {
+            log_n: 10,
+            r: 8,
+            p: 16,
+        }
+        .derive_key(b"password", b"NaCl")
+        .unwrap();
+        assert_eq!(
+            out[..32],
+            [
+                0xfd, 0xba, 0xbe, 0x1c, 0x9d, 0x34, 0x72, 0x00, 0x78, 0x56, 0xe7, 0x19, 0x0d, 0x01,
+                0xe9, 0xfe, 0x7c, 0x6a, 0xd7, 0xcb, 0xc8, 0x23, 0x78, 0x30, 0xe7, 0x73, 0x76, 0x63,
+                0x4b, 0x37, 0x31, 0x62
+            ]
+        );
+
+        let out: [u8; 32] = Kdf::Argon2id {
+            m_cost: 64,
+            t_cost: 2,
+            p_cost: 1,
+        }
+        .derive_key(b"password", b"somesaltsomesalt")
+        .unwrap();
+        assert_eq!(
+            out,
+            [
+                0x1a, 0x98, 0xfb, 0xa8, 0xe6, 0x39, 0x44, 0x25, 0xc9, 0xbd, 0xc3, 0x33, 0xa9, 0xc7,
+                0x50, 0xaa, 0xf8, 0x11, 0xdf, 0x07, 0x0f, 0x5f, 0xb4, 0xa5, 0x78, 0x8e, 0x1c, 0xf4,
+                0xdb, 0x57, 0x93, 0xa1
+            ]
+        );
+
+        assert_eq!(
+            Kdf::Pbkdf2Sha256 { iterations: 0 }.derive_key::<[u8; 32]>(b"password", b"salt"),
+            Err(Error::InvalidParameters)
+        );
+    }
+
+    #[test]
+    fn seal_open() {
+        let kdfs = [
+            Kdf::Pbkdf2Sha256 { iterations: 100 },
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Argon2id {
+                m_cost: 64,
+                t_cost: 1,
+                p_cost: 1,
+            },
+        ];
+        let msg = b"hello world";
+        for kdf in kdfs {
+            for cipher in [Cipher::Aes256Gcm, Cipher::Aegis256] {
+                let mut sealed = seal_with_password(msg, b"password", kdf, cipher).unwrap();
+                let tag_len = match cipher {
+                    Cipher::Aes256Gcm => aes256gcm::TAG_LEN,
+                    Cipher::Aegis256 => aegis256::TAG_LEN,
+                };
+                assert_eq!(
+                    sealed.len(),
+                    HEADER_FIXED_LEN + cipher.nonce_len() + msg.len() + tag_len
+                );
+                assert_eq!(open_with_password(&sealed, b"password").unwrap(), msg);
+                assert_eq!(
+                    open_with_password(&sealed, b"wrong password"),
+                    Err(Error::VerificationFailed)
+                );
+                sealed[20] ^= 1;
+                assert_eq!(
+                    open_with_password(&sealed, b"password"),
+                    Err(Error::VerificationFailed)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn reject_expensive_headers() {
+        let kdf = Kdf::Argon2id {
+            m_cost: 64,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        let mut sealed = seal_with_password(b"msg", b"password", kdf, Cipher::Aegis256).unwrap();
+        sealed[2..6].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            open_with_password(&sealed, b"password"),
+            Err(Error::InvalidParameters)
+        );
+        sealed[1] = 42;
+        assert_eq!(
+            open_with_password(&sealed, b"password"),
+            Err(Error::InvalidParameters)
+        );
+
+        let kdfs = [
+            Kdf::Pbkdf2Sha256 {
+                iterations: MAX_PBKDF2_ITERATIONS + 1,
+            },
+            Kdf::Scrypt {
+                log_n: 21,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Argon2id {
+                m_cost: u32::MAX,
+                t_cost: 1,
+                p_cost: 1,
+            },
+        ];
+        for kdf in kdfs {
+            assert_eq!(
+                seal_with_password(b"msg", b"password", kdf, Cipher::Aegis256),
+                Err(Error::InvalidParameters)
+            );
+        }
+    }
+}
@@ -0,0 +1,266 @@
+//! PMAC-AES-128 message authentication code for WASI (WebAssembly System Interface).
+//!
+//! Provides message authentication using Rogaway's parallelizable MAC (PMAC1) with AES-128.
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::pmac_aes128::{mac, verify, generate_key};
+//! let key = generate_key();
+//! let msg = b"hello";
+//! let tag = mac(msg, &key);
+//! assert!(verify(&tag, msg, &key).is_ok());
+//! ```
+
+use crate::soft::Aes;
+pub use crate::*;
+
+/// The length of the key in bytes.
+///
+/// This constant is used for key array sizing.
+pub const KEY_LEN: usize = 16;
+/// The length of the authentication tag in bytes.
+///
+/// This constant is used for tag array sizing.
+pub const TAG_LEN: usize = 16;
+
+/// Key type for PMAC-AES128 (16 bytes).
+pub type Key = [u8; KEY_LEN];
+/// Tag type for PMAC-AES128 (16 bytes).
+pub type Tag = [u8; TAG_LEN];
+
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::pmac_aes128::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+type Block = [u8; 16];
+
+fn dbl(b: &Block) -> Block {
+    let x = u128::from_be_bytes(*b);
+    ((x << 1) ^ ((x >> 127) * 0x87)).to_be_bytes()
+}
+
+fn half(b: &Block) -> Block {
+    let x = u128::from_be_bytes(*b);
+    ((x >> 1) ^ ((x & 1) * ((1 << 127) | 0x43))).to_be_bytes()
+}
+
+fn xor(a: &mut Block, b: &[u8]) {
+    a.iter_mut().zip(b).for_each(|(a, b)| *a ^= b);
+}
+
+/// Incremental PMAC-AES128 state.
+///
+/// # Example
+/// ```
+/// use aes_wasm::pmac_aes128::{mac, Key, PmacAes128};
+/// let key = Key::default();
+/// let mut st = PmacAes128::new(&key);
+/// st.update(b"hello ");
+/// st.update(b"world");
+/// assert_eq!(st.finalize(), mac(b"hello world", &key));
+/// ```
+#[derive(Clone)]
+pub struct PmacAes128 {
+    aes: Aes,
+    l: [Block; 64],
+    l_inv: Block,
+    offset: Block,
+    sum: Block,
+    counter: u64,
+    buf: Block,
+    buf_len: usize,
+}
+
+impl PmacAes128 {
+    /// Creates a new PMAC state for the given key.
+    pub fn new(key: &Key) -> Self {
+        let aes = Aes::new(key);
+        let mut l0 = Block::default();
+        aes.encrypt_block(&mut l0);
+        let mut l = [Block::default(); 64];
+        l[0] = l0;
+        for i in 1..l.len() {
+            l[i] = dbl(&l[i - 1]);
+        }
+        PmacAes128 {
+            aes,
+            l,
+            l_inv: half(&l0),
+            offset: Block::default(),
+            sum: Block::default(),
+            counter: 0,
+            buf: Block::default(),
+            buf_len: 0,
+        }
+    }
+
+    fn process_block(&mut self, block: &Block) {
+        self.counter += 1;
+        let offset = self.l[self.counter.trailing_zeros() as usize];
+        xor(&mut self.offset, &offset);
+        let mut t = *block;
+        xor(&mut t, &self.offset);
+        self.aes.encrypt_block(&mut t);
+        xor(&mut self.sum, &t);
+    }
+
+    /// Absorbs more data into the state.
+    pub fn update(&mut self, input: impl AsRef<[u8]>) {
+        let mut input = input.as_ref();
+        while !input.is_empty() {
+            if self.buf_len == self.buf.len() {
+                let block = self.buf;
+                self.process_block(&block);
+                self.buf_len = 0;
+            }
+            let n = (self.buf.len() - self.buf_len).min(input.len());
+            self.buf[self.buf_len..][..n].copy_from_slice(&input[..n]);
+            self.buf_len += n;
+            input = &input[n..];
+        }
+    }
+
+    /// Computes the authentication tag.
+    pub fn finalize(mut self) -> Tag {
+        if self.buf_len == self.buf.len() {
+            xor(&mut self.sum, &self.buf);
+            xor(&mut self.sum, &self.l_inv);
+        } else {
+            self.buf[self.buf_len] = 0x80;
+            self.buf[self.buf_len + 1..].fill(0);
+            xor(&mut self.sum, &self.buf);
+        }
+        let mut tag = self.sum;
+        self.aes.encrypt_block(&mut tag);
+        tag
+    }
+
+    /// Computes the authentication tag and compares it with `expected` in constant time.
+    pub fn verify(self, expected: &Tag) -> Result<(), Error> {
+        if !ct_eq(&self.finalize(), expected) {
+            return Err(Error::VerificationFailed);
+        }
+        Ok(())
+    }
+}
+
+/// Computes the PMAC for a message using AES-128.
+///
+/// # Arguments
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// Authentication tag as a 16-byte array.
+///
+/// # Example
+/// ```
+/// use aes_wasm::pmac_aes128::{mac, Key};
+/// let key = Key::default();
+/// let msg = b"hello";
+/// let tag = mac(msg, &key);
+/// ```
+pub fn mac(msg: impl AsRef<[u8]>, key: &Key) -> Tag {
+    let mut st = PmacAes128::new(key);
+    st.update(msg);
+    st.finalize()
+}
+
+/// Verifies a PMAC tag in constant time.
+///
+/// # Arguments
+/// * `tag` - The expected authentication tag.
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// `Ok(())` if the tag is valid, or `Err(Error)` if it is not.
+///
+/// # Example
+/// ```
+/// use aes_wasm::pmac_aes128::{mac, verify, Key};
+/// let key = Key::default();
+/// let msg = b"hello";
+/// let tag = mac(msg, &key);
+/// assert!(verify(&tag, msg, &key).is_ok());
+/// ```
+pub fn verify(tag: &Tag, msg: impl AsRef<[u8]>, key: &Key) -> Result<(), Error> {
+    let mut st = PmacAes128::new(key);
+    st.update(msg);
+    st.verify(tag)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pmac_aes128() {
+        let key: Key = core::array::from_fn(|i| i as u8);
+        let msg: Vec<u8> = (0..34).collect();
+        let vectors: [(&[u8], Tag); 4] = [
+            (
+                &msg[..0],
+                [
+                    0x43, 0x99, 0x57, 0x2c, 0xd6, 0xea, 0x53, 0x41, 0xb8, 0xd3, 0x58, 0x76, 0xa7,
+                    0x09, 0x8a, 0xf7,
+                ],
+            ),
+            (
+                &msg[..16],
+                [
+                    0xeb, 0xbd, 0x82, 0x2f, 0xa4, 0x58, 0xda, 0xf6, 0xdf, 0xda, 0xd7, 0xc2, 0x7d,
+                    0xa7, 0x63, 0x38,
+                ],
+            ),
+            (
+                &msg[..32],
+                [
+                    0xe9, 0x7a, 0xc0, 0x4e, 0x9e, 0x5e, 0x33, 0x99, 0xce, 0x53, 0x55, 0xcd, 0x74,
+                    0x07, 0xbc, 0x75,
+                ],
+            ),
+            (
+                &msg[..34],
+                [
+                    0x5c, 0xba, 0x7d, 0x5e, 0xb2, 0x4f, 0x7c, 0x86, 0xcc, 0xc5, 0x46, 0x04, 0xe5,
+                    0x3d, 0x55, 0x12,
+                ],
+            ),
+        ];
+        for (msg, expected) in vectors {
+            assert_eq!(mac(msg, &key), expected);
+            assert!(verify(&expected, msg, &key).is_ok());
+        }
+        let tag = mac([0u8; 1000], &key);
+        assert_eq!(
+            tag,
+            [
+                0xc2, 0xc9, 0xfa, 0x1d, 0x99, 0x85, 0xf6, 0xf0, 0xd2, 0xaf, 0xf9, 0x15, 0xa0, 0xe8,
+                0xd9, 0x10
+            ]
+        );
+        let mut st = PmacAes128::new(&key);
+        for chunk in [0u8; 1000].chunks(7) {
+            st.update(chunk);
+        }
+        assert_eq!(st.finalize(), tag);
+        assert_eq!(
+            verify(&tag, [0u8; 999], &key),
+            Err(Error::VerificationFailed)
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Poly1305-AES message authentication code for WASI (WebAssembly System Interface).
+//!
+//! Provides message authentication using Bernstein's Poly1305-AES: a Poly1305 polynomial
+//! evaluation masked with the AES-128 encryption of a nonce.
+//!
+//! A nonce must never be reused with the same key.
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::poly1305_aes::{mac, verify, generate_key, generate_nonce};
+//! let key = generate_key();
+//! let nonce = generate_nonce();
+//! let msg = b"hello";
+//! let tag = mac(msg, &key, nonce);
+//! assert!(verify(&tag, msg, &key, nonce).is_ok());
+//! ```
+
+use crate::soft::Aes;
+pub use crate::*;
+
+/// The length of the key in bytes.
+///
+/// The key is the AES-128 key followed by the 16-byte Poly1305 `r` value.
+pub const KEY_LEN: usize = 32;
+/// The length of the authentication tag in bytes.
+///
+/// This constant is used for tag array sizing.
+pub const TAG_LEN: usize = 16;
+/// The length of the nonce in bytes.
+///
+/// This constant is used for nonce array sizing.
+pub const NONCE_LEN: usize = 16;
+
+/// Key type for Poly1305-AES (32 bytes).
+pub type Key = [u8; KEY_LEN];
+/// Tag type for Poly1305-AES (16 bytes).
+pub type Tag = [u8; TAG_LEN];
+/// Nonce type for Poly1305-AES (16 bytes).
+pub type Nonce = [u8; NONCE_LEN];
+
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::poly1305_aes::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+/// Generates a random nonce using the system's secure random number generator.
+///
+/// # Returns
+/// A new random nonce.
+///
+/// # Example
+/// ```
+/// use aes_wasm::poly1305_aes::generate_nonce;
+/// let nonce = generate_nonce();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_nonce() -> Nonce {
+    let mut nonce = Nonce::default();
+    random_bytes(&mut nonce);
+    nonce
+}
+
+fn le32(b: &[u8]) -> u32 {
+    u32::from_le_bytes(b[..4].try_into().unwrap())
+}
+
+/// Incremental Poly1305-AES state.
+///
+/// # Example
+/// ```
+/// use aes_wasm::poly1305_aes::{mac, Key, Nonce, Poly1305Aes};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let mut st = Poly1305Aes::new(&key, nonce);
+/// st.update(b"hello ");
+/// st.update(b"world");
+/// assert_eq!(st.finalize(), mac(b"hello world", &key, nonce));
+/// ```
+#[derive(Clone)]
+pub struct Poly1305Aes {
+    r: [u32; 5],
+    h: [u32; 5],
+    s: [u8; 16],
+    buf: [u8; 16],
+    buf_len: usize,
+}
+
+impl Poly1305Aes {
+    /// Creates a new Poly1305-AES state for the given key and nonce.
+    pub fn new(key: &Key, nonce: Nonce) -> Self {
+        let (k, r) = key.split_at(16);
+        let mut s = nonce;
+        Aes::new(k).encrypt_block(&mut s);
+        let r = [
+            le32(&r[0..]) & 0x3ffffff,
+            (le32(&r[3..]) >> 2) & 0x3ffff03,
+            (le32(&r[6..]) >> 4) & 0x3ffc0ff,
+            (le32(&r[9..]) >> 6) & 0x3f03fff,
+            (le32(&r[12..]) >> 8) & 0x00fffff,
+        ];
+        Poly1305Aes {
+            r,
+            h: [0; 5],
+            s,
+            buf: [0; 16],
+            buf_len: 0,
+        }
+    }
+
+    fn process_block(&mut self, block: &[u8; 16], hibit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+        let h = &mut self.h;
+        h[0] += le32(&block[0..]) & 0x3ffffff;
+        h[1] += (le32(&block[3..]) >> 2) & 0x3ffffff;
+        h[2] += (le32(&block[6..]) >> 4) & 0x3ffffff;
+        h[3] += (le32(&block[9..]) >> 6) & 0x3ffffff;
+        h[4] += (le32(&block[12..]) >> 8) | hibit;
+        let [h0, h1, h2, h3, h4] = h.map(u64::from);
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let c = (d4 >> 26) as u32;
+        h[0] = (d0 as u32 & 0x3ffffff) + c * 5;
+        h[1] = (d1 as u32 & 0x3ffffff) + (h[0] >> 26);
+        h[0] &= 0x3ffffff;
+        h[2] = d2 as u32 & 0x3ffffff;
+        h[3] = d3 as u32 & 0x3ffffff;
+        h[4] = d4 as u32 & 0x3ffffff;
+    }
+
+    /// Absorbs more data into the state.
+    pub fn update(&mut self, input: impl AsRef<[u8]>) {
+        let mut input = input.as_ref();
+        while !input.is_empty() {
+            let n = (self.buf.len() - self.buf_len).min(input.len());
+            self.buf[self.buf_len..][..n].copy_from_slice(&input[..n]);
+            self.buf_len += n;
+            input = &input[n..];
+            if self.buf_len == self.buf.len() {
+                let block = self.buf;
+                self.process_block(&block, 1 << 24);
+                self.buf_len = 0;
+            }
+        }
+    }
+
+    /// Computes the authentication tag.
+    pub fn finalize(mut self) -> Tag {
+        if self.buf_len > 0 {
+            let mut block = [0u8; 16];
+            block[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+            block[self.buf_len] = 1;
+            self.process_block(&block, 0);
+        }
+        let mut h = self.h;
+        let mut c = 0;
+        for x in h[1..].iter_mut() {
+            *x += c;
+            c = *x >> 26;
+            *x &= 0x3ffffff;
+        }
+        h[0] += c * 5;
+        c = h[0] >> 26;
+        h[0] &= 0x3ffffff;
+        h[1] += c;
+
+        // Compute h - p and select it if it is non-negative.
+        let mut g = [0u32; 5];
+        c = 5;
+        for i in 0..5 {
+            g[i] = h[i].wrapping_add(c);
+            c = g[i] >> 26;
+            g[i] &= 0x3ffffff;
+        }
+        g[4] = g[4].wrapping_add(c << 26).wrapping_sub(1 << 26);
+        let mask = (g[4] >> 31).wrapping_sub(1);
+        for i in 0..5 {
+            h[i] = (h[i] & !mask) | (g[i] & mask);
+        }
+
+        let h = [
+            h[0] | (h[1] << 26),
+            (h[1] >> 6) | (h[2] << 20),
+            (h[2] >> 12) | (h[3] << 14),
+            (h[3] >> 18) | (h[4] << 8),
+        ];
+        let mut tag = Tag::default();
+        let mut f = 0u64;
+        for i in 0..4 {
+            f += u64::from(h[i]) + u64::from(le32(&self.s[i * 4..]));
+            tag[i * 4..][..4].copy_from_slice(&(f as u32).to_le_bytes());
+            f >>= 32;
+        }
+        tag
+    }
+
+    /// Computes the authentication tag and compares it with `expected` in constant time.
+    pub fn verify(self, expected: &Tag) -> Result<(), Error> {
+        if !ct_eq(&self.finalize(), expected) {
+            return Err(Error::VerificationFailed);
+        }
+        Ok(())
+    }
+}
+
+/// Computes the Poly1305-AES authentication tag for a message.
+///
+/// # Arguments
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value, unique for each message.
+///
+/// # Returns
+/// Authentication tag as a 16-byte array.
+///
+/// # Example
+/// ```
+/// use aes_wasm::poly1305_aes::{mac, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let msg = b"hello";
+/// let tag = mac(msg, &key, nonce);
+/// ```
+pub fn mac(msg: impl AsRef<[u8]>, key: &Key, nonce: Nonce) -> Tag {
+    let mut st = Poly1305Aes::new(key, nonce);
+    st.update(msg);
+    st.finalize()
+}
+
+/// Verifies a Poly1305-AES authentication tag in constant time.
+///
+/// # Arguments
+/// * `tag` - The expected authentication tag.
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+/// * `nonce` - Nonce value.
+///
+/// # Returns
+/// `Ok(())` if the tag is valid, or `Err(Error)` if it is not.
+///
+/// # Example
+/// ```
+/// use aes_wasm::poly1305_aes::{mac, verify, Key, Nonce};
+/// let key = Key::default();
+/// let nonce = Nonce::default();
+/// let msg = b"hello";
+/// let tag = mac(msg, &key, nonce);
+/// assert!(verify(&tag, msg, &key, nonce).is_ok());
+/// ```
+pub fn verify(tag: &Tag, msg: impl AsRef<[u8]>, key: &Key, nonce: Nonce) -> Result<(), Error> {
+    let mut st = Poly1305Aes::new(key, nonce);
+    st.update(msg);
+    st.verify(tag)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn poly1305_aes() {
+        let key: Key = [
+            0xec, 0x07, 0x4c, 0x83, 0x55, 0x80, 0x74, 0x17, 0x01, 0x42, 0x5b, 0x62, 0x32, 0x35,
+            0xad, 0xd6, 0x85, 0x1f, 0xc4, 0x0c, 0x34, 0x67, 0xac, 0x0b, 0xe0, 0x5c, 0xc2, 0x04,
+            0x04, 0xf3, 0xf7, 0x00,
+        ];
+        let nonce: Nonce = [
+            0xfb, 0x44, 0x73, 0x50, 0xc4, 0xe8, 0x68, 0xc5, 0x2a, 0xc3, 0x27, 0x5c, 0xf9, 0xd4,
+            0x32, 0x7e,
+        ];
+        let msg = [0xf3, 0xf6];
+        let tag = mac(msg, &key, nonce);
+        assert_eq!(
+            tag,
+            [
+                0xf4, 0xc6, 0x33, 0xc3, 0x04, 0x4f, 0xc1, 0x45, 0xf8, 0x4f, 0x33, 0x5c, 0xb8, 0x19,
+                0x53, 0xde
+            ]
+        );
+        assert!(verify(&tag, msg, &key, nonce).is_ok());
+        assert_eq!(
+            verify(&tag, [0xf3, 0xf7], &key, nonce),
+            Err(Error::VerificationFailed)
+        );
+
+        let key: Key = core::array::from_fn(|i| i as u8);
+        let nonce: Nonce = core::array::from_fn(|i| i as u8);
+        let mut st = Poly1305Aes::new(&key, nonce);
+        for chunk in [0xffu8; 131].chunks(7) {
+            st.update(chunk);
+        }
+        assert_eq!(
+            st.finalize(),
+            [
+                0x69, 0xff, 0x19, 0xa1, 0x61, 0xcb, 0x76, 0x96, 0xab, 0x23, 0x73, 0x77, 0xa0, 0x92,
+                0x50, 0x83
+            ]
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! Self-describing sealed format for the AEAD ciphers of this crate.
+//!
+//! `seal` encrypts a message with a random nonce, and prefixes the ciphertext with a format
+//! version, an algorithm identifier and the nonce, so that `open` can decrypt it without
+//! any out-of-band information besides the key.
+//!
+//! ## Format
+//!
+//! | offset | length | content                           |
+//! | :----- | -----: | :-------------------------------- |
+//! | 0      |      1 | format version (`1`)              |
+//! | 1      |      1 | algorithm identifier (see below)  |
+//! | 2      |      n | nonce                             |
+//! | 2 + n  |        | ciphertext                        |
+//! | end    |      t | authentication tag                |
+//!
+//! The two header bytes are authenticated: the additional data given to the cipher
+//! is the header (`version || algorithm identifier`) followed by the caller's
+//! additional data.
+//!
+//! | id   | algorithm          | key | nonce | tag |
+//! | ---: | :----------------- | --: | ----: | --: |
+//! | 1    | AES-128-GCM        |  16 |    12 |  16 |
+//! | 2    | AES-256-GCM        |  32 |    12 |  16 |
+//! | 3    | AES-128-OCB        |  16 |    12 |  16 |
+//! | 4    | AES-256-OCB        |  32 |    12 |  16 |
+//! | 5    | AEGIS-128L         |  16 |    16 |  32 |
+//! | 6    | AEGIS-128X2        |  16 |    16 |  32 |
+//! | 7    | AEGIS-128X4        |  16 |    16 |  32 |
+//! | 8    | AEGIS-256          |  32 |    32 |  32 |
+//! | 9    | AEGIS-256X2        |  32 |    32 |  32 |
+//! | 10   | AEGIS-256X4        |  32 |    32 |  32 |
+//! | 11   | AES-128-CBC-HS-256 |  32 |    16 |  16 |
+//! | 12   | AES-256-CBC-HS-512 |  64 |    16 |  32 |
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(feature = "gcm")] {
+//! use aes_wasm::aes256gcm::generate_key;
+//! use aes_wasm::sealed::{open, seal};
+//! use aes_wasm::Algorithm;
+//! let key = generate_key();
+//! let sealed = seal(Algorithm::Aes256Gcm, b"hello", b"ad", &key).unwrap();
+//! let plaintext = open(&sealed, b"ad", &key).unwrap();
+//! assert_eq!(plaintext, b"hello");
+//! # }
+//! ```
+
+pub use crate::*;
+
+/// The format version written by `seal`.
+pub const VERSION: u8 = 1;
+/// The length of the header (version and algorithm identifier) in bytes.
+pub const HEADER_LEN: usize = 2;
+
+fn header_and_ad(alg: Algorithm, ad: &[u8]) -> Vec<u8> {
+    let mut header_and_ad = Vec::with_capacity(HEADER_LEN + ad.len());
+    header_and_ad.extend_from_slice(&[VERSION, alg.id()]);
+    header_and_ad.extend_from_slice(ad);
+    header_and_ad
+}
+
+/// Encrypts a message with a caller-provided nonce into the sealed format.
+///
+/// The nonce must never be reused with the same key. Prefer `seal`, that generates a
+/// random nonce, unless the nonce is managed by the application.
+///
+/// # Arguments
+/// * `alg` - The AEAD algorithm.
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD), not included in the output.
+/// * `key` - The secret key, whose length must match the algorithm.
+/// * `nonce` - The nonce, whose length must match the algorithm.
+///
+/// # Returns
+/// The sealed message, `Err(Error::InvalidKeyLength)` if the key length is invalid, or
+/// `Err(Error::InvalidNonceLength)` if the nonce length is invalid.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "gcm")] {
+/// use aes_wasm::sealed::seal_with_nonce;
+/// use aes_wasm::Algorithm;
+/// let (key, nonce) = ([0u8; 16], [0u8; 12]);
+/// let sealed = seal_with_nonce(Algorithm::Aes128Gcm, b"hello", b"ad", &key, &nonce).unwrap();
+/// # }
+/// ```
+pub fn seal_with_nonce(
+    alg: Algorithm,
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &[u8],
+    nonce: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let ad = header_and_ad(alg, ad.as_ref());
+    let ciphertext_and_tag = alg.encrypt(msg, &ad, key, nonce)?;
+    let mut sealed = Vec::with_capacity(HEADER_LEN + nonce.len() + ciphertext_and_tag.len());
+    sealed.extend_from_slice(&ad[..HEADER_LEN]);
+    sealed.extend_from_slice(nonce);
+    sealed.extend_from_slice(&ciphertext_and_tag);
+    Ok(sealed)
+}
+
+/// Encrypts a message with a random nonce into the sealed format.
+///
+/// # Arguments
+/// * `alg` - The AEAD algorithm.
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD), not included in the output.
+/// * `key` - The secret key, whose length must match the algorithm.
+///
+/// # Returns
+/// The sealed message, or `Err(Error::InvalidKeyLength)` if the key length is invalid.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "aegis")] {
+/// use aes_wasm::aegis128l::generate_key;
+/// use aes_wasm::sealed::seal;
+/// use aes_wasm::Algorithm;
+/// let key = generate_key();
+/// let sealed = seal(Algorithm::Aegis128L, b"hello", b"ad", &key).unwrap();
+/// # }
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn seal(
+    alg: Algorithm,
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+    key: &[u8],
+) -> Result<Vec<u8>, Error> {
+    seal_with_nonce(alg, msg, ad, key, &alg.generate_nonce())
+}
+
+/// Returns the algorithm used by a sealed message, without decrypting it.
+///
+/// # Returns
+/// The algorithm, or `Err(Error::InvalidParameters)` if the version or the algorithm
+/// identifier is not supported.
+pub fn algorithm(sealed: impl AsRef<[u8]>) -> Result<Algorithm, Error> {
+    match sealed.as_ref() {
+        [VERSION, id, ..] => Algorithm::from_id(*id),
+        _ => Err(Error::InvalidParameters),
+    }
+}
+
+/// Decrypts a sealed message, using the algorithm and nonce stored in its header.
+///
+/// # Arguments
+/// * `sealed` - The sealed message.
+/// * `ad` - Additional authenticated data (AAD), as given to `seal`.
+/// * `key` - The secret key.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidParameters)` if the header
+/// is not supported, `Err(Error::InvalidKeyLength)` if the key length doesn't match the
+/// algorithm, `Err(Error::InvalidLength)` if the input is too short, or
+/// `Err(Error::VerificationFailed)` if verification fails.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "aegis")] {
+/// use aes_wasm::aegis256::generate_key;
+/// use aes_wasm::sealed::{open, seal};
+/// use aes_wasm::Algorithm;
+/// let key = generate_key();
+/// let sealed = seal(Algorithm::Aegis256, b"hello", b"ad", &key).unwrap();
+/// let plaintext = open(&sealed, b"ad", &key).unwrap();
+/// # }
+/// ```
+pub fn open(sealed: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, key: &[u8]) -> Result<Vec<u8>, Error> {
+    let sealed = sealed.as_ref();
+    let alg = algorithm(sealed)?;
+    if key.len() != alg.key_len() {
+        return Err(Error::InvalidKeyLength);
+    }
+    if sealed.len() < HEADER_LEN + alg.nonce_len() + alg.tag_len() {
+        return Err(Error::InvalidLength);
+    }
+    let (nonce, ciphertext_and_tag) = sealed[HEADER_LEN..].split_at(alg.nonce_len());
+    let ad = header_and_ad(alg, ad.as_ref());
+    alg.decrypt(ciphertext_and_tag, ad, key, nonce)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sealed() {
+        let msg = b"hello world";
+        let ad = b"ad";
+        for alg in Algorithm::ALL {
+            let key = alg.generate_key();
+            let mut sealed = seal(alg, msg, ad, &key).unwrap();
+            assert_eq!(algorithm(&sealed), Ok(alg));
+            assert_eq!(open(&sealed, ad, &key).unwrap(), msg);
+            assert_eq!(open(&sealed, b"", &key), Err(Error::VerificationFailed));
+            assert_eq!(open(&sealed, ad, &key[1..]), Err(Error::InvalidKeyLength));
+            sealed[HEADER_LEN] ^= 1;
+            assert_eq!(open(&sealed, ad, &key), Err(Error::VerificationFailed));
+            assert_eq!(
+                open(&sealed[..HEADER_LEN + 1], ad, &key),
+                Err(Error::InvalidLength)
+            );
+        }
+        assert_eq!(open([VERSION, 0], b"", &[]), Err(Error::InvalidParameters));
+        assert_eq!(open([2, 1], b"", &[]), Err(Error::InvalidParameters));
+    }
+
+    #[cfg(any(feature = "gcm", feature = "cbc", feature = "aegis"))]
+    #[test]
+    fn sealed_vectors() {
+        // Cross-checked with Python's `cryptography` package.
+        let msg = b"hello world";
+        let ad = b"ad";
+        let key: Vec<u8> = (0..32).collect();
+        let nonce: Vec<u8> = (0..16).collect();
+
+        #[cfg(feature = "gcm")]
+        {
+            let sealed = seal_with_nonce(Algorithm::Aes128Gcm, msg, ad, &key[..16], &nonce[..12]);
+            assert_eq!(
+                sealed.unwrap(),
+                [
+                    0x01, 0x01, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a,
+                    0x0b, 0xfb, 0x09, 0xcb, 0xa2, 0x09, 0x3b, 0x80, 0x3b, 0x39, 0xbe, 0x05, 0xc2,
+                    0x96, 0xba, 0xa6, 0x37, 0xac, 0x24, 0xa8, 0x2a, 0x36, 0xa0, 0x4b, 0xfc, 0xda,
+                    0xca, 0xfa
+                ]
+            );
+
+            let sealed = seal_with_nonce(Algorithm::Aes256Gcm, msg, ad, &key, &nonce[..12]);
+            assert_eq!(
+                sealed.unwrap(),
+                [
+                    0x01, 0x02, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a,
+                    0x0b, 0x2f, 0x67, 0xba, 0x77, 0xaa, 0xc5, 0xb5, 0x74, 0xff, 0x2d, 0xf3, 0x2f,
+                    0xd1, 0xf4, 0x3e, 0x19, 0x9a, 0xdc, 0x91, 0xec, 0x4a, 0x43, 0xca, 0xb9, 0xac,
+                    0x8f, 0x4f
+                ]
+            );
+        }
+
+        #[cfg(feature = "cbc")]
+        {
+            let sealed = seal_with_nonce(Algorithm::Aes128CbcHs256, msg, ad, &key, &nonce);
+            assert_eq!(
+                sealed.unwrap(),
+                [
+                    0x01, 0x0b, 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a,
+                    0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x90, 0x84, 0xff, 0x1e, 0x75, 0xfd, 0xbf, 0x4b,
+                    0x84, 0x6a, 0x51, 0x9f, 0x26, 0xc4, 0x9a, 0x80, 0x1e, 0xf9, 0x49, 0x67, 0x69,
+                    0x9b, 0x99, 0x13, 0x98, 0x99, 0x3f, 0xe3, 0xc7, 0x97, 0x16, 0x7f
+                ]
+            );
+        }
+
+        #[cfg(feature = "aegis")]
+        {
+            // Cross-checked with the `aegis` crate.
+            let sealed =
+                seal_with_nonce(Algorithm::Aegis128L, msg, ad, &key[..16], &nonce).unwrap();
+            let (ciphertext, tag) = aegis::aegis128l::Aegis128L::<32>::new(
+                key[..16].try_into().unwrap(),
+                nonce[..].try_into().unwrap(),
+            )
+            .encrypt(msg, &[&[VERSION, 5][..], ad].concat());
+            assert_eq!(
+                sealed,
+                [&[VERSION, 5][..], &nonce, &ciphertext, &tag].concat()
+            );
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Secret streams on AEGIS-256, with the same push/pull model as libsodium's
+//! `crypto_secretstream`.
+//!
+//! A sender initializes a state with `init_push`, and sends the returned header to the
+//! receiver, followed by messages encrypted with `push`. The receiver initializes its state
+//! with `init_pull` and decrypts messages in order with `pull`.
+//!
+//! Every message is tagged:
+//!
+//! - `TAG_MESSAGE`: the most common tag, that doesn't add any information about the nature
+//!   of the message.
+//! - `TAG_PUSH`: indicates that the message marks the end of a set of messages, but not the
+//!   end of the stream.
+//! - `TAG_REKEY`: the key is ratcheted after this message.
+//! - `TAG_FINAL`: indicates that the message marks the end of the stream, and erases the
+//!   secret key used to encrypt the previous sequence.
+//!
+//! The API and the tags are the same as libsodium's, but the construction is based on
+//! AEGIS-256, so streams are not interchangeable with `crypto_secretstream_xchacha20poly1305`.
+//!
+//! ## Wire format
+//!
+//! The header is a random 32-byte value. The initial state is derived from the key and the
+//! header: the first 59 bytes of the AEGIS-256 keystream for the key and the header as a nonce
+//! are split into a 32-byte state key `k` and a 27-byte internal nonce `n`. The message
+//! counter `i` is set to `0`.
+//!
+//! Each message is encrypted with AEGIS-256 under the key `k`, the nonce
+//! `n || 0x00 || LE32(i)`, and the caller's additional data. The plaintext is the tag byte
+//! followed by the message, so a ciphertext is `ABYTES` (33) bytes longer than its message:
+//!
+//! | length | content                     |
+//! | -----: | :-------------------------- |
+//! |      1 | encrypted tag               |
+//! |      m | encrypted message           |
+//! |     32 | AEGIS-256 tag               |
+//!
+//! The counter is incremented after each message. If it wraps around, or if the tag includes
+//! `TAG_REKEY`, the state is rekeyed: `k || n` is encrypted with AEGIS-256 under the key `k` and
+//! the nonce `n || 0x01 || LE32(i)`, the result becomes the new `k || n`, and the counter is
+//! reset to `0`.
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::secretstream::{generate_key, init_pull, init_push, pull, push};
+//! use aes_wasm::secretstream::{TAG_FINAL, TAG_MESSAGE};
+//! let key = generate_key();
+//!
+//! let (mut state, header) = init_push(&key);
+//! let c1 = push(&mut state, b"hello", b"", TAG_MESSAGE);
+//! let c2 = push(&mut state, b"world", b"", TAG_FINAL);
+//!
+//! let mut state = init_pull(&header, &key);
+//! assert_eq!(pull(&mut state, &c1, b"").unwrap(), (b"hello".to_vec(), TAG_MESSAGE));
+//! assert_eq!(pull(&mut state, &c2, b"").unwrap(), (b"world".to_vec(), TAG_FINAL));
+//! ```
+
+pub use crate::*;
+
+/// The length of the key in bytes.
+///
+/// This constant is used for key array sizing.
+pub const KEYBYTES: usize = aegis256::KEY_LEN;
+/// The length of the stream header in bytes.
+///
+/// This constant is used for header array sizing.
+pub const HEADERBYTES: usize = aegis256::NONCE_LEN;
+/// The number of bytes added to each message.
+pub const ABYTES: usize = 1 + aegis256::TAG_LEN;
+
+/// Tag for a regular message.
+pub const TAG_MESSAGE: u8 = 0x00;
+/// Tag for the last message of a set of messages.
+pub const TAG_PUSH: u8 = 0x01;
+/// Tag forcing a rekey after the message.
+pub const TAG_REKEY: u8 = 0x02;
+/// Tag for the last message of the stream.
+pub const TAG_FINAL: u8 = TAG_PUSH | TAG_REKEY;
+
+const INONCE_LEN: usize = 27;
+
+/// Key type for secret streams (32 bytes).
+pub type Key = [u8; KEYBYTES];
+/// Stream header type (32 bytes).
+pub type Header = [u8; HEADERBYTES];
+
+/// State of a secret stream, for either pushing or pulling messages.
+#[derive(Clone)]
+pub struct State {
+    k: aegis256::Key,
+    inonce: [u8; INONCE_LEN],
+    counter: u32,
+}
+
+impl State {
+    fn new(key: &Key, header: &Header) -> Self {
+        let (keystream, _) =
+            aegis256::encrypt_detached([0u8; KEYBYTES + INONCE_LEN], b"", key, *header);
+        let mut state = State {
+            k: [0u8; KEYBYTES],
+            inonce: [0u8; INONCE_LEN],
+            counter: 0,
+        };
+        state.k.copy_from_slice(&keystream[..KEYBYTES]);
+        state.inonce.copy_from_slice(&keystream[KEYBYTES..]);
+        state
+    }
+
+    fn nonce(&self, domain: u8) -> aegis256::Nonce {
+        let mut nonce = aegis256::Nonce::default();
+        nonce[..INONCE_LEN].copy_from_slice(&self.inonce);
+        nonce[INONCE_LEN] = domain;
+        nonce[INONCE_LEN + 1..].copy_from_slice(&self.counter.to_le_bytes());
+        nonce
+    }
+
+    fn advance(&mut self, tag: u8) {
+        self.counter = self.counter.wrapping_add(1);
+        if tag & TAG_REKEY != 0 || self.counter == 0 {
+            rekey(self);
+        }
+    }
+}
+
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::secretstream::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    aegis256::generate_key()
+}
+
+/// Initializes a state to push messages, with a random header.
+///
+/// # Arguments
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// Tuple of the state and the header, that must be sent to the receiver.
+#[cfg(feature = "getrandom")]
+pub fn init_push(key: &Key) -> (State, Header) {
+    let mut header = Header::default();
+    random_bytes(&mut header);
+    (State::new(key, &header), header)
+}
+
+/// Encrypts a message, and advances the state.
+///
+/// # Arguments
+/// * `state` - The state returned by `init_push`.
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD), not included in the output.
+/// * `tag` - The message tag, usually `TAG_MESSAGE`.
+///
+/// # Returns
+/// The encrypted message, `ABYTES` bytes longer than `msg`.
+pub fn push(state: &mut State, msg: impl AsRef<[u8]>, ad: impl AsRef<[u8]>, tag: u8) -> Vec<u8> {
+    let msg = msg.as_ref();
+    let mut tag_and_msg = Vec::with_capacity(1 + msg.len());
+    tag_and_msg.push(tag);
+    tag_and_msg.extend_from_slice(msg);
+    let ciphertext = aegis256::encrypt(tag_and_msg, ad, &state.k, state.nonce(0));
+    state.advance(tag);
+    ciphertext
+}
+
+/// Initializes a state to pull messages.
+///
+/// # Arguments
+/// * `header` - The header returned by `init_push`.
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// The state.
+pub fn init_pull(header: &Header, key: &Key) -> State {
+    State::new(key, header)
+}
+
+/// Decrypts the next message, and advances the state.
+///
+/// The state is unchanged if verification fails. Applications must check for `TAG_FINAL`
+/// to detect the end of the stream.
+///
+/// # Arguments
+/// * `state` - The state returned by `init_pull`.
+/// * `ciphertext` - The encrypted message.
+/// * `ad` - Additional authenticated data (AAD), as given to `push`.
+///
+/// # Returns
+/// `Ok((msg, tag))` if verification succeeds, `Err(Error::InvalidLength)` if the message is
+/// shorter than `ABYTES`, or `Err(Error::VerificationFailed)` if the message is invalid,
+/// truncated, or out of order.
+pub fn pull(
+    state: &mut State,
+    ciphertext: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+) -> Result<(Vec<u8>, u8), Error> {
+    let ciphertext = ciphertext.as_ref();
+    if ciphertext.len() < ABYTES {
+        return Err(Error::InvalidLength);
+    }
+    let mut tag_and_msg = aegis256::decrypt(ciphertext, ad, &state.k, state.nonce(0))?;
+    let tag = tag_and_msg.remove(0);
+    state.advance(tag);
+    Ok((tag_and_msg, tag))
+}
+
+/// Explicitly rekeys a state.
+///
+/// Senders and receivers must rekey at the same point in the stream.
+pub fn rekey(state: &mut State) {
+    let mut k_and_inonce = [0u8; KEYBYTES + INONCE_LEN];
+    k_and_inonce[..KEYBYTES].copy_from_slice(&state.k);
+    k_and_inonce[KEYBYTES..].copy_from_slice(&state.inonce);
+    let (ciphertext, _) = aegis256::encrypt_detached(k_and_inonce, b"", &state.k, state.nonce(1));
+    state.k.copy_from_slice(&ciphertext[..KEYBYTES]);
+    state.inonce.copy_from_slice(&ciphertext[KEYBYTES..]);
+    state.counter = 0;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn secretstream() {
+        let key = generate_key();
+        let (mut push_state, header) = init_push(&key);
+        let c1 = push(&mut push_state, b"hello", b"ad", TAG_MESSAGE);
+        let c2 = push(&mut push_state, b"", b"", TAG_PUSH);
+        let c3 = push(&mut push_state, b"world", b"", TAG_REKEY);
+        rekey(&mut push_state);
+        let c4 = push(&mut push_state, b"!", b"", TAG_FINAL);
+        assert_eq!(c1.len(), 5 + ABYTES);
+
+        let mut state = init_pull(&header, &key);
+        assert_eq!(pull(&mut state, &c2, b""), Err(Error::VerificationFailed));
+        assert_eq!(pull(&mut state, &c1, b""), Err(Error::VerificationFailed));
+        assert_eq!(
+            pull(&mut state, &c1, b"ad").unwrap(),
+            (b"hello".to_vec(), TAG_MESSAGE)
+        );
+        assert_eq!(pull(&mut state, &c2, b"").unwrap(), (vec![], TAG_PUSH));
+        let mut tampered = c3.clone();
+        tampered[0] ^= TAG_REKEY;
+        assert_eq!(
+            pull(&mut state, &tampered, b""),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            pull(&mut state, &c3, b"").unwrap(),
+            (b"world".to_vec(), TAG_REKEY)
+        );
+        assert_eq!(pull(&mut state, &c4, b""), Err(Error::VerificationFailed));
+        rekey(&mut state);
+        assert_eq!(
+            pull(&mut state, &c4, b"").unwrap(),
+            (b"!".to_vec(), TAG_FINAL)
+        );
+        assert_eq!(
+            pull(&mut state, &c4[..ABYTES - 1], b""),
+            Err(Error::InvalidLength)
+        );
+
+        // Rekeying with TAG_REKEY is equivalent to rekeying after the message.
+        let (mut a, header) = init_push(&key);
+        let mut b = init_pull(&header, &key);
+        push(&mut a, b"", b"", TAG_REKEY);
+        b.advance(TAG_MESSAGE);
+        rekey(&mut b);
+        assert_eq!((a.k, a.inonce, a.counter), (b.k, b.inonce, b.counter));
+
+        // The state is automatically rekeyed when the counter wraps around.
+        a.counter = u32::MAX;
+        b.counter = u32::MAX;
+        let c = push(&mut a, b"wrap", b"", TAG_MESSAGE);
+        assert_eq!(a.counter, 0);
+        assert_ne!(a.k, b.k);
+        assert_eq!(
+            pull(&mut b, &c, b"").unwrap(),
+            (b"wrap".to_vec(), TAG_MESSAGE)
+        );
+        assert_eq!((a.k, a.inonce), (b.k, b.inonce));
+    }
+
+    #[test]
+    fn secretstream_format() {
+        // Cross-checked with the `aegis` crate.
+        let key: Key = core::array::from_fn(|i| i as u8);
+        let header: Header = core::array::from_fn(|i| 0x80 | i as u8);
+        let mut state = init_pull(&header, &key);
+
+        let (keystream, _) =
+            aegis::aegis256::Aegis256::<32>::new(&key, &header).encrypt(&[0u8; 59], &[]);
+        let mut nonce = [0u8; 32];
+        nonce[..27].copy_from_slice(&keystream[32..]);
+        nonce[28..].copy_from_slice(&1u32.to_le_bytes());
+        let k: [u8; 32] = keystream[..32].try_into().unwrap();
+        let mut second = init_pull(&header, &key);
+        second.counter = 1;
+        let (ciphertext, tag) =
+            aegis::aegis256::Aegis256::<32>::new(&k, &nonce).encrypt(b"\x03bye", b"ad");
+        let ciphertext = [&ciphertext[..], &tag].concat();
+        assert_eq!(push(&mut second, b"bye", b"ad", TAG_FINAL), ciphertext);
+
+        state.advance(TAG_MESSAGE);
+        assert_eq!(
+            pull(&mut state, &ciphertext, b"ad").unwrap(),
+            (b"bye".to_vec(), TAG_FINAL)
+        );
+    }
+}
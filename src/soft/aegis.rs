@@ -0,0 +1,282 @@
+//! AEGIS-128L, AEGIS-256 and their multi-lane AEGIS-128X and AEGIS-256X variants,
+//! with 256-bit tags.
+//!
+//! `D` is the number of lanes: AEGIS-128L and AEGIS-256 use a single lane.
+
+use super::aes::{aes_rounds, and, xor, xor_block, Block};
+use crate::ct_eq;
+
+const C0: Block = [
+    0x00, 0x01, 0x01, 0x02, 0x03, 0x05, 0x08, 0x0d, 0x15, 0x22, 0x37, 0x59, 0x90, 0xe9, 0x79, 0x62,
+];
+const C1: Block = [
+    0xdb, 0x3d, 0x18, 0x55, 0x6d, 0xc2, 0x2f, 0xf1, 0x20, 0x11, 0x31, 0x42, 0x73, 0xb5, 0x28, 0xdd,
+];
+
+pub(crate) const TAG_LEN: usize = 32;
+
+const MAX_LANES: usize = 4;
+
+fn context(lane: usize, lanes: usize) -> Block {
+    let mut ctx = Block::default();
+    ctx[0] = lane as u8;
+    ctx[1] = lanes as u8 - 1;
+    ctx
+}
+
+fn lengths(ad_len: usize, msg_len: usize) -> Block {
+    let mut u = Block::default();
+    u[..8].copy_from_slice(&(ad_len as u64 * 8).to_le_bytes());
+    u[8..].copy_from_slice(&(msg_len as u64 * 8).to_le_bytes());
+    u
+}
+
+/// Updates `S` blocks of `D` lanes: `S'[i] = AESRound(S[i - 1], S[i])`, with the message
+/// blocks absorbed into the given state blocks.
+fn update<const S: usize, const D: usize>(s: &mut [[Block; D]; S], msg: &[(usize, &[Block; D])]) {
+    let mut t = [Block::default(); 8 * MAX_LANES];
+    let t = &mut t[..S * D];
+    for i in 0..S {
+        t[i * D..(i + 1) * D].copy_from_slice(&s[(i + S - 1) % S]);
+    }
+    for &(i, m) in msg {
+        s[i].iter_mut().zip(m).for_each(|(b, m)| xor_block(b, m));
+    }
+    aes_rounds(t, s.iter().flatten().copied());
+    for (i, lanes) in s.iter_mut().enumerate() {
+        lanes.copy_from_slice(&t[i * D..(i + 1) * D]);
+    }
+}
+
+fn split<const D: usize>(bytes: &[u8]) -> [Block; D] {
+    core::array::from_fn(|j| bytes[16 * j..16 * (j + 1)].try_into().unwrap())
+}
+
+/// Operations shared by all the AEGIS variants.
+trait Aegis {
+    const RATE: usize;
+    fn absorb(&mut self, block: &[u8]);
+    fn apply_keystream(&self, block: &mut [u8]);
+    fn finalize(&mut self, ad_len: usize, msg_len: usize) -> [u8; TAG_LEN];
+}
+
+/// AEGIS-128L and AEGIS-128X state.
+struct Aegis128X<const D: usize> {
+    s: [[Block; D]; 8],
+}
+
+impl<const D: usize> Aegis128X<D> {
+    fn new(key: &Block, nonce: &Block) -> Self {
+        let kn = xor(key, nonce);
+        let (kc0, kc1) = (xor(key, &C0), xor(key, &C1));
+        let mut st = Aegis128X {
+            s: [kn, C1, C0, C1, kn, kc0, kc1, kc0].map(|b| [b; D]),
+        };
+        let (n, k) = ([*nonce; D], [*key; D]);
+        for _ in 0..10 {
+            if D > 1 {
+                for j in 0..D {
+                    let ctx = context(j, D);
+                    xor_block(&mut st.s[3][j], &ctx);
+                    xor_block(&mut st.s[7][j], &ctx);
+                }
+            }
+            st.update(&n, &k);
+        }
+        st
+    }
+
+    fn update(&mut self, m0: &[Block; D], m1: &[Block; D]) {
+        update(&mut self.s, &[(0, m0), (4, m1)]);
+    }
+}
+
+impl<const D: usize> Aegis for Aegis128X<D> {
+    const RATE: usize = 32 * D;
+
+    fn absorb(&mut self, block: &[u8]) {
+        self.update(&split(&block[..16 * D]), &split(&block[16 * D..]));
+    }
+
+    fn apply_keystream(&self, block: &mut [u8]) {
+        let s = &self.s;
+        let z0: [Block; D] =
+            core::array::from_fn(|j| xor(&xor(&s[6][j], &s[1][j]), &and(&s[2][j], &s[3][j])));
+        let z1: [Block; D] =
+            core::array::from_fn(|j| xor(&xor(&s[2][j], &s[5][j]), &and(&s[6][j], &s[7][j])));
+        let z = z0.iter().chain(z1.iter()).flatten();
+        block.iter_mut().zip(z).for_each(|(b, z)| *b ^= z);
+    }
+
+    fn finalize(&mut self, ad_len: usize, msg_len: usize) -> [u8; TAG_LEN] {
+        let u = lengths(ad_len, msg_len);
+        let t: [Block; D] = core::array::from_fn(|j| xor(&self.s[2][j], &u));
+        for _ in 0..7 {
+            self.update(&t, &t);
+        }
+        let mut tag = [0u8; TAG_LEN];
+        let (t0, t1) = tag.split_at_mut(16);
+        for j in 0..D {
+            for i in 0..4 {
+                xor_block(t0.try_into().unwrap(), &self.s[i][j]);
+                xor_block(t1.try_into().unwrap(), &self.s[i + 4][j]);
+            }
+        }
+        tag
+    }
+}
+
+/// AEGIS-256 and AEGIS-256X state.
+struct Aegis256X<const D: usize> {
+    s: [[Block; D]; 6],
+}
+
+impl<const D: usize> Aegis256X<D> {
+    fn new(key: &[u8; 32], nonce: &[u8; 32]) -> Self {
+        let (k0, k1): (Block, Block) =
+            (key[..16].try_into().unwrap(), key[16..].try_into().unwrap());
+        let (n0, n1): (Block, Block) = (
+            nonce[..16].try_into().unwrap(),
+            nonce[16..].try_into().unwrap(),
+        );
+        let (k0n0, k1n1) = (xor(&k0, &n0), xor(&k1, &n1));
+        let mut st = Aegis256X {
+            s: [k0n0, k1n1, C1, C0, xor(&k0, &C0), xor(&k1, &C1)].map(|b| [b; D]),
+        };
+        for _ in 0..4 {
+            for m in [k0, k1, k0n0, k1n1] {
+                if D > 1 {
+                    for j in 0..D {
+                        let ctx = context(j, D);
+                        xor_block(&mut st.s[3][j], &ctx);
+                        xor_block(&mut st.s[5][j], &ctx);
+                    }
+                }
+                st.update(&[m; D]);
+            }
+        }
+        st
+    }
+
+    fn update(&mut self, m: &[Block; D]) {
+        update(&mut self.s, &[(0, m)]);
+    }
+}
+
+impl<const D: usize> Aegis for Aegis256X<D> {
+    const RATE: usize = 16 * D;
+
+    fn absorb(&mut self, block: &[u8]) {
+        self.update(&split(block));
+    }
+
+    fn apply_keystream(&self, block: &mut [u8]) {
+        let s = &self.s;
+        let z: [Block; D] = core::array::from_fn(|j| {
+            xor(
+                &xor(&xor(&s[1][j], &s[4][j]), &s[5][j]),
+                &and(&s[2][j], &s[3][j]),
+            )
+        });
+        block
+            .iter_mut()
+            .zip(z.iter().flatten())
+            .for_each(|(b, z)| *b ^= z);
+    }
+
+    fn finalize(&mut self, ad_len: usize, msg_len: usize) -> [u8; TAG_LEN] {
+        let u = lengths(ad_len, msg_len);
+        let t: [Block; D] = core::array::from_fn(|j| xor(&self.s[3][j], &u));
+        for _ in 0..7 {
+            self.update(&t);
+        }
+        let mut tag = [0u8; TAG_LEN];
+        let (t0, t1) = tag.split_at_mut(16);
+        for j in 0..D {
+            for i in 0..3 {
+                xor_block(t0.try_into().unwrap(), &self.s[i][j]);
+                xor_block(t1.try_into().unwrap(), &self.s[i + 3][j]);
+            }
+        }
+        tag
+    }
+}
+
+fn absorb_ad<A: Aegis>(st: &mut A, ad: &[u8]) {
+    let mut chunks = ad.chunks_exact(A::RATE);
+    for block in &mut chunks {
+        st.absorb(block);
+    }
+    let rest = chunks.remainder();
+    if !rest.is_empty() {
+        let mut padded = [0u8; 32 * MAX_LANES];
+        padded[..rest.len()].copy_from_slice(rest);
+        st.absorb(&padded[..A::RATE]);
+    }
+}
+
+fn encrypt<A: Aegis>(mut st: A, buf: &mut [u8], ad: &[u8]) -> [u8; TAG_LEN] {
+    absorb_ad(&mut st, ad);
+    for block in buf.chunks_mut(A::RATE) {
+        let mut padded = [0u8; 32 * MAX_LANES];
+        let padded = &mut padded[..A::RATE];
+        padded[..block.len()].copy_from_slice(block);
+        st.apply_keystream(block);
+        st.absorb(padded);
+    }
+    st.finalize(ad.len(), buf.len())
+}
+
+fn decrypt<A: Aegis>(mut st: A, buf: &mut [u8], tag: &[u8; TAG_LEN], ad: &[u8]) -> bool {
+    absorb_ad(&mut st, ad);
+    for block in buf.chunks_mut(A::RATE) {
+        st.apply_keystream(block);
+        let mut padded = [0u8; 32 * MAX_LANES];
+        let padded = &mut padded[..A::RATE];
+        padded[..block.len()].copy_from_slice(block);
+        st.absorb(padded);
+    }
+    let valid = ct_eq(&st.finalize(ad.len(), buf.len()), tag);
+    if !valid {
+        buf.fill(0);
+    }
+    valid
+}
+
+pub(crate) fn aegis128x_encrypt<const D: usize>(
+    buf: &mut [u8],
+    ad: &[u8],
+    nonce: &Block,
+    key: &Block,
+) -> [u8; TAG_LEN] {
+    encrypt(Aegis128X::<D>::new(key, nonce), buf, ad)
+}
+
+pub(crate) fn aegis128x_decrypt<const D: usize>(
+    buf: &mut [u8],
+    tag: &[u8; TAG_LEN],
+    ad: &[u8],
+    nonce: &Block,
+    key: &Block,
+) -> bool {
+    decrypt(Aegis128X::<D>::new(key, nonce), buf, tag, ad)
+}
+
+pub(crate) fn aegis256x_encrypt<const D: usize>(
+    buf: &mut [u8],
+    ad: &[u8],
+    nonce: &[u8; 32],
+    key: &[u8; 32],
+) -> [u8; TAG_LEN] {
+    encrypt(Aegis256X::<D>::new(key, nonce), buf, ad)
+}
+
+pub(crate) fn aegis256x_decrypt<const D: usize>(
+    buf: &mut [u8],
+    tag: &[u8; TAG_LEN],
+    ad: &[u8],
+    nonce: &[u8; 32],
+    key: &[u8; 32],
+) -> bool {
+    decrypt(Aegis256X::<D>::new(key, nonce), buf, tag, ad)
+}
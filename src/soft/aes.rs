@@ -0,0 +1,366 @@
+//! Constant-time AES block cipher and round function.
+//!
+//! The S-box is computed as an inversion in GF(2^8) followed by the affine transform, on
+//! bitsliced bytes, so that no table lookups depend on secret data.
+//!
+//! When compiling for WebAssembly with the `simd128` target feature, the bitslicing and the
+//! block operations use SIMD instructions.
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+use core::arch::wasm32::*;
+
+pub(crate) type Block = [u8; 16];
+
+/// Bitsliced representation of up to 64 bytes: plane `i` holds bit `i` of every byte.
+type Planes = [u64; 8];
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+fn pack(bytes: &[u8]) -> Planes {
+    let mut p = Planes::default();
+    for (j, &byte) in bytes.iter().enumerate() {
+        for (i, plane) in p.iter_mut().enumerate() {
+            *plane |= (((byte >> i) & 1) as u64) << j;
+        }
+    }
+    p
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+fn unpack(p: &Planes, bytes: &mut [u8]) {
+    for (j, byte) in bytes.iter_mut().enumerate() {
+        *byte = p
+            .iter()
+            .enumerate()
+            .fold(0, |b, (i, plane)| b | ((((plane >> j) & 1) as u8) << i));
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn load(block: &Block) -> v128 {
+    // SAFETY: `v128_load` doesn't require alignment, and a block is 16 bytes long.
+    unsafe { v128_load(block.as_ptr().cast()) }
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn store(v: v128) -> Block {
+    let mut block = Block::default();
+    // SAFETY: `v128_store` doesn't require alignment, and a block is 16 bytes long.
+    unsafe { v128_store(block.as_mut_ptr().cast(), v) };
+    block
+}
+
+/// Collects bit `i` of 16 bytes at a time by shifting it to the sign bit.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn pack(bytes: &[u8]) -> Planes {
+    let mut p = Planes::default();
+    for (g, group) in bytes.chunks(16).enumerate() {
+        let mut block = Block::default();
+        block[..group.len()].copy_from_slice(group);
+        let v = load(&block);
+        for (i, plane) in p.iter_mut().enumerate() {
+            let bits = i8x16_bitmask(i8x16_shl(v, 7 - i as u32));
+            *plane |= (bits as u64) << (16 * g);
+        }
+    }
+    p
+}
+
+/// Spreads 16 bits of every plane to 16 bytes at a time.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+fn unpack(p: &Planes, bytes: &mut [u8]) {
+    let select = u8x16(0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1);
+    let weights = u8x16(1, 2, 4, 8, 16, 32, 64, 128, 1, 2, 4, 8, 16, 32, 64, 128);
+    for (g, group) in bytes.chunks_mut(16).enumerate() {
+        let mut v = u8x16_splat(0);
+        for (i, plane) in p.iter().enumerate() {
+            let bits = i8x16_swizzle(u16x8_splat((plane >> (16 * g)) as u16), select);
+            let set = i8x16_ne(v128_and(bits, weights), u8x16_splat(0));
+            v = v128_or(v, v128_and(set, u8x16_splat(1 << i)));
+        }
+        group.copy_from_slice(&store(v)[..group.len()]);
+    }
+}
+
+/// Reduces a product modulo x^8 + x^4 + x^3 + x + 1.
+fn reduce(mut c: [u64; 15]) -> Planes {
+    for k in (8..15).rev() {
+        c[k - 4] ^= c[k];
+        c[k - 5] ^= c[k];
+        c[k - 7] ^= c[k];
+        c[k - 8] ^= c[k];
+    }
+    c[..8].try_into().unwrap()
+}
+
+fn gf_mul(a: &Planes, b: &Planes) -> Planes {
+    let mut c = [0u64; 15];
+    for i in 0..8 {
+        for j in 0..8 {
+            c[i + j] ^= a[i] & b[j];
+        }
+    }
+    reduce(c)
+}
+
+fn gf_square(a: &Planes) -> Planes {
+    let mut c = [0u64; 15];
+    for i in 0..8 {
+        c[2 * i] = a[i];
+    }
+    reduce(c)
+}
+
+/// Computes x^254, which is the inverse of x, or 0 if x is 0.
+fn gf_inv(x: &Planes) -> Planes {
+    let x2 = gf_square(x);
+    let x3 = gf_mul(&x2, x);
+    let x12 = gf_square(&gf_square(&x3));
+    let x15 = gf_mul(&x12, &x3);
+    let x240 = gf_square(&gf_square(&gf_square(&gf_square(&x15))));
+    let x252 = gf_mul(&x240, &x12);
+    gf_mul(&x252, &x2)
+}
+
+fn affine(b: &Planes) -> Planes {
+    core::array::from_fn(|i| {
+        let s = b[i] ^ b[(i + 4) % 8] ^ b[(i + 5) % 8] ^ b[(i + 6) % 8] ^ b[(i + 7) % 8];
+        if (0x63 >> i) & 1 == 1 {
+            !s
+        } else {
+            s
+        }
+    })
+}
+
+fn inv_affine(s: &Planes) -> Planes {
+    core::array::from_fn(|i| {
+        let b = s[(i + 7) % 8] ^ s[(i + 5) % 8] ^ s[(i + 2) % 8];
+        if (0x05 >> i) & 1 == 1 {
+            !b
+        } else {
+            b
+        }
+    })
+}
+
+/// Applies the AES S-box to every byte.
+pub(crate) fn sub_bytes(bytes: &mut [u8]) {
+    for chunk in bytes.chunks_mut(64) {
+        let p = affine(&gf_inv(&pack(chunk)));
+        unpack(&p, chunk);
+    }
+}
+
+/// Applies the inverse AES S-box to every byte.
+fn inv_sub_bytes(bytes: &mut [u8]) {
+    for chunk in bytes.chunks_mut(64) {
+        let p = gf_inv(&inv_affine(&pack(chunk)));
+        unpack(&p, chunk);
+    }
+}
+
+fn xtime(a: u8) -> u8 {
+    (a << 1) ^ ((a >> 7) * 0x1b)
+}
+
+fn shift_rows(b: &mut Block) {
+    let s = *b;
+    for c in 0..4 {
+        for r in 0..4 {
+            b[r + 4 * c] = s[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+fn inv_shift_rows(b: &mut Block) {
+    let s = *b;
+    for c in 0..4 {
+        for r in 0..4 {
+            b[r + 4 * ((c + r) % 4)] = s[r + 4 * c];
+        }
+    }
+}
+
+fn mix_columns(b: &mut Block) {
+    for col in b.chunks_mut(4) {
+        let (a0, a1, a2, a3) = (col[0], col[1], col[2], col[3]);
+        let t = a0 ^ a1 ^ a2 ^ a3;
+        col[0] ^= t ^ xtime(a0 ^ a1);
+        col[1] ^= t ^ xtime(a1 ^ a2);
+        col[2] ^= t ^ xtime(a2 ^ a3);
+        col[3] ^= t ^ xtime(a3 ^ a0);
+    }
+}
+
+fn inv_mix_columns(b: &mut Block) {
+    for col in b.chunks_mut(4) {
+        let u = xtime(xtime(col[0] ^ col[2]));
+        let v = xtime(xtime(col[1] ^ col[3]));
+        col[0] ^= u;
+        col[1] ^= v;
+        col[2] ^= u;
+        col[3] ^= v;
+    }
+    mix_columns(b);
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+pub(crate) fn xor(a: &Block, b: &Block) -> Block {
+    core::array::from_fn(|i| a[i] ^ b[i])
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+pub(crate) fn and(a: &Block, b: &Block) -> Block {
+    core::array::from_fn(|i| a[i] & b[i])
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub(crate) fn xor(a: &Block, b: &Block) -> Block {
+    store(v128_xor(load(a), load(b)))
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+pub(crate) fn and(a: &Block, b: &Block) -> Block {
+    store(v128_and(load(a), load(b)))
+}
+
+pub(crate) fn xor_block(a: &mut Block, b: &Block) {
+    *a = xor(a, b);
+}
+
+/// Computes `MixColumns(ShiftRows(SubBytes(block))) ^ round_key` for every block at once.
+pub(crate) fn aes_rounds(blocks: &mut [Block], round_keys: impl Iterator<Item = Block>) {
+    sub_bytes(blocks.as_flattened_mut());
+    for (b, rk) in blocks.iter_mut().zip(round_keys) {
+        shift_rows(b);
+        mix_columns(b);
+        xor_block(b, &rk);
+    }
+}
+
+/// The number of blocks processed in parallel, so that a batch fills the bitsliced planes.
+pub(crate) const PARALLEL_BLOCKS: usize = 4;
+
+/// Expanded AES-128 or AES-256 key.
+#[derive(Clone)]
+pub(crate) struct Aes {
+    round_keys: [Block; 15],
+    rounds: usize,
+}
+
+impl Aes {
+    /// Expands a 16-byte (AES-128) or 32-byte (AES-256) key.
+    pub(crate) fn new(key: &[u8]) -> Self {
+        let nk = key.len() / 4;
+        debug_assert!(nk == 4 || nk == 8);
+        let rounds = nk + 6;
+        let mut w = [[0u8; 4]; 60];
+        for (i, word) in key.chunks(4).enumerate() {
+            w[i].copy_from_slice(word);
+        }
+        let mut rcon = 1u8;
+        for i in nk..4 * (rounds + 1) {
+            let mut t = w[i - 1];
+            if i % nk == 0 {
+                t.rotate_left(1);
+                sub_bytes(&mut t);
+                t[0] ^= rcon;
+                rcon = xtime(rcon);
+            } else if nk > 6 && i % nk == 4 {
+                sub_bytes(&mut t);
+            }
+            w[i] = core::array::from_fn(|j| w[i - nk][j] ^ t[j]);
+        }
+        let mut round_keys = [Block::default(); 15];
+        for (rk, words) in round_keys.iter_mut().zip(w.chunks(4)) {
+            rk.copy_from_slice(words.as_flattened());
+        }
+        Aes { round_keys, rounds }
+    }
+
+    /// Encrypts blocks in place.
+    pub(crate) fn encrypt_blocks(&self, blocks: &mut [Block]) {
+        for batch in blocks.chunks_mut(PARALLEL_BLOCKS) {
+            batch
+                .iter_mut()
+                .for_each(|b| xor_block(b, &self.round_keys[0]));
+            for round in 1..self.rounds {
+                aes_rounds(batch, core::iter::repeat(self.round_keys[round]));
+            }
+            sub_bytes(batch.as_flattened_mut());
+            for b in batch.iter_mut() {
+                shift_rows(b);
+                xor_block(b, &self.round_keys[self.rounds]);
+            }
+        }
+    }
+
+    /// Decrypts blocks in place.
+    pub(crate) fn decrypt_blocks(&self, blocks: &mut [Block]) {
+        for batch in blocks.chunks_mut(PARALLEL_BLOCKS) {
+            batch
+                .iter_mut()
+                .for_each(|b| xor_block(b, &self.round_keys[self.rounds]));
+            for round in (1..self.rounds).rev() {
+                batch.iter_mut().for_each(inv_shift_rows);
+                inv_sub_bytes(batch.as_flattened_mut());
+                for b in batch.iter_mut() {
+                    xor_block(b, &self.round_keys[round]);
+                    inv_mix_columns(b);
+                }
+            }
+            batch.iter_mut().for_each(inv_shift_rows);
+            inv_sub_bytes(batch.as_flattened_mut());
+            batch
+                .iter_mut()
+                .for_each(|b| xor_block(b, &self.round_keys[0]));
+        }
+    }
+
+    /// Encrypts a single block in place.
+    pub(crate) fn encrypt_block(&self, block: &mut Block) {
+        self.encrypt_blocks(core::slice::from_mut(block));
+    }
+
+    /// Decrypts a single block in place.
+    pub(crate) fn decrypt_block(&self, block: &mut Block) {
+        self.decrypt_blocks(core::slice::from_mut(block));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hex(s: &str) -> Block {
+        core::array::from_fn(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+    }
+
+    #[test]
+    fn sbox() {
+        let mut bytes: [u8; 256] = core::array::from_fn(|i| i as u8);
+        sub_bytes(&mut bytes);
+        assert_eq!(bytes[..4], [0x63, 0x7c, 0x77, 0x7b]);
+        assert_eq!(bytes[0x53], 0xed);
+        assert_eq!(bytes[0xff], 0x16);
+        inv_sub_bytes(&mut bytes);
+        assert!(bytes.iter().enumerate().all(|(i, &b)| b == i as u8));
+    }
+
+    #[test]
+    fn fips197() {
+        let pt = hex("00112233445566778899aabbccddeeff");
+        let key: [u8; 32] = core::array::from_fn(|i| i as u8);
+        for (key, ct) in [
+            (&key[..16], "69c4e0d86a7b0430d8cdb78070b4c55a"),
+            (&key[..], "8ea2b7ca516745bfeafc49904b496089"),
+        ] {
+            let aes = Aes::new(key);
+            let mut blocks = [pt; 5];
+            aes.encrypt_blocks(&mut blocks);
+            assert!(blocks.iter().all(|b| *b == hex(ct)));
+            aes.decrypt_blocks(&mut blocks);
+            assert!(blocks.iter().all(|b| *b == pt));
+        }
+    }
+}
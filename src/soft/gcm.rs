@@ -0,0 +1,101 @@
+//! AES-GCM with 96-bit nonces and 128-bit tags.
+
+use super::aes::{xor_block, Aes, Block, PARALLEL_BLOCKS};
+use crate::ct_eq;
+
+pub(crate) const TAG_LEN: usize = 16;
+
+/// Multiplies two elements of GF(2^128) in the bit order used by GCM, in constant time.
+fn gf_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0;
+    let mut v = y;
+    for i in (0..128).rev() {
+        z ^= v & ((x >> i) & 1).wrapping_neg();
+        v = (v >> 1) ^ ((0xe1 << 120) & (v & 1).wrapping_neg());
+    }
+    z
+}
+
+struct Ghash {
+    h: u128,
+    acc: u128,
+}
+
+impl Ghash {
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = Block::default();
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.acc = gf_mul(self.acc ^ u128::from_be_bytes(block), self.h);
+        }
+    }
+
+    fn finalize(mut self, ad_len: usize, msg_len: usize) -> Block {
+        let lengths = ((ad_len as u128 * 8) << 64) | (msg_len as u128 * 8);
+        self.acc = gf_mul(self.acc ^ lengths, self.h);
+        self.acc.to_be_bytes()
+    }
+}
+
+/// Applies the CTR keystream, with a 32-bit counter, starting from counter block 2.
+fn ctr32(aes: &Aes, j0: &Block, buf: &mut [u8]) {
+    let counter = u32::from_be_bytes(j0[12..].try_into().unwrap());
+    for (i, chunk) in buf.chunks_mut(16 * PARALLEL_BLOCKS).enumerate() {
+        let mut ks = [*j0; PARALLEL_BLOCKS];
+        for (k, b) in ks.iter_mut().enumerate() {
+            let n = counter.wrapping_add((i * PARALLEL_BLOCKS + k + 1) as u32);
+            b[12..].copy_from_slice(&n.to_be_bytes());
+        }
+        aes.encrypt_blocks(&mut ks);
+        chunk
+            .iter_mut()
+            .zip(ks.as_flattened())
+            .for_each(|(b, k)| *b ^= k);
+    }
+}
+
+fn init(key: &[u8], nonce: &[u8; 12]) -> (Aes, Ghash, Block) {
+    let aes = Aes::new(key);
+    let mut h = Block::default();
+    aes.encrypt_block(&mut h);
+    let mut j0 = Block::default();
+    j0[..12].copy_from_slice(nonce);
+    j0[15] = 1;
+    let ghash = Ghash {
+        h: u128::from_be_bytes(h),
+        acc: 0,
+    };
+    (aes, ghash, j0)
+}
+
+fn tag(aes: &Aes, ghash: Ghash, j0: &Block, ad_len: usize, msg_len: usize) -> Block {
+    let mut tag = *j0;
+    aes.encrypt_block(&mut tag);
+    xor_block(&mut tag, &ghash.finalize(ad_len, msg_len));
+    tag
+}
+
+pub(crate) fn encrypt(buf: &mut [u8], ad: &[u8], nonce: &[u8; 12], key: &[u8]) -> Block {
+    let (aes, mut ghash, j0) = init(key, nonce);
+    ctr32(&aes, &j0, buf);
+    ghash.update_padded(ad);
+    ghash.update_padded(buf);
+    tag(&aes, ghash, &j0, ad.len(), buf.len())
+}
+
+pub(crate) fn decrypt(
+    buf: &mut [u8],
+    expected_tag: &Block,
+    ad: &[u8],
+    nonce: &[u8; 12],
+    key: &[u8],
+) -> bool {
+    let (aes, mut ghash, j0) = init(key, nonce);
+    ghash.update_padded(ad);
+    ghash.update_padded(buf);
+    if !ct_eq(&tag(&aes, ghash, &j0, ad.len(), buf.len()), expected_tag) {
+        return false;
+    }
+    ctr32(&aes, &j0, buf);
+    true
+}
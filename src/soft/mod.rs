@@ -0,0 +1,760 @@
+//! Pure-Rust implementation of the functions exported by the Zig library.
+//!
+//! This backend is used on targets the prebuilt `wasm-libs/libaes.a` archive cannot be linked
+//! to, for in-place AES-OCB encryption, for AES-OCB with associated data, which the prebuilt
+//! archive doesn't compute correctly, for decrypting messages produced by that code, and for
+//! single-block encryption with an expanded key, which it doesn't export. Every
+//! function has the same signature and semantics as its Zig counterpart, so that modules can
+//! use either backend interchangeably. Input and output buffers may alias.
+//!
+//! The implementation doesn't use lookup tables, and runs in constant time.
+
+#![allow(clippy::too_many_arguments)]
+// Which parts of the shared code are used depends on the enabled algorithms. With the Zig
+// backend, only AES-OCB and the block cipher used by the MACs, the KDFs and the DRBG are used.
+#![cfg_attr(
+    any(
+        zig_backend,
+        not(all(
+            feature = "aegis",
+            feature = "gcm",
+            feature = "ocb",
+            feature = "cbc",
+            feature = "ctr",
+            feature = "cmac"
+        ))
+    ),
+    allow(dead_code, unused_imports, unused_macros)
+)]
+
+#[cfg(feature = "aegis")]
+mod aegis;
+mod aes;
+#[cfg(feature = "gcm")]
+mod gcm;
+mod modes;
+#[cfg(feature = "ocb")]
+mod ocb;
+
+pub(crate) use aes::Aes;
+use aes::Block;
+use core::slice;
+#[cfg(feature = "ocb")]
+pub(crate) use ocb::decrypt_legacy as ocb_decrypt_legacy;
+
+/// Copies the input to the output buffer, which may be the same, and returns the output.
+unsafe fn in_out<'a>(
+    out: *mut u8,
+    out_len: usize,
+    input: *const u8,
+    in_len: usize,
+) -> &'a mut [u8] {
+    debug_assert_eq!(out_len, in_len);
+    core::ptr::copy(input, out, in_len.min(out_len));
+    slice::from_raw_parts_mut(out, out_len)
+}
+
+unsafe fn array<'a, const N: usize>(p: *const u8) -> &'a [u8; N] {
+    &*(p as *const [u8; N])
+}
+
+macro_rules! aead {
+    (
+        $encrypt:ident, $decrypt:ident, $key_len:literal, $nonce_len:literal, $tag_len:expr,
+        $enc:expr, $dec:expr
+    ) => {
+        pub(crate) unsafe fn $encrypt(
+            c: *mut u8,
+            c_len: usize,
+            tag: *mut u8,
+            m: *const u8,
+            m_len: usize,
+            ad: *const u8,
+            ad_len: usize,
+            nonce: *const u8,
+            k: *const u8,
+        ) -> i32 {
+            let buf = in_out(c, c_len, m, m_len);
+            let ad = slice::from_raw_parts(ad, ad_len);
+            let (nonce, k) = (array::<$nonce_len>(nonce), array::<$key_len>(k));
+            *(tag as *mut [u8; $tag_len]) = $enc(buf, ad, nonce, k);
+            0
+        }
+
+        pub(crate) unsafe fn $decrypt(
+            m: *mut u8,
+            m_len: usize,
+            c: *const u8,
+            c_len: usize,
+            tag: *const u8,
+            ad: *const u8,
+            ad_len: usize,
+            nonce: *const u8,
+            k: *const u8,
+        ) -> i32 {
+            let buf = in_out(m, m_len, c, c_len);
+            let ad = slice::from_raw_parts(ad, ad_len);
+            let (nonce, k) = (array::<$nonce_len>(nonce), array::<$key_len>(k));
+            if !$dec(buf, array::<{ $tag_len }>(tag), ad, nonce, k) {
+                buf.fill(0);
+                return -1;
+            }
+            0
+        }
+    };
+}
+
+#[cfg(feature = "gcm")]
+aead!(
+    aes128gcm_encrypt,
+    aes128gcm_decrypt,
+    16,
+    12,
+    gcm::TAG_LEN,
+    gcm::encrypt,
+    gcm::decrypt
+);
+#[cfg(feature = "gcm")]
+aead!(
+    aes256gcm_encrypt,
+    aes256gcm_decrypt,
+    32,
+    12,
+    gcm::TAG_LEN,
+    gcm::encrypt,
+    gcm::decrypt
+);
+#[cfg(feature = "ocb")]
+aead!(
+    aes128ocb_encrypt,
+    aes128ocb_decrypt,
+    16,
+    12,
+    ocb::TAG_LEN,
+    ocb::encrypt,
+    ocb::decrypt
+);
+#[cfg(feature = "ocb")]
+aead!(
+    aes256ocb_encrypt,
+    aes256ocb_decrypt,
+    32,
+    12,
+    ocb::TAG_LEN,
+    ocb::encrypt,
+    ocb::decrypt
+);
+#[cfg(feature = "aegis")]
+aead!(
+    _aegis128l_encrypt,
+    _aegis128l_decrypt,
+    16,
+    16,
+    aegis::TAG_LEN,
+    aegis::aegis128x_encrypt::<1>,
+    aegis::aegis128x_decrypt::<1>
+);
+#[cfg(feature = "aegis")]
+aead!(
+    _aegis128x2_encrypt,
+    _aegis128x2_decrypt,
+    16,
+    16,
+    aegis::TAG_LEN,
+    aegis::aegis128x_encrypt::<2>,
+    aegis::aegis128x_decrypt::<2>
+);
+#[cfg(feature = "aegis")]
+aead!(
+    _aegis128x4_encrypt,
+    _aegis128x4_decrypt,
+    16,
+    16,
+    aegis::TAG_LEN,
+    aegis::aegis128x_encrypt::<4>,
+    aegis::aegis128x_decrypt::<4>
+);
+#[cfg(feature = "aegis")]
+aead!(
+    _aegis256_encrypt,
+    _aegis256_decrypt,
+    32,
+    32,
+    aegis::TAG_LEN,
+    aegis::aegis256x_encrypt::<1>,
+    aegis::aegis256x_decrypt::<1>
+);
+#[cfg(feature = "aegis")]
+aead!(
+    _aegis256x2_encrypt,
+    _aegis256x2_decrypt,
+    32,
+    32,
+    aegis::TAG_LEN,
+    aegis::aegis256x_encrypt::<2>,
+    aegis::aegis256x_decrypt::<2>
+);
+#[cfg(feature = "aegis")]
+aead!(
+    _aegis256x4_encrypt,
+    _aegis256x4_decrypt,
+    32,
+    32,
+    aegis::TAG_LEN,
+    aegis::aegis256x_encrypt::<4>,
+    aegis::aegis256x_decrypt::<4>
+);
+
+macro_rules! cbc {
+    ($encrypt:ident, $decrypt:ident, $key_len:literal) => {
+        pub(crate) unsafe fn $encrypt(
+            c: *mut u8,
+            c_len: usize,
+            m: *const u8,
+            m_len: usize,
+            iv: *const u8,
+            k: *const u8,
+        ) -> i32 {
+            core::ptr::copy(m, c, m_len);
+            let buf = slice::from_raw_parts_mut(c, c_len);
+            let aes = Aes::new(array::<$key_len>(k));
+            modes::cbc_encrypt(&aes, array(iv), buf, m_len);
+            0
+        }
+
+        pub(crate) unsafe fn $decrypt(
+            m: *mut u8,
+            m_len: usize,
+            c: *const u8,
+            c_len: usize,
+            iv: *const u8,
+            k: *const u8,
+        ) -> i32 {
+            let (m, c) = (
+                slice::from_raw_parts_mut(m, m_len),
+                slice::from_raw_parts(c, c_len),
+            );
+            let aes = Aes::new(array::<$key_len>(k));
+            match modes::cbc_decrypt(&aes, array(iv), c, m) {
+                Some(len) => i32::try_from(len).unwrap_or(-1),
+                None => -1,
+            }
+        }
+    };
+}
+
+#[cfg(feature = "cbc")]
+cbc!(aes128cbc_encrypt, aes128cbc_decrypt, 16);
+#[cfg(feature = "cbc")]
+cbc!(aes256cbc_encrypt, aes256cbc_decrypt, 32);
+
+macro_rules! ctr {
+    ($name:ident, $key_len:literal) => {
+        pub(crate) unsafe fn $name(
+            c: *mut u8,
+            c_len: usize,
+            m: *const u8,
+            m_len: usize,
+            iv: *const u8,
+            k: *const u8,
+        ) -> i32 {
+            let buf = in_out(c, c_len, m, m_len);
+            modes::ctr(&Aes::new(array::<$key_len>(k)), array(iv), buf);
+            0
+        }
+    };
+}
+
+#[cfg(feature = "ctr")]
+ctr!(aes128ctr, 16);
+#[cfg(feature = "ctr")]
+ctr!(aes256ctr, 32);
+
+#[cfg(feature = "cmac")]
+pub(crate) unsafe fn cmac_aes128(tag: *mut u8, m: *const u8, m_len: usize, k: *const u8) -> i32 {
+    let aes = Aes::new(array::<16>(k));
+    *(tag as *mut Block) = modes::cmac(&aes, slice::from_raw_parts(m, m_len));
+    0
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use aes_gcm::aead::{Aead, KeyInit, Payload};
+    use aes_gcm::aes::cipher::{BlockEncryptMut, KeyIvInit, StreamCipher};
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+
+    const LENS: [usize; 14] = [0, 1, 15, 16, 17, 31, 32, 33, 63, 64, 65, 128, 257, 1000];
+
+    fn input(len: usize, seed: u8) -> Vec<u8> {
+        (0..len)
+            .map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed))
+            .collect()
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Returns the ciphertext and tag computed by an AEAD backend function.
+    macro_rules! encrypt {
+        ($f:path, $tag_len:expr, $msg:expr, $ad:expr, $nonce:expr, $key:expr) => {{
+            let (msg, ad, nonce, key): (&[u8], &[u8], &[u8], &[u8]) = ($msg, $ad, $nonce, $key);
+            let mut out = vec![0u8; msg.len() + $tag_len];
+            let (c, tag) = out.split_at_mut(msg.len());
+            let (c_len, m_len, ad_len) = (c.len(), msg.len(), ad.len());
+            let (m, ad, nonce, key) = (msg.as_ptr(), ad.as_ptr(), nonce.as_ptr(), key.as_ptr());
+            let res = unsafe {
+                $f(
+                    c.as_mut_ptr(),
+                    c_len,
+                    tag.as_mut_ptr(),
+                    m,
+                    m_len,
+                    ad,
+                    ad_len,
+                    nonce,
+                    key,
+                )
+            };
+            assert_eq!(res, 0);
+            out
+        }};
+    }
+
+    /// Returns the plaintext decrypted by an AEAD backend function, if verification succeeds.
+    macro_rules! decrypt {
+        ($f:path, $tag_len:expr, $ct:expr, $ad:expr, $nonce:expr, $key:expr) => {{
+            let (ct, ad, nonce, key): (&[u8], &[u8], &[u8], &[u8]) = ($ct, $ad, $nonce, $key);
+            let (c, tag) = ct.split_at(ct.len() - $tag_len);
+            let mut m = vec![0u8; c.len()];
+            let (c_len, ad_len) = (c.len(), ad.len());
+            let (c, tag, ad, nonce, key) = (
+                c.as_ptr(),
+                tag.as_ptr(),
+                ad.as_ptr(),
+                nonce.as_ptr(),
+                key.as_ptr(),
+            );
+            let m_len = m.len();
+            let res = unsafe { $f(m.as_mut_ptr(), m_len, c, c_len, tag, ad, ad_len, nonce, key) };
+            (res == 0).then_some(m)
+        }};
+    }
+
+    /// Checks that an AEAD backend function round-trips, rejects forgeries, and matches `reference`.
+    macro_rules! check_aead {
+        ($enc:path, $dec:path, $key_len:expr, $nonce_len:expr, $tag_len:expr, $reference:expr) => {{
+            let (key, nonce) = (input($key_len, 1), input($nonce_len, 2));
+            for len in LENS {
+                let (msg, ad) = (input(len, 3), input(len / 2, 4));
+                let ct = encrypt!($enc, $tag_len, &msg, &ad, &nonce, &key);
+                assert_eq!(ct, $reference(&msg, &ad, &nonce, &key), "length {len}");
+                assert_eq!(decrypt!($dec, $tag_len, &ct, &ad, &nonce, &key), Some(msg));
+                let mut forged = ct.clone();
+                forged[len / 2] ^= 1;
+                assert_eq!(decrypt!($dec, $tag_len, &forged, &ad, &nonce, &key), None);
+            }
+        }};
+    }
+
+    macro_rules! aegis_reference {
+        ($cipher:ty) => {
+            |msg: &[u8], ad: &[u8], nonce: &[u8], key: &[u8]| {
+                let cipher = <$cipher>::new(key.try_into().unwrap(), nonce.try_into().unwrap());
+                let (mut ct, tag) = cipher.encrypt(msg, ad);
+                ct.extend_from_slice(&tag);
+                ct
+            }
+        };
+    }
+
+    macro_rules! gcm_reference {
+        ($cipher:ty) => {
+            |msg: &[u8], ad: &[u8], nonce: &[u8], key: &[u8]| {
+                let cipher = <$cipher>::new_from_slice(key).unwrap();
+                cipher
+                    .encrypt(nonce.into(), Payload { msg, aad: ad })
+                    .unwrap()
+            }
+        };
+    }
+
+    #[cfg(feature = "aegis")]
+    #[test]
+    fn aegis() {
+        use ::aegis::{aegis128l, aegis128x2, aegis128x4, aegis256, aegis256x2, aegis256x4};
+        let r = aegis_reference!(aegis128l::Aegis128L::<32>);
+        check_aead!(_aegis128l_encrypt, _aegis128l_decrypt, 16, 16, 32, r);
+        let r = aegis_reference!(aegis128x2::Aegis128X2::<32>);
+        check_aead!(_aegis128x2_encrypt, _aegis128x2_decrypt, 16, 16, 32, r);
+        let r = aegis_reference!(aegis128x4::Aegis128X4::<32>);
+        check_aead!(_aegis128x4_encrypt, _aegis128x4_decrypt, 16, 16, 32, r);
+        let r = aegis_reference!(aegis256::Aegis256::<32>);
+        check_aead!(_aegis256_encrypt, _aegis256_decrypt, 32, 32, 32, r);
+        let r = aegis_reference!(aegis256x2::Aegis256X2::<32>);
+        check_aead!(_aegis256x2_encrypt, _aegis256x2_decrypt, 32, 32, 32, r);
+        let r = aegis_reference!(aegis256x4::Aegis256X4::<32>);
+        check_aead!(_aegis256x4_encrypt, _aegis256x4_decrypt, 32, 32, 32, r);
+    }
+
+    #[cfg(feature = "gcm")]
+    #[test]
+    fn gcm() {
+        let r = gcm_reference!(aes_gcm::Aes128Gcm);
+        check_aead!(aes128gcm_encrypt, aes128gcm_decrypt, 16, 12, 16, r);
+        let r = gcm_reference!(aes_gcm::Aes256Gcm);
+        check_aead!(aes256gcm_encrypt, aes256gcm_decrypt, 32, 12, 16, r);
+    }
+
+    #[cfg(feature = "ocb")]
+    #[test]
+    fn ocb() {
+        // Computed with the AESOCB3 implementation of Python's `cryptography` package.
+        let vectors = [
+            (16, 0, 0, "5ec007ef94c30680784b332e929447af"),
+            (16, 7, 16, "adc44f0a301e51314956ee57ecd4468a565fc4e82058c7"),
+            (16, 16, 7, "a67926c038c8c29070a5f406930937b449ecdd300ec8f2e51ae4455be9dc167b"),
+            (
+                16,
+                33,
+                40,
+                "a67926c038c8c29070a5f406930937b427b9240f0edf4589bf329e2671fd3ff8f54852e98278ac8eb2\
+                 ba9da50a579b6e0e",
+            ),
+            (32, 0, 0, "4e2a044fb40bcc9eba51a0271377b3d9"),
+            (32, 7, 16, "01cf1ecafcb63a4aee64915a1dee04728f44e4b9db3dce"),
+            (32, 16, 7, "c64dad0d73bd1f1391836bf35d4ef4d119d6e03d5f4140554bfbf9f4350336c1"),
+            (
+                32,
+                64,
+                0,
+                "c64dad0d73bd1f1391836bf35d4ef4d1284c33e161f625911e824163677a04c42dbdda44208745ae24\
+                 39f89c51d90c2d812fef452684cab4f72bc38153df64f058511b03086a883bae61c5cd6d65c161",
+            ),
+        ];
+        for (key_len, msg_len, ad_len, expected) in vectors {
+            let (key, nonce) = (input(key_len, 1), input(12, 2));
+            let (msg, ad) = (input(msg_len, 3), input(ad_len, 4));
+            let (ct, pt) = if key_len == 16 {
+                let ct = encrypt!(aes128ocb_encrypt, 16, &msg, &ad, &nonce, &key);
+                (
+                    ct.clone(),
+                    decrypt!(aes128ocb_decrypt, 16, &ct, &ad, &nonce, &key),
+                )
+            } else {
+                let ct = encrypt!(aes256ocb_encrypt, 16, &msg, &ad, &nonce, &key);
+                (
+                    ct.clone(),
+                    decrypt!(aes256ocb_decrypt, 16, &ct, &ad, &nonce, &key),
+                )
+            };
+            assert_eq!(ct, hex(expected));
+            assert_eq!(pt, Some(msg));
+        }
+    }
+
+    #[cfg(feature = "ctr")]
+    #[test]
+    fn ctr() {
+        type Aes128Ctr = ctr::Ctr128BE<aes_gcm::aes::Aes128>;
+        let (key, iv) = (input(16, 1), [0xffu8; 16]);
+        for len in LENS {
+            let msg = input(len, 3);
+            let mut expected = msg.clone();
+            Aes128Ctr::new(key[..].into(), &iv.into()).apply_keystream(&mut expected);
+            let mut out = vec![0u8; len];
+            unsafe {
+                aes128ctr(
+                    out.as_mut_ptr(),
+                    len,
+                    msg.as_ptr(),
+                    len,
+                    iv.as_ptr(),
+                    key.as_ptr(),
+                )
+            };
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[cfg(feature = "cbc")]
+    #[test]
+    fn cbc() {
+        type Aes256CbcEnc = cbc::Encryptor<aes_gcm::aes::Aes256>;
+        let (key, iv) = (input(32, 1), [0xffu8; 16]);
+        for len in LENS {
+            let msg = input(len, 3);
+            let mut padded = msg.clone();
+            padded.resize((len + 16) & !15, 0);
+            let expected = Aes256CbcEnc::new(key[..].into(), &iv.into())
+                .encrypt_padded_mut::<cbc::cipher::block_padding::Pkcs7>(&mut padded, len)
+                .unwrap();
+            let mut c = vec![0u8; expected.len()];
+            unsafe {
+                aes256cbc_encrypt(
+                    c.as_mut_ptr(),
+                    c.len(),
+                    msg.as_ptr(),
+                    len,
+                    iv.as_ptr(),
+                    key.as_ptr(),
+                )
+            };
+            assert_eq!(c, expected);
+            let mut m = vec![0u8; c.len() - 1];
+            let res = unsafe {
+                aes256cbc_decrypt(
+                    m.as_mut_ptr(),
+                    m.len(),
+                    c.as_ptr(),
+                    c.len(),
+                    iv.as_ptr(),
+                    key.as_ptr(),
+                )
+            };
+            assert_eq!(res, len as i32);
+            assert_eq!(m[..len], msg[..]);
+        }
+        let c = [0u8; 32];
+        let mut m = [0u8; 31];
+        for c_len in [0, 15, 32] {
+            let res = unsafe {
+                aes128cbc_decrypt(
+                    m.as_mut_ptr(),
+                    m.len(),
+                    c.as_ptr(),
+                    c_len,
+                    iv.as_ptr(),
+                    key.as_ptr(),
+                )
+            };
+            assert_eq!(res, -1);
+        }
+    }
+
+    #[cfg(feature = "cmac")]
+    #[test]
+    fn cmac() {
+        let key = input(16, 1);
+        for len in LENS {
+            let msg = input(len, 3);
+            let mut expected =
+                <cmac::Cmac<aes_gcm::aes::Aes128> as cmac::Mac>::new_from_slice(&key).unwrap();
+            cmac::Mac::update(&mut expected, &msg);
+            let mut tag = [0u8; 16];
+            unsafe { cmac_aes128(tag.as_mut_ptr(), msg.as_ptr(), len, key.as_ptr()) };
+            assert_eq!(tag[..], cmac::Mac::finalize(expected).into_bytes()[..]);
+        }
+    }
+
+    /// The Zig library, to compare both backends.
+    #[cfg(zig_backend)]
+    mod zig {
+        macro_rules! aead {
+            ($($encrypt:ident, $decrypt:ident;)*) => {
+                $(
+                    pub fn $encrypt(
+                        c: *mut u8,
+                        c_len: usize,
+                        tag: *mut u8,
+                        m: *const u8,
+                        m_len: usize,
+                        ad: *const u8,
+                        ad_len: usize,
+                        nonce: *const u8,
+                        k: *const u8,
+                    ) -> i32;
+                    pub fn $decrypt(
+                        m: *mut u8,
+                        m_len: usize,
+                        c: *const u8,
+                        c_len: usize,
+                        tag: *const u8,
+                        ad: *const u8,
+                        ad_len: usize,
+                        nonce: *const u8,
+                        k: *const u8,
+                    ) -> i32;
+                )*
+            };
+        }
+
+        extern "C" {
+            aead! {
+                aes128gcm_encrypt, aes128gcm_decrypt;
+                aes256gcm_encrypt, aes256gcm_decrypt;
+                aes128ocb_encrypt, aes128ocb_decrypt;
+                aes256ocb_encrypt, aes256ocb_decrypt;
+                _aegis128l_encrypt, _aegis128l_decrypt;
+                _aegis128x2_encrypt, _aegis128x2_decrypt;
+                _aegis128x4_encrypt, _aegis128x4_decrypt;
+                _aegis256_encrypt, _aegis256_decrypt;
+                _aegis256x2_encrypt, _aegis256x2_decrypt;
+                _aegis256x4_encrypt, _aegis256x4_decrypt;
+            }
+            pub fn aes128cbc_encrypt(
+                c: *mut u8,
+                c_len: usize,
+                m: *const u8,
+                m_len: usize,
+                iv: *const u8,
+                k: *const u8,
+            ) -> i32;
+            pub fn aes256cbc_encrypt(
+                c: *mut u8,
+                c_len: usize,
+                m: *const u8,
+                m_len: usize,
+                iv: *const u8,
+                k: *const u8,
+            ) -> i32;
+            pub fn aes128ctr(
+                c: *mut u8,
+                c_len: usize,
+                m: *const u8,
+                m_len: usize,
+                iv: *const u8,
+                k: *const u8,
+            ) -> i32;
+            pub fn aes256ctr(
+                c: *mut u8,
+                c_len: usize,
+                m: *const u8,
+                m_len: usize,
+                iv: *const u8,
+                k: *const u8,
+            ) -> i32;
+            pub fn cmac_aes128(tag: *mut u8, m: *const u8, m_len: usize, k: *const u8) -> i32;
+        }
+    }
+
+    #[cfg(zig_backend)]
+    #[test]
+    fn differential() {
+        macro_rules! same_aead {
+            ($enc:ident, $dec:ident, $key_len:expr, $nonce_len:expr, $tag_len:expr) => {
+                same_aead!($enc, $dec, $key_len, $nonce_len, $tag_len, |len| len / 2)
+            };
+            ($enc:ident, $dec:ident, $key_len:expr, $nonce_len:expr, $tag_len:expr, $ad_len:expr) => {{
+                let (key, nonce) = (input($key_len, 1), input($nonce_len, 2));
+                for len in LENS {
+                    for ad in [input(0, 4), input($ad_len(len), 4)] {
+                        let msg = input(len, 3);
+                        let ct = encrypt!(zig::$enc, $tag_len, &msg, &ad, &nonce, &key);
+                        let name = stringify!($enc);
+                        let expected = encrypt!($enc, $tag_len, &msg, &ad, &nonce, &key);
+                        assert_eq!(ct, expected, "{name} {len} {}", ad.len());
+                        assert_eq!(
+                            decrypt!($dec, $tag_len, &ct, &ad, &nonce, &key),
+                            Some(msg.clone())
+                        );
+                        assert_eq!(
+                            decrypt!(zig::$dec, $tag_len, &expected, &ad, &nonce, &key),
+                            Some(msg)
+                        );
+                    }
+                }
+            }};
+        }
+        #[cfg(feature = "gcm")]
+        same_aead!(aes128gcm_encrypt, aes128gcm_decrypt, 16, 12, 16);
+        #[cfg(feature = "gcm")]
+        same_aead!(aes256gcm_encrypt, aes256gcm_decrypt, 32, 12, 16);
+        #[cfg(feature = "aegis")]
+        same_aead!(_aegis128l_encrypt, _aegis128l_decrypt, 16, 16, 32);
+        #[cfg(feature = "aegis")]
+        same_aead!(_aegis128x2_encrypt, _aegis128x2_decrypt, 16, 16, 32);
+        #[cfg(feature = "aegis")]
+        same_aead!(_aegis128x4_encrypt, _aegis128x4_decrypt, 16, 16, 32);
+        #[cfg(feature = "aegis")]
+        same_aead!(_aegis256_encrypt, _aegis256_decrypt, 32, 32, 32);
+        #[cfg(feature = "aegis")]
+        same_aead!(_aegis256x2_encrypt, _aegis256x2_decrypt, 32, 32, 32);
+        #[cfg(feature = "aegis")]
+        same_aead!(_aegis256x4_encrypt, _aegis256x4_decrypt, 32, 32, 32);
+        #[cfg(all(feature = "ocb", feature = "build-from-source"))]
+        same_aead!(aes128ocb_encrypt, aes128ocb_decrypt, 16, 12, 16);
+        #[cfg(all(feature = "ocb", feature = "build-from-source"))]
+        same_aead!(aes256ocb_encrypt, aes256ocb_decrypt, 32, 12, 16);
+        // The prebuilt OCB code computes tags that differ from RFC 7253 when associated data
+        // is present, so both backends are only compared without it. The modules use the
+        // pure-Rust code in that case, which is checked below, and `decrypt_legacy` accepts
+        // the tags of the prebuilt code.
+        #[cfg(all(feature = "ocb", not(feature = "build-from-source")))]
+        same_aead!(aes128ocb_encrypt, aes128ocb_decrypt, 16, 12, 16, |_| 0);
+        #[cfg(all(feature = "ocb", not(feature = "build-from-source")))]
+        same_aead!(aes256ocb_encrypt, aes256ocb_decrypt, 32, 12, 16, |_| 0);
+        #[cfg(feature = "ocb")]
+        for len in LENS {
+            let (key, nonce) = (input(32, 1), input(12, 2));
+            let (msg, ad) = (input(len, 3), input(len / 2 + 1, 4));
+            let expected = encrypt!(aes128ocb_encrypt, 16, &msg, &ad, &nonce, &key[..16]);
+            let (k, n) = (key[..16].try_into().unwrap(), nonce[..].try_into().unwrap());
+            assert_eq!(crate::aes128ocb::encrypt(&msg, &ad, k, n), expected);
+            assert_eq!(
+                crate::aes128ocb::decrypt(&expected, &ad, k, n),
+                Ok(msg.clone())
+            );
+            let expected = encrypt!(aes256ocb_encrypt, 16, &msg, &ad, &nonce, &key);
+            let (k, n) = (key[..].try_into().unwrap(), nonce[..].try_into().unwrap());
+            assert_eq!(crate::aes256ocb::encrypt(&msg, &ad, k, n), expected);
+            assert_eq!(
+                crate::aes256ocb::decrypt(&expected, &ad, k, n),
+                Ok(msg.clone())
+            );
+            #[cfg(not(feature = "build-from-source"))]
+            {
+                let legacy = encrypt!(zig::aes128ocb_encrypt, 16, &msg, &ad, &nonce, &key[..16]);
+                let (k, n) = (key[..16].try_into().unwrap(), nonce[..].try_into().unwrap());
+                assert_eq!(
+                    crate::aes128ocb::decrypt_legacy(&legacy, &ad, k, n),
+                    Ok(msg.clone())
+                );
+                let legacy = encrypt!(zig::aes256ocb_encrypt, 16, &msg, &ad, &nonce, &key);
+                let (k, n) = (key[..].try_into().unwrap(), nonce[..].try_into().unwrap());
+                assert_eq!(
+                    crate::aes256ocb::decrypt_legacy(&legacy, &ad, k, n),
+                    Ok(msg)
+                );
+            }
+        }
+
+        let (key, iv) = (input(32, 1), [0xffu8; 16]);
+        for len in LENS {
+            let msg = input(len, 3);
+            macro_rules! same {
+                ($f:ident, $out_len:expr) => {{
+                    let (mut a, mut b) = (vec![0u8; $out_len], vec![0u8; $out_len]);
+                    let (m, iv, k) = (msg.as_ptr(), iv.as_ptr(), key.as_ptr());
+                    unsafe {
+                        zig::$f(a.as_mut_ptr(), a.len(), m, len, iv, k);
+                        $f(b.as_mut_ptr(), b.len(), m, len, iv, k);
+                    }
+                    assert_eq!(a, b, "{} {len}", stringify!($f));
+                }};
+            }
+            #[cfg(feature = "cbc")]
+            same!(aes128cbc_encrypt, (len + 16) & !15);
+            #[cfg(feature = "cbc")]
+            same!(aes256cbc_encrypt, (len + 16) & !15);
+            #[cfg(feature = "ctr")]
+            same!(aes128ctr, len);
+            #[cfg(feature = "ctr")]
+            same!(aes256ctr, len);
+            #[cfg(feature = "cmac")]
+            {
+                let (mut a, mut b) = ([0u8; 16], [0u8; 16]);
+                unsafe {
+                    zig::cmac_aes128(a.as_mut_ptr(), msg.as_ptr(), len, key.as_ptr());
+                    cmac_aes128(b.as_mut_ptr(), msg.as_ptr(), len, key.as_ptr());
+                }
+                assert_eq!(a, b);
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! AES-CTR with a 128-bit counter, AES-CBC with PKCS#7 padding, and CMAC-AES.
+
+use super::aes::{xor_block, Aes, Block, PARALLEL_BLOCKS};
+
+pub(crate) fn ctr(aes: &Aes, iv: &Block, buf: &mut [u8]) {
+    let counter = u128::from_be_bytes(*iv);
+    for (i, chunk) in buf.chunks_mut(16 * PARALLEL_BLOCKS).enumerate() {
+        let mut ks: [Block; PARALLEL_BLOCKS] = core::array::from_fn(|k| {
+            counter
+                .wrapping_add((i * PARALLEL_BLOCKS + k) as u128)
+                .to_be_bytes()
+        });
+        aes.encrypt_blocks(&mut ks);
+        chunk
+            .iter_mut()
+            .zip(ks.as_flattened())
+            .for_each(|(b, k)| *b ^= k);
+    }
+}
+
+/// Encrypts `buf[..msg_len]`, padded to `buf.len()`, which must be the next multiple of 16.
+pub(crate) fn cbc_encrypt(aes: &Aes, iv: &Block, buf: &mut [u8], msg_len: usize) {
+    let pad = (buf.len() - msg_len) as u8;
+    buf[msg_len..].fill(pad);
+    let mut chaining = *iv;
+    for chunk in buf.chunks_exact_mut(16) {
+        xor_block(&mut chaining, (*chunk).try_into().unwrap());
+        aes.encrypt_block(&mut chaining);
+        chunk.copy_from_slice(&chaining);
+    }
+}
+
+/// Decrypts `c` into `m`, which must be at least `c.len() - 1` bytes long, and returns the
+/// length of the unpadded plaintext.
+pub(crate) fn cbc_decrypt(aes: &Aes, iv: &Block, c: &[u8], m: &mut [u8]) -> Option<usize> {
+    if c.is_empty() || c.len() % 16 != 0 || m.len() < c.len() - 1 {
+        return None;
+    }
+    let mut chaining = *iv;
+    let mut last = Block::default();
+    for (i, block) in c.chunks_exact(16).enumerate() {
+        let block: Block = block.try_into().unwrap();
+        last = block;
+        aes.decrypt_block(&mut last);
+        xor_block(&mut last, &chaining);
+        if i < c.len() / 16 - 1 {
+            m[i * 16..(i + 1) * 16].copy_from_slice(&last);
+        }
+        chaining = block;
+    }
+    let pad = last[15];
+    let mut bad = (pad.wrapping_sub(1) >> 7) | (16u8.wrapping_sub(pad) >> 7);
+    for (i, &b) in last.iter().enumerate() {
+        let in_pad = ((15 - i as u8).wrapping_sub(pad) >> 7) & 1;
+        bad |= in_pad * (b ^ pad);
+    }
+    if bad != 0 {
+        return None;
+    }
+    let msg_len = c.len() - pad as usize;
+    let last_len = 16 - pad as usize;
+    m[msg_len - last_len..msg_len].copy_from_slice(&last[..last_len]);
+    Some(msg_len)
+}
+
+pub(crate) fn cmac(aes: &Aes, msg: &[u8]) -> Block {
+    let dbl = |b: &Block| {
+        let x = u128::from_be_bytes(*b);
+        ((x << 1) ^ ((x >> 127) * 0x87)).to_be_bytes()
+    };
+    let mut l = Block::default();
+    aes.encrypt_block(&mut l);
+    let k1 = dbl(&l);
+    let k2 = dbl(&k1);
+    let full_blocks = msg.len().saturating_sub(1) / 16;
+    let mut x = Block::default();
+    for block in msg[..full_blocks * 16].chunks_exact(16) {
+        xor_block(&mut x, block.try_into().unwrap());
+        aes.encrypt_block(&mut x);
+    }
+    let last = &msg[full_blocks * 16..];
+    x.iter_mut().zip(last).for_each(|(x, m)| *x ^= m);
+    if last.len() == 16 {
+        xor_block(&mut x, &k1);
+    } else {
+        x[last.len()] ^= 0x80;
+        xor_block(&mut x, &k2);
+    }
+    aes.encrypt_block(&mut x);
+    x
+}
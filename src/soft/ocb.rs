@@ -0,0 +1,167 @@
+//! AES-OCB (RFC 7253) with 96-bit nonces and 128-bit tags.
+
+use super::aes::{xor_block, Aes, Block};
+use crate::ct_eq;
+
+pub(crate) const TAG_LEN: usize = 16;
+
+fn double(b: &Block) -> Block {
+    let x = u128::from_be_bytes(*b);
+    ((x << 1) ^ ((x >> 127) * 0x87)).to_be_bytes()
+}
+
+struct Ocb {
+    aes: Aes,
+    l_star: Block,
+    l_dollar: Block,
+    l: [Block; usize::BITS as usize],
+}
+
+impl Ocb {
+    fn new(key: &[u8]) -> Self {
+        let aes = Aes::new(key);
+        let mut l_star = Block::default();
+        aes.encrypt_block(&mut l_star);
+        let l_dollar = double(&l_star);
+        let mut l = [double(&l_dollar); usize::BITS as usize];
+        for i in 1..l.len() {
+            l[i] = double(&l[i - 1]);
+        }
+        Ocb {
+            aes,
+            l_star,
+            l_dollar,
+            l,
+        }
+    }
+
+    /// Returns HASH(K, A), padding a partial last block with `pad` followed by zeros.
+    fn hash(&self, ad: &[u8], pad: u8) -> Block {
+        let mut sum = Block::default();
+        let mut offset = Block::default();
+        let mut chunks = ad.chunks_exact(16);
+        for (i, chunk) in chunks.by_ref().enumerate() {
+            xor_block(&mut offset, &self.l[(i + 1).trailing_zeros() as usize]);
+            let mut block: Block = chunk.try_into().unwrap();
+            xor_block(&mut block, &offset);
+            self.aes.encrypt_block(&mut block);
+            xor_block(&mut sum, &block);
+        }
+        let rest = chunks.remainder();
+        if !rest.is_empty() {
+            xor_block(&mut offset, &self.l_star);
+            let mut block = Block::default();
+            block[..rest.len()].copy_from_slice(rest);
+            block[rest.len()] = pad;
+            xor_block(&mut block, &offset);
+            self.aes.encrypt_block(&mut block);
+            xor_block(&mut sum, &block);
+        }
+        sum
+    }
+
+    fn initial_offset(&self, nonce: &[u8; 12]) -> Block {
+        let mut n = Block::default();
+        n[3] = 1;
+        n[4..].copy_from_slice(nonce);
+        let bottom = (n[15] & 0x3f) as usize;
+        n[15] &= 0xc0;
+        self.aes.encrypt_block(&mut n);
+        let mut stretch = [0u8; 24];
+        stretch[..16].copy_from_slice(&n);
+        for i in 0..8 {
+            stretch[16 + i] = n[i] ^ n[i + 1];
+        }
+        let (shift, bits) = (bottom / 8, bottom % 8);
+        core::array::from_fn(|i| {
+            let hi = stretch[i + shift] << bits;
+            let lo = ((stretch[i + shift + 1] as u16) >> (8 - bits)) as u8;
+            hi | lo
+        })
+    }
+
+    /// Encrypts or decrypts `buf` in place, and returns the tag.
+    fn process(&self, buf: &mut [u8], ad: &[u8], nonce: &[u8; 12], encrypt: bool) -> Block {
+        let mut offset = self.initial_offset(nonce);
+        let mut checksum = Block::default();
+        let mut chunks = buf.chunks_exact_mut(16);
+        for (i, chunk) in chunks.by_ref().enumerate() {
+            xor_block(&mut offset, &self.l[(i + 1).trailing_zeros() as usize]);
+            let mut block: Block = (*chunk).try_into().unwrap();
+            if encrypt {
+                xor_block(&mut checksum, &block);
+            }
+            xor_block(&mut block, &offset);
+            if encrypt {
+                self.aes.encrypt_block(&mut block);
+            } else {
+                self.aes.decrypt_block(&mut block);
+            }
+            xor_block(&mut block, &offset);
+            if !encrypt {
+                xor_block(&mut checksum, &block);
+            }
+            chunk.copy_from_slice(&block);
+        }
+        let rest = chunks.into_remainder();
+        if !rest.is_empty() {
+            xor_block(&mut offset, &self.l_star);
+            let mut pad = offset;
+            self.aes.encrypt_block(&mut pad);
+            let mut padded = Block::default();
+            if encrypt {
+                padded[..rest.len()].copy_from_slice(rest);
+            }
+            rest.iter_mut().zip(pad).for_each(|(b, p)| *b ^= p);
+            if !encrypt {
+                padded[..rest.len()].copy_from_slice(rest);
+            }
+            padded[rest.len()] = 0x80;
+            xor_block(&mut checksum, &padded);
+        }
+        let mut tag = checksum;
+        xor_block(&mut tag, &offset);
+        xor_block(&mut tag, &self.l_dollar);
+        self.aes.encrypt_block(&mut tag);
+        xor_block(&mut tag, &self.hash(ad, 0x80));
+        tag
+    }
+}
+
+pub(crate) fn encrypt(buf: &mut [u8], ad: &[u8], nonce: &[u8; 12], key: &[u8]) -> Block {
+    Ocb::new(key).process(buf, ad, nonce, true)
+}
+
+pub(crate) fn decrypt(
+    buf: &mut [u8],
+    expected_tag: &Block,
+    ad: &[u8],
+    nonce: &[u8; 12],
+    key: &[u8],
+) -> bool {
+    let tag = Ocb::new(key).process(buf, ad, nonce, false);
+    let valid = ct_eq(&tag, expected_tag);
+    if !valid {
+        buf.fill(0);
+    }
+    valid
+}
+
+/// Decrypts a message whose tag was computed by version 0.1.17 or earlier of the prebuilt
+/// library, which padded a partial last block of associated data with 0x01 instead of 0x80.
+pub(crate) fn decrypt_legacy(
+    buf: &mut [u8],
+    expected_tag: &Block,
+    ad: &[u8],
+    nonce: &[u8; 12],
+    key: &[u8],
+) -> bool {
+    let ocb = Ocb::new(key);
+    let mut tag = ocb.process(buf, &[], nonce, false);
+    xor_block(&mut tag, &ocb.hash(ad, 0x01));
+    let valid = ct_eq(&tag, expected_tag);
+    if !valid {
+        buf.fill(0);
+    }
+    valid
+}
@@ -0,0 +1,510 @@
+//! STREAM construction for segmented authenticated encryption.
+//!
+//! This module implements the nonce-based online authenticated encryption construction of
+//! Hoang, Reyhanitabar, Rogaway and Vizár (STREAM), over any AEAD of this crate.
+//!
+//! A message is split into segments of `segment_size` bytes, the last one being possibly
+//! shorter. Each segment is encrypted separately, with a nonce made of a random per-stream
+//! prefix, a 32-bit big-endian segment counter, and a flag set to `1` for the last segment
+//! and `0` otherwise:
+//!
+//! | length            | content              |
+//! | ----------------: | :------------------- |
+//! | `nonce_len() - 5` | nonce prefix         |
+//! |                 4 | segment counter      |
+//! |                 1 | last segment flag    |
+//!
+//! Reordered, duplicated, truncated or extended streams fail to decrypt, and each segment
+//! can be decrypted independently, given its index.
+//!
+//! Since the nonce prefix is random, the number of streams encrypted with the same key
+//! should be limited. With AES-GCM and AES-OCB, the prefix is 7 bytes long, and a key
+//! should not be used for more than 2^24 streams. AEGIS and AES-CBC-HMAC-SHA2 have longer
+//! prefixes.
+//!
+//! ## Example
+//! ```rust
+//! # #[cfg(feature = "gcm")] {
+//! use aes_wasm::stream::{Decryptor, Encryptor};
+//! use aes_wasm::Algorithm;
+//! let alg = Algorithm::Aes256Gcm;
+//! let key = alg.generate_key();
+//! let mut encryptor = Encryptor::new(alg, &key, 4).unwrap();
+//! let nonce_prefix = encryptor.nonce_prefix().to_vec();
+//! let c0 = encryptor.encrypt_next(b"hell", b"").unwrap();
+//! let c1 = encryptor.encrypt_last(b"o", b"").unwrap();
+//!
+//! let mut decryptor = Decryptor::new(alg, &key, &nonce_prefix, 4).unwrap();
+//! let mut plaintext = decryptor.decrypt_next(&c0, b"").unwrap();
+//! plaintext.extend(decryptor.decrypt_last(&c1, b"").unwrap());
+//! assert_eq!(plaintext, b"hello");
+//! # }
+//! ```
+
+pub use crate::*;
+
+/// The length of the segment counter and last segment flag, appended to the nonce prefix.
+pub const NONCE_SUFFIX_LEN: usize = 5;
+/// A reasonable default segment size, in bytes.
+pub const DEFAULT_SEGMENT_SIZE: usize = 65536;
+
+/// Returns the length of the nonce prefix for an algorithm.
+pub fn nonce_prefix_len(alg: Algorithm) -> usize {
+    alg.nonce_len() - NONCE_SUFFIX_LEN
+}
+
+#[derive(Clone)]
+struct Stream {
+    alg: Algorithm,
+    key: Vec<u8>,
+    nonce_prefix: Vec<u8>,
+    segment_size: usize,
+}
+
+impl Stream {
+    fn new(
+        alg: Algorithm,
+        key: &[u8],
+        nonce_prefix: &[u8],
+        segment_size: usize,
+    ) -> Result<Self, Error> {
+        if key.len() != alg.key_len() {
+            return Err(Error::InvalidKeyLength);
+        }
+        if nonce_prefix.len() != nonce_prefix_len(alg) {
+            return Err(Error::InvalidNonceLength);
+        }
+        if segment_size == 0 {
+            return Err(Error::InvalidParameters);
+        }
+        Ok(Stream {
+            alg,
+            key: key.to_vec(),
+            nonce_prefix: nonce_prefix.to_vec(),
+            segment_size,
+        })
+    }
+
+    fn nonce(&self, index: u32, last: bool) -> Vec<u8> {
+        let mut nonce = Vec::with_capacity(self.alg.nonce_len());
+        nonce.extend_from_slice(&self.nonce_prefix);
+        nonce.extend_from_slice(&index.to_be_bytes());
+        nonce.push(last as u8);
+        nonce
+    }
+
+    fn encrypt(&self, index: u32, last: bool, segment: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+        let valid_len = match last {
+            false => segment.len() == self.segment_size,
+            true => segment.len() <= self.segment_size,
+        };
+        if !valid_len {
+            return Err(Error::InvalidParameters);
+        }
+        self.alg
+            .encrypt(segment, ad, &self.key, &self.nonce(index, last))
+    }
+
+    fn decrypt(&self, index: u32, last: bool, segment: &[u8], ad: &[u8]) -> Result<Vec<u8>, Error> {
+        let ciphertext_segment_size = self.alg.ciphertext_len(self.segment_size);
+        let valid_len = match last {
+            false => segment.len() == ciphertext_segment_size,
+            true => (self.alg.tag_len()..=ciphertext_segment_size).contains(&segment.len()),
+        };
+        // Segment boundaries are authenticated: truncated segments fail verification.
+        if !valid_len {
+            return Err(Error::VerificationFailed);
+        }
+        self.alg
+            .decrypt(segment, ad, &self.key, &self.nonce(index, last))
+    }
+}
+
+/// Encrypts a sequence of segments.
+#[derive(Clone)]
+pub struct Encryptor {
+    stream: Stream,
+    index: u32,
+}
+
+impl Encryptor {
+    /// Creates an encryptor with a random nonce prefix.
+    ///
+    /// # Arguments
+    /// * `alg` - The AEAD algorithm.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    /// * `segment_size` - The plaintext length of every segment but the last one.
+    ///
+    /// # Returns
+    /// The encryptor, `Err(Error::InvalidKeyLength)` if the key length is invalid, or
+    /// `Err(Error::InvalidParameters)` if the segment size is zero.
+    #[cfg(feature = "getrandom")]
+    pub fn new(alg: Algorithm, key: &[u8], segment_size: usize) -> Result<Self, Error> {
+        let mut nonce_prefix = vec![0u8; nonce_prefix_len(alg)];
+        random_bytes(&mut nonce_prefix);
+        Self::with_nonce_prefix(alg, key, &nonce_prefix, segment_size)
+    }
+
+    /// Creates an encryptor with a caller-provided nonce prefix.
+    ///
+    /// The nonce prefix must never be reused with the same key.
+    ///
+    /// # Returns
+    /// The encryptor, `Err(Error::InvalidKeyLength)` if the key length is invalid,
+    /// `Err(Error::InvalidNonceLength)` if the nonce prefix length is not
+    /// `nonce_prefix_len(alg)`, or `Err(Error::InvalidParameters)` if the segment size is zero.
+    pub fn with_nonce_prefix(
+        alg: Algorithm,
+        key: &[u8],
+        nonce_prefix: &[u8],
+        segment_size: usize,
+    ) -> Result<Self, Error> {
+        Ok(Encryptor {
+            stream: Stream::new(alg, key, nonce_prefix, segment_size)?,
+            index: 0,
+        })
+    }
+
+    /// Returns the nonce prefix, required to decrypt the stream.
+    pub fn nonce_prefix(&self) -> &[u8] {
+        &self.stream.nonce_prefix
+    }
+
+    /// Returns the plaintext segment size.
+    pub fn segment_size(&self) -> usize {
+        self.stream.segment_size
+    }
+
+    /// Encrypts a segment that is not the last one.
+    ///
+    /// # Arguments
+    /// * `segment` - The plaintext segment, exactly `segment_size` bytes long.
+    /// * `ad` - Additional authenticated data (AAD) for this segment.
+    ///
+    /// # Returns
+    /// The ciphertext segment with its tag appended, or `Err(Error::InvalidParameters)` if the
+    /// segment length is invalid or if the maximum number of segments has been reached.
+    pub fn encrypt_next(
+        &mut self,
+        segment: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        if self.index == u32::MAX {
+            return Err(Error::InvalidParameters);
+        }
+        let ciphertext = self
+            .stream
+            .encrypt(self.index, false, segment.as_ref(), ad.as_ref())?;
+        self.index += 1;
+        Ok(ciphertext)
+    }
+
+    /// Sets the index of the next segment.
+    #[cfg(all(test, feature = "async"))]
+    pub(crate) fn set_index(&mut self, index: u32) {
+        self.index = index;
+    }
+
+    /// Encrypts the last segment, terminating the stream.
+    ///
+    /// # Arguments
+    /// * `segment` - The plaintext segment, at most `segment_size` bytes long. It can be empty.
+    /// * `ad` - Additional authenticated data (AAD) for this segment.
+    ///
+    /// # Returns
+    /// The ciphertext segment with its tag appended, or `Err(Error::InvalidParameters)` if the
+    /// segment is too long.
+    pub fn encrypt_last(
+        self,
+        segment: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        self.stream
+            .encrypt(self.index, true, segment.as_ref(), ad.as_ref())
+    }
+}
+
+/// Decrypts a sequence of segments, or individual segments.
+#[derive(Clone)]
+pub struct Decryptor {
+    stream: Stream,
+    index: u32,
+}
+
+impl Decryptor {
+    /// Creates a decryptor.
+    ///
+    /// # Arguments
+    /// * `alg` - The AEAD algorithm.
+    /// * `key` - The secret key, whose length must match the algorithm.
+    /// * `nonce_prefix` - The nonce prefix of the encryptor.
+    /// * `segment_size` - The plaintext segment size of the encryptor.
+    ///
+    /// # Returns
+    /// The decryptor, `Err(Error::InvalidKeyLength)` if the key length is invalid,
+    /// `Err(Error::InvalidNonceLength)` if the nonce prefix length is invalid, or
+    /// `Err(Error::InvalidParameters)` if the segment size is zero.
+    pub fn new(
+        alg: Algorithm,
+        key: &[u8],
+        nonce_prefix: &[u8],
+        segment_size: usize,
+    ) -> Result<Self, Error> {
+        Ok(Decryptor {
+            stream: Stream::new(alg, key, nonce_prefix, segment_size)?,
+            index: 0,
+        })
+    }
+
+    /// Returns the length of a ciphertext segment that is not the last one.
+    pub fn ciphertext_segment_size(&self) -> usize {
+        self.stream.alg.ciphertext_len(self.stream.segment_size)
+    }
+
+    /// Decrypts a segment that is not the last one.
+    ///
+    /// # Arguments
+    /// * `segment` - The ciphertext segment, with its tag appended.
+    /// * `ad` - Additional authenticated data (AAD), as given to the encryptor.
+    ///
+    /// # Returns
+    /// `Ok(plaintext)` if verification succeeds, or `Err(Error::VerificationFailed)` otherwise,
+    /// including if the segment is the last one or is out of order.
+    pub fn decrypt_next(
+        &mut self,
+        segment: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        if self.index == u32::MAX {
+            return Err(Error::VerificationFailed);
+        }
+        let plaintext = self
+            .stream
+            .decrypt(self.index, false, segment.as_ref(), ad.as_ref())?;
+        self.index += 1;
+        Ok(plaintext)
+    }
+
+    /// Decrypts the last segment, completing the stream.
+    ///
+    /// A stream whose last segment has not been successfully decrypted may have been truncated.
+    ///
+    /// # Returns
+    /// `Ok(plaintext)` if verification succeeds, or `Err(Error::VerificationFailed)` otherwise,
+    /// including if the segment is not the last one.
+    pub fn decrypt_last(
+        self,
+        segment: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        self.stream
+            .decrypt(self.index, true, segment.as_ref(), ad.as_ref())
+    }
+
+    /// Decrypts an arbitrary segment, without affecting the sequential state.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the segment, starting at `0`.
+    /// * `last` - `true` if this is the last segment of the stream.
+    /// * `segment` - The ciphertext segment, with its tag appended.
+    /// * `ad` - Additional authenticated data (AAD), as given to the encryptor.
+    ///
+    /// # Returns
+    /// `Ok(plaintext)` if verification succeeds, or `Err(Error::VerificationFailed)` otherwise.
+    ///
+    /// # Example
+    /// ```
+    /// # #[cfg(feature = "aegis")] {
+    /// use aes_wasm::stream::{encrypt, nonce_prefix_len, Decryptor};
+    /// use aes_wasm::Algorithm;
+    /// let alg = Algorithm::Aegis128L;
+    /// let key = alg.generate_key();
+    /// let ciphertext = encrypt(alg, &key, 4, b"hello world", b"").unwrap();
+    /// let (nonce_prefix, segments) = ciphertext.split_at(nonce_prefix_len(alg));
+    /// let decryptor = Decryptor::new(alg, &key, nonce_prefix, 4).unwrap();
+    /// let size = decryptor.ciphertext_segment_size();
+    /// let segment = decryptor.decrypt_segment(1, false, &segments[size..2 * size], b"");
+    /// assert_eq!(segment.unwrap(), b"o wo");
+    /// # }
+    /// ```
+    pub fn decrypt_segment(
+        &self,
+        index: u32,
+        last: bool,
+        segment: impl AsRef<[u8]>,
+        ad: impl AsRef<[u8]>,
+    ) -> Result<Vec<u8>, Error> {
+        self.stream
+            .decrypt(index, last, segment.as_ref(), ad.as_ref())
+    }
+}
+
+/// Encrypts a complete message as a stream with a random nonce prefix.
+///
+/// # Arguments
+/// * `alg` - The AEAD algorithm.
+/// * `key` - The secret key, whose length must match the algorithm.
+/// * `segment_size` - The plaintext segment size.
+/// * `msg` - The plaintext message to encrypt.
+/// * `ad` - Additional authenticated data (AAD), used for every segment.
+///
+/// # Returns
+/// The nonce prefix followed by the ciphertext segments, or an error if the parameters are
+/// invalid.
+#[cfg(feature = "getrandom")]
+pub fn encrypt(
+    alg: Algorithm,
+    key: &[u8],
+    segment_size: usize,
+    msg: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let (mut msg, ad) = (msg.as_ref(), ad.as_ref());
+    let mut encryptor = Encryptor::new(alg, key, segment_size)?;
+    let mut out = encryptor.nonce_prefix().to_vec();
+    while msg.len() > segment_size {
+        let (segment, rest) = msg.split_at(segment_size);
+        out.extend(encryptor.encrypt_next(segment, ad)?);
+        msg = rest;
+    }
+    out.extend(encryptor.encrypt_last(msg, ad)?);
+    Ok(out)
+}
+
+/// Decrypts a complete stream produced by `encrypt`.
+///
+/// # Arguments
+/// * `alg` - The AEAD algorithm.
+/// * `key` - The secret key.
+/// * `segment_size` - The plaintext segment size given to `encrypt`.
+/// * `ciphertext` - The nonce prefix followed by the ciphertext segments.
+/// * `ad` - Additional authenticated data (AAD), as given to `encrypt`.
+///
+/// # Returns
+/// `Ok(plaintext)` if verification succeeds, `Err(Error::InvalidLength)` if the ciphertext is
+/// shorter than the nonce prefix, or `Err(Error::VerificationFailed)` otherwise.
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "aegis")] {
+/// use aes_wasm::stream::{decrypt, encrypt, DEFAULT_SEGMENT_SIZE};
+/// use aes_wasm::Algorithm;
+/// let alg = Algorithm::Aegis256;
+/// let key = alg.generate_key();
+/// let ciphertext = encrypt(alg, &key, DEFAULT_SEGMENT_SIZE, b"hello", b"ad").unwrap();
+/// let plaintext = decrypt(alg, &key, DEFAULT_SEGMENT_SIZE, &ciphertext, b"ad").unwrap();
+/// assert_eq!(plaintext, b"hello");
+/// # }
+/// ```
+pub fn decrypt(
+    alg: Algorithm,
+    key: &[u8],
+    segment_size: usize,
+    ciphertext: impl AsRef<[u8]>,
+    ad: impl AsRef<[u8]>,
+) -> Result<Vec<u8>, Error> {
+    let (ciphertext, ad) = (ciphertext.as_ref(), ad.as_ref());
+    if ciphertext.len() < nonce_prefix_len(alg) {
+        return Err(Error::InvalidLength);
+    }
+    let (nonce_prefix, mut ciphertext) = ciphertext.split_at(nonce_prefix_len(alg));
+    let mut decryptor = Decryptor::new(alg, key, nonce_prefix, segment_size)?;
+    let ciphertext_segment_size = decryptor.ciphertext_segment_size();
+    let mut out = Vec::with_capacity(ciphertext.len());
+    while ciphertext.len() > ciphertext_segment_size {
+        let (segment, rest) = ciphertext.split_at(ciphertext_segment_size);
+        out.extend(decryptor.decrypt_next(segment, ad)?);
+        ciphertext = rest;
+    }
+    out.extend(decryptor.decrypt_last(ciphertext, ad)?);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stream() {
+        let ad = b"ad";
+        for alg in Algorithm::ALL {
+            let key = alg.generate_key();
+            for len in [0, 1, 15, 16, 17, 48, 100] {
+                let msg: Vec<u8> = (0..len as u8).collect();
+                let ciphertext = encrypt(alg, &key, 16, &msg, ad).unwrap();
+                assert_eq!(decrypt(alg, &key, 16, &ciphertext, ad).unwrap(), msg);
+                assert_eq!(
+                    decrypt(alg, &key, 16, &ciphertext, b""),
+                    Err(Error::VerificationFailed)
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "gcm")]
+    #[test]
+    fn stream_segments() {
+        let alg = Algorithm::Aes128Gcm;
+        let key: Vec<u8> = (0..16).collect();
+        let nonce_prefix: Vec<u8> = (0..7).collect();
+        let mut encryptor = Encryptor::with_nonce_prefix(alg, &key, &nonce_prefix, 4).unwrap();
+        assert_eq!(
+            encryptor.encrypt_next(b"hel", b""),
+            Err(Error::InvalidParameters)
+        );
+        let c0 = encryptor.encrypt_next(b"hell", b"").unwrap();
+        let c1 = encryptor.encrypt_next(b"o wo", b"").unwrap();
+        let c2 = encryptor.encrypt_last(b"rld", b"").unwrap();
+
+        let nonce = [&nonce_prefix[..], &[0, 0, 0, 1, 0]].concat();
+        assert_eq!(c1, alg.encrypt(b"o wo", b"", &key, &nonce).unwrap());
+        let nonce = [&nonce_prefix[..], &[0, 0, 0, 2, 1]].concat();
+        assert_eq!(c2, alg.encrypt(b"rld", b"", &key, &nonce).unwrap());
+
+        let decryptor = Decryptor::new(alg, &key, &nonce_prefix, 4).unwrap();
+        assert_eq!(
+            decryptor.decrypt_segment(1, false, &c1, b"").unwrap(),
+            b"o wo"
+        );
+        assert_eq!(
+            decryptor.decrypt_segment(2, true, &c2, b"").unwrap(),
+            b"rld"
+        );
+        assert_eq!(
+            decryptor.decrypt_segment(0, false, &c1, b""),
+            Err(Error::VerificationFailed)
+        );
+
+        // Reordering
+        let mut d = decryptor.clone();
+        assert_eq!(d.decrypt_next(&c1, b""), Err(Error::VerificationFailed));
+
+        // Truncation
+        let mut d = decryptor.clone();
+        d.decrypt_next(&c0, b"").unwrap();
+        assert_eq!(d.decrypt_last(&c1, b""), Err(Error::VerificationFailed));
+
+        // Extension
+        let mut d = decryptor.clone();
+        d.decrypt_next(&c0, b"").unwrap();
+        d.decrypt_next(&c1, b"").unwrap();
+        assert_eq!(d.decrypt_next(&c2, b""), Err(Error::VerificationFailed));
+
+        let mut d = decryptor;
+        let mut plaintext = d.decrypt_next(&c0, b"").unwrap();
+        plaintext.extend(d.decrypt_next(&c1, b"").unwrap());
+        plaintext.extend(d.decrypt_last(&c2, b"").unwrap());
+        assert_eq!(plaintext, b"hello world");
+
+        let ciphertext = [&nonce_prefix[..], &c0, &c1].concat();
+        assert_eq!(
+            decrypt(alg, &key, 4, ciphertext, b""),
+            Err(Error::VerificationFailed)
+        );
+        assert_eq!(
+            Encryptor::with_nonce_prefix(alg, &key, &nonce_prefix[1..], 4).err(),
+            Some(Error::InvalidNonceLength)
+        );
+        assert!(Encryptor::new(alg, &key, 0).is_err());
+    }
+}
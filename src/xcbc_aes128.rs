@@ -0,0 +1,304 @@
+//! AES-XCBC-MAC-96 and AES-XCBC-PRF-128 for WASI (WebAssembly System Interface).
+//!
+//! Provides message authentication using the three-key CBC-MAC construction with AES-128,
+//! as specified by RFC 3566 (AES-XCBC-MAC-96) and RFC 4434 (AES-XCBC-PRF-128).
+//!
+//! ## Example
+//! ```rust
+//! use aes_wasm::xcbc_aes128::{mac, verify, generate_key};
+//! let key = generate_key();
+//! let msg = b"hello";
+//! let tag = mac(msg, &key);
+//! assert!(verify(&tag, msg, &key).is_ok());
+//! ```
+
+use crate::soft::Aes;
+pub use crate::*;
+
+/// The length of the key in bytes.
+///
+/// This constant is used for key array sizing.
+pub const KEY_LEN: usize = 16;
+/// The length of the truncated AES-XCBC-MAC-96 authentication tag in bytes.
+///
+/// This constant is used for tag array sizing.
+pub const TAG_LEN: usize = 12;
+/// The length of the AES-XCBC-PRF-128 output in bytes.
+///
+/// This constant is used for PRF output array sizing.
+pub const PRF_LEN: usize = 16;
+
+/// Key type for AES-XCBC (16 bytes).
+pub type Key = [u8; KEY_LEN];
+/// Tag type for AES-XCBC-MAC-96 (12 bytes).
+pub type Tag = [u8; TAG_LEN];
+/// Output type for AES-XCBC-PRF-128 (16 bytes).
+pub type PrfOutput = [u8; PRF_LEN];
+
+/// Generates a random key using the system's secure random number generator.
+///
+/// # Returns
+/// A new random key.
+///
+/// # Example
+/// ```
+/// use aes_wasm::xcbc_aes128::generate_key;
+/// let key = generate_key();
+/// ```
+#[cfg(feature = "getrandom")]
+pub fn generate_key() -> Key {
+    let mut key = [0u8; KEY_LEN];
+    random_bytes(&mut key);
+    key
+}
+
+type Block = [u8; 16];
+
+fn xor(a: &mut Block, b: &[u8]) {
+    a.iter_mut().zip(b).for_each(|(a, b)| *a ^= b);
+}
+
+/// Incremental AES-XCBC state.
+///
+/// # Example
+/// ```
+/// use aes_wasm::xcbc_aes128::{mac, Key, XcbcAes128};
+/// let key = Key::default();
+/// let mut st = XcbcAes128::new(&key);
+/// st.update(b"hello ");
+/// st.update(b"world");
+/// assert_eq!(st.finalize(), mac(b"hello world", &key));
+/// ```
+#[derive(Clone)]
+pub struct XcbcAes128 {
+    k1: Aes,
+    k2: Block,
+    k3: Block,
+    e: Block,
+    buf: Block,
+    buf_len: usize,
+}
+
+impl XcbcAes128 {
+    /// Creates a new AES-XCBC state for the given key.
+    pub fn new(key: &Key) -> Self {
+        let mut k = [[1u8; 16], [2u8; 16], [3u8; 16]];
+        Aes::new(key).encrypt_blocks(&mut k);
+        XcbcAes128 {
+            k1: Aes::new(&k[0]),
+            k2: k[1],
+            k3: k[2],
+            e: Block::default(),
+            buf: Block::default(),
+            buf_len: 0,
+        }
+    }
+
+    /// Creates a new AES-XCBC-PRF-128 state for a key of any length (RFC 4434).
+    ///
+    /// Shorter keys are padded with zeros, longer keys are first compressed
+    /// with AES-XCBC-PRF-128 using an all-zero key.
+    pub fn new_prf(key: impl AsRef<[u8]>) -> Self {
+        let key = key.as_ref();
+        let mut k = Key::default();
+        if key.len() <= KEY_LEN {
+            k[..key.len()].copy_from_slice(key);
+        } else {
+            k = prf(key, Key::default());
+        }
+        Self::new(&k)
+    }
+
+    /// Absorbs more data into the state.
+    pub fn update(&mut self, input: impl AsRef<[u8]>) {
+        let mut input = input.as_ref();
+        while !input.is_empty() {
+            if self.buf_len == self.buf.len() {
+                xor(&mut self.e, &self.buf);
+                self.k1.encrypt_block(&mut self.e);
+                self.buf_len = 0;
+            }
+            let n = (self.buf.len() - self.buf_len).min(input.len());
+            self.buf[self.buf_len..][..n].copy_from_slice(&input[..n]);
+            self.buf_len += n;
+            input = &input[n..];
+        }
+    }
+
+    /// Computes the full 128-bit AES-XCBC-PRF-128 output.
+    pub fn finalize_prf(mut self) -> PrfOutput {
+        if self.buf_len == self.buf.len() {
+            xor(&mut self.e, &self.buf);
+            xor(&mut self.e, &self.k2);
+        } else {
+            self.buf[self.buf_len] = 0x80;
+            self.buf[self.buf_len + 1..].fill(0);
+            xor(&mut self.e, &self.buf);
+            xor(&mut self.e, &self.k3);
+        }
+        self.k1.encrypt_block(&mut self.e);
+        self.e
+    }
+
+    /// Computes the truncated AES-XCBC-MAC-96 authentication tag.
+    pub fn finalize(self) -> Tag {
+        self.finalize_prf()[..TAG_LEN].try_into().unwrap()
+    }
+
+    /// Computes the authentication tag and compares it with `expected` in constant time.
+    pub fn verify(self, expected: &Tag) -> Result<(), Error> {
+        if !ct_eq(&self.finalize(), expected) {
+            return Err(Error::VerificationFailed);
+        }
+        Ok(())
+    }
+}
+
+/// Computes the AES-XCBC-MAC-96 tag for a message.
+///
+/// # Arguments
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// Authentication tag as a 12-byte array.
+///
+/// # Example
+/// ```
+/// use aes_wasm::xcbc_aes128::{mac, Key};
+/// let key = Key::default();
+/// let msg = b"hello";
+/// let tag = mac(msg, &key);
+/// ```
+pub fn mac(msg: impl AsRef<[u8]>, key: &Key) -> Tag {
+    let mut st = XcbcAes128::new(key);
+    st.update(msg);
+    st.finalize()
+}
+
+/// Verifies an AES-XCBC-MAC-96 tag in constant time.
+///
+/// # Arguments
+/// * `tag` - The expected authentication tag.
+/// * `msg` - The message to authenticate.
+/// * `key` - Reference to the secret key.
+///
+/// # Returns
+/// `Ok(())` if the tag is valid, or `Err(Error)` if it is not.
+///
+/// # Example
+/// ```
+/// use aes_wasm::xcbc_aes128::{mac, verify, Key};
+/// let key = Key::default();
+/// let msg = b"hello";
+/// let tag = mac(msg, &key);
+/// assert!(verify(&tag, msg, &key).is_ok());
+/// ```
+pub fn verify(tag: &Tag, msg: impl AsRef<[u8]>, key: &Key) -> Result<(), Error> {
+    let mut st = XcbcAes128::new(key);
+    st.update(msg);
+    st.verify(tag)
+}
+
+/// Computes AES-XCBC-PRF-128 (RFC 4434) for a message, using a key of any length.
+///
+/// # Arguments
+/// * `msg` - The input message.
+/// * `key` - The secret key, of any length.
+///
+/// # Returns
+/// PRF output as a 16-byte array.
+///
+/// # Example
+/// ```
+/// use aes_wasm::xcbc_aes128::prf;
+/// let out = prf(b"hello", b"a variable-length key");
+/// ```
+pub fn prf(msg: impl AsRef<[u8]>, key: impl AsRef<[u8]>) -> PrfOutput {
+    let mut st = XcbcAes128::new_prf(key);
+    st.update(msg);
+    st.finalize_prf()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn xcbc_aes128() {
+        let key: Key = core::array::from_fn(|i| i as u8);
+        let msg: Vec<u8> = (0..34).collect();
+        let vectors: [(&[u8], PrfOutput); 4] = [
+            (
+                &msg[..0],
+                [
+                    0x75, 0xf0, 0x25, 0x1d, 0x52, 0x8a, 0xc0, 0x1c, 0x45, 0x73, 0xdf, 0xd5, 0x84,
+                    0xd7, 0x9f, 0x29,
+                ],
+            ),
+            (
+                &msg[..3],
+                [
+                    0x5b, 0x37, 0x65, 0x80, 0xae, 0x2f, 0x19, 0xaf, 0xe7, 0x21, 0x9c, 0xee, 0xf1,
+                    0x72, 0x75, 0x6f,
+                ],
+            ),
+            (
+                &msg[..16],
+                [
+                    0xd2, 0xa2, 0x46, 0xfa, 0x34, 0x9b, 0x68, 0xa7, 0x99, 0x98, 0xa4, 0x39, 0x4f,
+                    0xf7, 0xa2, 0x63,
+                ],
+            ),
+            (
+                &msg[..34],
+                [
+                    0xbe, 0xcb, 0xb3, 0xbc, 0xcd, 0xb5, 0x18, 0xa3, 0x06, 0x77, 0xd5, 0x48, 0x1f,
+                    0xb6, 0xb4, 0xd8,
+                ],
+            ),
+        ];
+        for (msg, expected) in vectors {
+            assert_eq!(prf(msg, key), expected);
+            assert_eq!(mac(msg, &key), expected[..TAG_LEN]);
+            assert!(verify(expected[..TAG_LEN].try_into().unwrap(), msg, &key).is_ok());
+        }
+        let mut st = XcbcAes128::new(&key);
+        for chunk in [0u8; 1000].chunks(7) {
+            st.update(chunk);
+        }
+        assert_eq!(
+            st.finalize_prf(),
+            [
+                0xf0, 0xda, 0xfe, 0xe8, 0x95, 0xdb, 0x30, 0x25, 0x37, 0x61, 0x10, 0x3b, 0x5d, 0x84,
+                0x52, 0x8f
+            ]
+        );
+        let tag = mac(&msg, &key);
+        assert_eq!(
+            verify(&tag, &msg[1..], &key),
+            Err(Error::VerificationFailed)
+        );
+    }
+
+    #[test]
+    fn xcbc_aes128_prf_rfc4434() {
+        let msg: Vec<u8> = (0..20).collect();
+        let key: Vec<u8> = (0..10).collect();
+        assert_eq!(
+            prf(&msg, key),
+            [
+                0x0f, 0xa0, 0x87, 0xaf, 0x7d, 0x86, 0x6e, 0x76, 0x53, 0x43, 0x4e, 0x60, 0x2f, 0xdd,
+                0xe8, 0x35
+            ]
+        );
+        let key: Vec<u8> = (0..16).chain([0xed, 0xcb]).collect();
+        assert_eq!(
+            prf(&msg, key),
+            [
+                0x8c, 0xd3, 0xc9, 0x3a, 0xe5, 0x98, 0xa9, 0x80, 0x30, 0x06, 0xff, 0xb6, 0x7c, 0x40,
+                0xe9, 0xe4
+            ]
+        );
+    }
+}
@@ -0,0 +1,191 @@
+/*
+ * Smoke test of the C interface, compiled against include/aes_wasm.h and linked with the crate
+ * built as a static library:
+ *
+ *   cargo rustc --release --lib --features capi --crate-type staticlib
+ *   cc -std=c99 -Wall -Wextra -Werror -Iinclude tests/capi.c target/release/libaes_wasm.a \
+ *      -lpthread -ldl -lm -o target/capi && target/capi
+ */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "aes_wasm.h"
+
+typedef int (*aead_fn)(uint8_t *, size_t, size_t *, const uint8_t *, size_t, const uint8_t *,
+                       size_t, const uint8_t *, size_t, const uint8_t *, size_t);
+typedef int (*cipher_fn)(uint8_t *, size_t, size_t *, const uint8_t *, size_t, const uint8_t *,
+                         size_t, const uint8_t *, size_t);
+typedef int (*mac_fn)(uint8_t *, size_t, size_t *, const uint8_t *, size_t, const uint8_t *,
+                      size_t);
+typedef int (*verify_fn)(const uint8_t *, size_t, const uint8_t *, size_t, const uint8_t *,
+                         size_t);
+
+#define AEAD(M, P)                                                                \
+    { #M, aes_wasm_##M##_encrypt, aes_wasm_##M##_decrypt, AES_WASM_##P##_KEY_LEN, \
+      AES_WASM_##P##_NONCE_LEN, AES_WASM_##P##_TAG_LEN }
+#define CIPHER(M, P)                                                              \
+    { #M, aes_wasm_##M##_encrypt, aes_wasm_##M##_decrypt, AES_WASM_##P##_KEY_LEN, \
+      AES_WASM_##P##_IV_LEN }
+#define MAC(M, P)                                                                 \
+    { #M, aes_wasm_##M##_mac, aes_wasm_##M##_verify, AES_WASM_##P##_KEY_LEN,      \
+      AES_WASM_##P##_TAG_LEN }
+
+static const struct {
+    const char *name;
+    aead_fn     encrypt, decrypt;
+    size_t      key_len, nonce_len, tag_len;
+} aeads[] = {
+    AEAD(aegis128l, AEGIS128L),
+    AEAD(aegis128x2, AEGIS128X2),
+    AEAD(aegis128x4, AEGIS128X4),
+    AEAD(aegis256, AEGIS256),
+    AEAD(aegis256x2, AEGIS256X2),
+    AEAD(aegis256x4, AEGIS256X4),
+    AEAD(aes128gcm, AES128GCM),
+    AEAD(aes256gcm, AES256GCM),
+    AEAD(aes128ocb, AES128OCB),
+    AEAD(aes256ocb, AES256OCB),
+    AEAD(aes128cbc_hs256, AES128CBC_HS256),
+    AEAD(aes256cbc_hs512, AES256CBC_HS512),
+};
+
+static const struct {
+    const char *name;
+    cipher_fn   encrypt, decrypt;
+    size_t      key_len, iv_len;
+} ciphers[] = {
+    CIPHER(aes128cbc, AES128CBC),
+    CIPHER(aes256cbc, AES256CBC),
+    CIPHER(aes128ctr, AES128CTR),
+    CIPHER(aes256ctr, AES256CTR),
+};
+
+static const struct {
+    const char *name;
+    mac_fn      mac;
+    verify_fn   verify;
+    size_t      key_len, tag_len;
+} macs[] = {
+    MAC(cmac_aes128, CMAC_AES128),
+    MAC(pmac_aes128, PMAC_AES128),
+    MAC(xcbc_aes128, XCBC_AES128),
+};
+
+static int failures;
+
+#define CHECK(name, cond)                                                          \
+    do {                                                                           \
+        if (!(cond)) {                                                             \
+            fprintf(stderr, "%s: %s:%d: %s\n", name, __FILE__, __LINE__, #cond); \
+            failures++;                                                            \
+        }                                                                          \
+    } while (0)
+
+int
+main(void)
+{
+    static const uint8_t msg[] = "The quick brown fox jumps over the lazy dog";
+    static const uint8_t ad[]  = "associated data";
+    uint8_t              key[64], nonce[32], c[256], m[256], tag[64];
+    size_t               c_len, m_len, tag_len, i;
+
+    memset(key, 0x42, sizeof key);
+    memset(nonce, 0x24, sizeof nonce);
+
+    for (i = 0; i < sizeof aeads / sizeof aeads[0]; i++) {
+        const char *name = aeads[i].name;
+
+        CHECK(name, aeads[i].encrypt(NULL, 0, &c_len, msg, sizeof msg, ad, sizeof ad, nonce,
+                                     aeads[i].nonce_len, key,
+                                     aeads[i].key_len) == AES_WASM_ERROR_BUFFER_TOO_SMALL);
+        CHECK(name, c_len >= sizeof msg + aeads[i].tag_len);
+        CHECK(name, aeads[i].encrypt(c, sizeof c, &c_len, msg, sizeof msg, ad, sizeof ad, nonce,
+                                     aeads[i].nonce_len, key, aeads[i].key_len) == AES_WASM_OK);
+        CHECK(name, aeads[i].decrypt(m, sizeof m, &m_len, c, c_len, ad, sizeof ad, nonce,
+                                     aeads[i].nonce_len, key, aeads[i].key_len) == AES_WASM_OK);
+        CHECK(name, m_len == sizeof msg && memcmp(m, msg, m_len) == 0);
+        CHECK(name, aeads[i].decrypt(m, sizeof m, &m_len, c, c_len, NULL, 0, nonce,
+                                     aeads[i].nonce_len, key,
+                                     aeads[i].key_len) == AES_WASM_ERROR_VERIFICATION_FAILED);
+        CHECK(name, aeads[i].encrypt(c, sizeof c, &c_len, msg, sizeof msg, ad, sizeof ad, nonce,
+                                     aeads[i].nonce_len, key, aeads[i].key_len - 1) ==
+                        AES_WASM_ERROR_INVALID_KEY_LENGTH);
+    }
+
+    for (i = 0; i < sizeof ciphers / sizeof ciphers[0]; i++) {
+        const char *name = ciphers[i].name;
+
+        CHECK(name, ciphers[i].encrypt(c, sizeof c, &c_len, msg, sizeof msg, nonce,
+                                       ciphers[i].iv_len, key, ciphers[i].key_len) == AES_WASM_OK);
+        CHECK(name, ciphers[i].decrypt(m, sizeof m, &m_len, c, c_len, nonce, ciphers[i].iv_len,
+                                       key, ciphers[i].key_len) == AES_WASM_OK);
+        CHECK(name, m_len == sizeof msg && memcmp(m, msg, m_len) == 0);
+        CHECK(name, ciphers[i].encrypt(c, sizeof c, &c_len, msg, sizeof msg, nonce,
+                                       ciphers[i].iv_len - 1, key, ciphers[i].key_len) ==
+                        AES_WASM_ERROR_INVALID_NONCE_LENGTH);
+    }
+
+    for (i = 0; i < sizeof macs / sizeof macs[0]; i++) {
+        const char *name = macs[i].name;
+
+        CHECK(name, macs[i].mac(tag, sizeof tag, &tag_len, msg, sizeof msg, key,
+                                macs[i].key_len) == AES_WASM_OK);
+        CHECK(name, tag_len == macs[i].tag_len);
+        CHECK(name, macs[i].verify(tag, tag_len, msg, sizeof msg, key, macs[i].key_len) ==
+                        AES_WASM_OK);
+        tag[0] ^= 1;
+        CHECK(name, macs[i].verify(tag, tag_len, msg, sizeof msg, key, macs[i].key_len) ==
+                        AES_WASM_ERROR_VERIFICATION_FAILED);
+    }
+
+    CHECK("poly1305_aes",
+          aes_wasm_poly1305_aes_mac(tag, sizeof tag, &tag_len, msg, sizeof msg, nonce,
+                                    AES_WASM_POLY1305_AES_NONCE_LEN, key,
+                                    AES_WASM_POLY1305_AES_KEY_LEN) == AES_WASM_OK);
+    CHECK("poly1305_aes", aes_wasm_poly1305_aes_verify(tag, tag_len, msg, sizeof msg, nonce,
+                                                       AES_WASM_POLY1305_AES_NONCE_LEN, key,
+                                                       AES_WASM_POLY1305_AES_KEY_LEN) ==
+                              AES_WASM_OK);
+
+    /* AES-128-GCM test case 2 from the GCM specification. */
+    {
+        static const uint8_t expected[] = {
+            0x03, 0x88, 0xda, 0xce, 0x60, 0xb6, 0xa3, 0x92, 0xf3, 0x28, 0xc2,
+            0xb9, 0x71, 0xb2, 0xfe, 0x78, 0xab, 0x6e, 0x47, 0xd4, 0x2c, 0xec,
+            0x13, 0xbd, 0xf5, 0x3a, 0x67, 0xb2, 0x12, 0x57, 0xbd, 0xdf,
+        };
+        uint8_t zero[16] = { 0 };
+
+        CHECK("aes128gcm", aes_wasm_aes128gcm_encrypt(c, sizeof c, &c_len, zero, 16, NULL, 0,
+                                                      zero, 12, zero, 16) == AES_WASM_OK);
+        CHECK("aes128gcm", c_len == sizeof expected && memcmp(c, expected, c_len) == 0);
+    }
+
+    /* CMAC-AES-128 example 2 from RFC 4493. */
+    {
+        static const uint8_t k[]        = { 0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6,
+                                            0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf, 0x4f, 0x3c };
+        static const uint8_t in[]       = { 0x6b, 0xc1, 0xbe, 0xe2, 0x2e, 0x40, 0x9f, 0x96,
+                                            0xe9, 0x3d, 0x7e, 0x11, 0x73, 0x93, 0x17, 0x2a };
+        static const uint8_t expected[] = { 0x07, 0x0a, 0x16, 0xb4, 0x6b, 0x4d, 0x41, 0x44,
+                                            0xf7, 0x9b, 0xdd, 0x9d, 0xd0, 0x4a, 0x28, 0x7c };
+
+        CHECK("cmac_aes128", aes_wasm_cmac_aes128_mac(tag, sizeof tag, &tag_len, in, sizeof in, k,
+                                                      sizeof k) == AES_WASM_OK);
+        CHECK("cmac_aes128", tag_len == sizeof expected && memcmp(tag, expected, tag_len) == 0);
+    }
+
+    {
+        uint8_t *buf = aes_wasm_alloc(100);
+
+        CHECK("alloc", buf != NULL && aes_wasm_alloc(0) == NULL);
+        aes_wasm_free(buf, 100);
+    }
+
+    if (failures != 0) {
+        return 1;
+    }
+    puts("ok");
+    return 0;
+}
@@ -1,10 +1,9 @@
 const std = @import("std");
-const cbc = @import("cbc");
 
 const Aes128Gcm = std.crypto.aead.aes_gcm.Aes128Gcm;
 const Aes256Gcm = std.crypto.aead.aes_gcm.Aes256Gcm;
-const Aes128Ocb = std.crypto.aead.aes_ocb.Aes128Ocb;
-const Aes256Ocb = std.crypto.aead.aes_ocb.Aes256Ocb;
+const Aes128Ocb = Ocb(std.crypto.aead.aes_ocb.Aes128Ocb, std.crypto.core.aes.Aes128);
+const Aes256Ocb = Ocb(std.crypto.aead.aes_ocb.Aes256Ocb, std.crypto.core.aes.Aes256);
 const Aegis128L = std.crypto.aead.aegis.Aegis128L_256;
 const Aegis128X2 = std.crypto.aead.aegis.Aegis128X2_256;
 const Aegis128X4 = std.crypto.aead.aegis.Aegis128X4_256;
@@ -12,12 +11,159 @@ const Aegis256 = std.crypto.aead.aegis.Aegis256_256;
 const Aegis256X2 = std.crypto.aead.aegis.Aegis256X2_256;
 const Aegis256X4 = std.crypto.aead.aegis.Aegis256X4_256;
 const CmacAes128 = std.crypto.auth.cmac.CmacAes128;
-const Aes128Cbc = cbc.CBC(std.crypto.core.aes.Aes128);
-const Aes256Cbc = cbc.CBC(std.crypto.core.aes.Aes256);
+const Aes128Cbc = Cbc(std.crypto.core.aes.Aes128);
+const Aes256Cbc = Cbc(std.crypto.core.aes.Aes256);
 const modes = std.crypto.core.modes;
 
 pub const std_options = std.Options{ .side_channels_mitigations = .none };
 
+/// AES-CBC with PKCS#7 padding.
+fn Cbc(comptime BlockCipher: type) type {
+    return struct {
+        const Self = @This();
+
+        enc: std.crypto.core.aes.AesEncryptCtx(BlockCipher),
+        dec: std.crypto.core.aes.AesDecryptCtx(BlockCipher),
+
+        fn init(key: [BlockCipher.key_bits / 8]u8) Self {
+            return .{ .enc = BlockCipher.initEnc(key), .dec = BlockCipher.initDec(key) };
+        }
+
+        /// Encrypts `src` into `dst`, which must be `(src.len / 16 + 1) * 16` bytes long.
+        fn encrypt(self: Self, dst: []u8, src: []const u8, iv: [16]u8) void {
+            std.debug.assert(dst.len == (src.len / 16 + 1) * 16);
+            const pad: u8 = @intCast(dst.len - src.len);
+            var chaining = iv;
+            var i: usize = 0;
+            while (i < dst.len) : (i += 16) {
+                var block: [16]u8 = undefined;
+                if (i + 16 <= src.len) {
+                    block = src[i..][0..16].*;
+                } else {
+                    @memset(&block, pad);
+                    @memcpy(block[0 .. src.len - i], src[i..]);
+                }
+                for (&block, chaining) |*b, c| b.* ^= c;
+                self.enc.encrypt(&chaining, &block);
+                dst[i..][0..16].* = chaining;
+            }
+        }
+
+        /// Decrypts `src` into `dst`, which must be at least `src.len - 1` bytes long, and
+        /// returns the length of the plaintext.
+        ///
+        /// The whole padding is checked, in constant time.
+        fn decrypt(self: Self, dst: []u8, src: []const u8, iv: [16]u8) error{InvalidPadding}!usize {
+            if (src.len == 0 or src.len % 16 != 0 or dst.len < src.len - 1) {
+                return error.InvalidPadding;
+            }
+            var chaining = iv;
+            var last: [16]u8 = undefined;
+            var i: usize = 0;
+            while (i < src.len) : (i += 16) {
+                const block = src[i..][0..16].*;
+                self.dec.decrypt(&last, &block);
+                for (&last, chaining) |*b, c| b.* ^= c;
+                if (i + 16 < src.len) {
+                    dst[i..][0..16].* = last;
+                }
+                chaining = block;
+            }
+            const pad = last[15];
+            var bad: u8 = ((pad -% 1) >> 7) | ((16 -% pad) >> 7);
+            for (last, 0..) |b, j| {
+                const in_pad = (((15 - @as(u8, @intCast(j))) -% pad) >> 7) & 1;
+                bad |= in_pad * (b ^ pad);
+            }
+            if (bad != 0) {
+                return error.InvalidPadding;
+            }
+            const len = src.len - pad;
+            const last_len = 16 - pad;
+            @memcpy(dst[len - last_len .. len], last[0..last_len]);
+            return len;
+        }
+    };
+}
+
+/// AES-OCB, with the associated data hashed as specified by RFC 7253.
+///
+/// `std.crypto.aead.aes_ocb` pads a partial last block of associated data with 0x01 instead of
+/// 0x80. Messages are processed without associated data, and the hash of the associated data is
+/// added to the tag here instead.
+fn Ocb(comptime Aead: type, comptime BlockCipher: type) type {
+    return struct {
+        const key_length = Aead.key_length;
+        const nonce_length = Aead.nonce_length;
+        const tag_length = Aead.tag_length;
+
+        fn double(l: [16]u8) [16]u8 {
+            const x = std.mem.readInt(u128, &l, .big);
+            var out: [16]u8 = undefined;
+            std.mem.writeInt(u128, &out, (x << 1) ^ (0x87 & (0 -% (x >> 127))), .big);
+            return out;
+        }
+
+        /// HASH(K, A), from section 4.1 of RFC 7253.
+        fn hash(ad: []const u8, key: [key_length]u8) [16]u8 {
+            const aes = BlockCipher.initEnc(key);
+            const zeros = [_]u8{0} ** 16;
+            var l_star: [16]u8 = undefined;
+            aes.encrypt(&l_star, &zeros);
+            var l: [@bitSizeOf(usize)][16]u8 = undefined;
+            l[0] = double(double(l_star));
+            for (1..l.len) |j| {
+                l[j] = double(l[j - 1]);
+            }
+            var sum = zeros;
+            var offset = zeros;
+            var i: usize = 0;
+            while (i + 16 <= ad.len) : (i += 16) {
+                for (&offset, l[@ctz(i / 16 + 1)]) |*o, x| o.* ^= x;
+                var block = ad[i..][0..16].*;
+                for (&block, offset) |*b, o| b.* ^= o;
+                aes.encrypt(&block, &block);
+                for (&sum, block) |*s, b| s.* ^= b;
+            }
+            if (i < ad.len) {
+                for (&offset, l_star) |*o, x| o.* ^= x;
+                var block = zeros;
+                @memcpy(block[0 .. ad.len - i], ad[i..]);
+                block[ad.len - i] = 0x80;
+                for (&block, offset) |*b, o| b.* ^= o;
+                aes.encrypt(&block, &block);
+                for (&sum, block) |*s, b| s.* ^= b;
+            }
+            return sum;
+        }
+
+        fn encrypt(
+            c: []u8,
+            tag: *[tag_length]u8,
+            m: []const u8,
+            ad: []const u8,
+            nonce: [nonce_length]u8,
+            key: [key_length]u8,
+        ) void {
+            Aead.encrypt(c, tag, m, "", nonce, key);
+            for (tag, hash(ad, key)) |*t, h| t.* ^= h;
+        }
+
+        fn decrypt(
+            m: []u8,
+            c: []const u8,
+            tag: [tag_length]u8,
+            ad: []const u8,
+            nonce: [nonce_length]u8,
+            key: [key_length]u8,
+        ) !void {
+            var expected = tag;
+            for (&expected, hash(ad, key)) |*t, h| t.* ^= h;
+            try Aead.decrypt(m, c, expected, "", nonce, key);
+        }
+    };
+}
+
 // AES128-GCM
 
 export fn aes128gcm_encrypt(
@@ -170,8 +316,8 @@ export fn aes128cbc_decrypt(
     k: [*c]const [16]u8,
 ) callconv(.C) i32 {
     const z = Aes128Cbc.init(k.*);
-    const trimmed = z.decryptAndTrim(m[0..m_len], c[0..c_len], iv.*) catch return -1;
-    return std.math.cast(i32, trimmed.len) orelse return -1;
+    const len = z.decrypt(m[0..m_len], c[0..c_len], iv.*) catch return -1;
+    return std.math.cast(i32, len) orelse return -1;
 }
 
 // AES256-CBC
@@ -198,8 +344,8 @@ export fn aes256cbc_decrypt(
     k: [*c]const [32]u8,
 ) callconv(.C) i32 {
     const z = Aes256Cbc.init(k.*);
-    const trimmed = z.decryptAndTrim(m[0..m_len], c[0..c_len], iv.*) catch return -1;
-    return std.math.cast(i32, trimmed.len) orelse return -1;
+    const len = z.decrypt(m[0..m_len], c[0..c_len], iv.*) catch return -1;
+    return std.math.cast(i32, len) orelse return -1;
 }
 
 // AEGIS-128L
@@ -12,11 +12,6 @@ pub fn build(b: *std.Build) void {
         .preferred_optimize_mode = .ReleaseFast,
     });
 
-    const cbc = b.dependency("cbc", .{
-        .target = target,
-        .optimize = optimize,
-    });
-
     const lib = b.addStaticLibrary(.{
         .name = "aes",
         .root_source_file = b.path("aes.zig"),
@@ -24,8 +19,6 @@ pub fn build(b: *std.Build) void {
         .optimize = optimize,
         .strip = true,
     });
-    lib.root_module.addImport("cbc", cbc.module("cbc"));
-    lib.linkLibrary(cbc.artifact("cbc"));
     b.installArtifact(lib);
 
     const exe = b.addExecutable(.{
@@ -38,7 +31,5 @@ pub fn build(b: *std.Build) void {
     exe.rdynamic = true;
     exe.wasi_exec_model = .reactor;
     exe.entry = .disabled;
-    exe.root_module.addImport("cbc", cbc.module("cbc"));
-    exe.linkLibrary(cbc.artifact("cbc"));
     b.installArtifact(exe);
 }